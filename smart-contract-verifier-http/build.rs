@@ -0,0 +1,7 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        // `optional` fields are experimental in protoc versions before 3.15
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .compile(&["proto/smart_contract_verifier.proto"], &["proto"])?;
+    Ok(())
+}
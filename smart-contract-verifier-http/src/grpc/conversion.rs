@@ -0,0 +1,240 @@
+use super::proto::{
+    self, verify_response, VerifySolidityMultiPartRequest, VerifySolidityStandardJsonRequest,
+    VerifySourcifyRequest, VerifyVyperMultiPartRequest,
+};
+use crate::{
+    handlers::{solidity_multi_part, solidity_standard_json, sourcify, vyper_multi_part},
+    verification_response::{
+        ContractError, VerificationResponse, VerificationResult, VerificationStatus,
+    },
+};
+use actix_web::http::StatusCode;
+use std::path::PathBuf;
+use tonic::Status;
+
+/// Bad requests are reported as `INVALID_ARGUMENT`, all other errors as `INTERNAL`.
+pub fn into_status(err: actix_web::Error) -> Status {
+    let message = err.to_string();
+    match err.as_response_error().status_code() {
+        StatusCode::BAD_REQUEST => Status::invalid_argument(message),
+        _ => Status::internal(message),
+    }
+}
+
+impl TryFrom<VerifySolidityMultiPartRequest> for solidity_multi_part::VerificationRequest {
+    type Error = Status;
+
+    fn try_from(request: VerifySolidityMultiPartRequest) -> Result<Self, Self::Error> {
+        let optimizer_details = request
+            .optimizer_details
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|err| {
+                Status::invalid_argument(format!("Invalid optimizer details: {}", err))
+            })?;
+        Ok(Self {
+            deployed_bytecode: request.deployed_bytecode,
+            creation_bytecode: request.creation_bytecode,
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version: request.compiler_version,
+            contract_name: request.contract_name,
+            normalize_sources: request.normalize_sources,
+            artifacts: Some(request.artifacts),
+            callback_url: None,
+            content: solidity_multi_part::MultiPartFiles {
+                sources: request
+                    .sources
+                    .into_iter()
+                    .map(|(path, content)| (PathBuf::from(path), content))
+                    .collect(),
+                evm_version: request.evm_version,
+                optimization: request.optimization,
+                optimization_runs: request.optimization_runs.map(|runs| runs as usize),
+                optimizer_details,
+                via_ir: request.via_ir,
+                remappings: Some(request.remappings),
+                contract_libraries: (!request.contract_libraries.is_empty())
+                    .then(|| request.contract_libraries.into_iter().collect()),
+            },
+        })
+    }
+}
+
+impl From<VerifySolidityStandardJsonRequest> for solidity_standard_json::VerificationRequest {
+    fn from(request: VerifySolidityStandardJsonRequest) -> Self {
+        Self {
+            deployed_bytecode: request.deployed_bytecode,
+            creation_bytecode: request.creation_bytecode,
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version: request.compiler_version,
+            contract_name: request.contract_name,
+            normalize_sources: request.normalize_sources,
+            artifacts: Some(request.artifacts),
+            callback_url: None,
+            content: solidity_standard_json::StandardJson {
+                input: request.input,
+            },
+        }
+    }
+}
+
+impl From<VerifyVyperMultiPartRequest> for vyper_multi_part::VerificationRequest {
+    fn from(request: VerifyVyperMultiPartRequest) -> Self {
+        Self {
+            deployed_bytecode: request.deployed_bytecode,
+            creation_bytecode: request.creation_bytecode,
+            compiler_version: request.compiler_version,
+            contract_name: request.contract_name,
+            normalize_sources: request.normalize_sources,
+            artifacts: Some(request.artifacts),
+            callback_url: None,
+            content: vyper_multi_part::MultiPartFiles {
+                sources: request
+                    .sources
+                    .into_iter()
+                    .map(|(path, content)| (PathBuf::from(path), content))
+                    .collect(),
+                evm_version: request.evm_version,
+            },
+        }
+    }
+}
+
+impl From<VerifySourcifyRequest> for sourcify::ApiRequest {
+    fn from(request: VerifySourcifyRequest) -> Self {
+        Self {
+            address: request.address,
+            chain: request.chain,
+            files: request.files.into_iter().collect(),
+            chosen_contract: request.chosen_contract.map(|index| index as usize),
+        }
+    }
+}
+
+impl From<VerificationResponse> for proto::VerifyResponse {
+    fn from(response: VerificationResponse) -> Self {
+        let status = match response.status {
+            VerificationStatus::Ok => verify_response::Status::Ok,
+            VerificationStatus::Failed => verify_response::Status::Failed,
+        };
+        Self {
+            message: response.message,
+            status: status as i32,
+            result: response.result.map(proto::VerificationResult::from),
+            errors: response
+                .errors
+                .into_iter()
+                .map(proto::ContractError::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<VerificationResult> for proto::VerificationResult {
+    fn from(result: VerificationResult) -> Self {
+        Self {
+            file_name: result.file_name,
+            contract_name: result.contract_name,
+            compiler_version: result.compiler_version,
+            evm_version: result.evm_version,
+            constructor_arguments: result.constructor_arguments.map(|args| args.to_string()),
+            optimization: result.optimization,
+            optimization_runs: result.optimization_runs.map(|runs| runs as u32),
+            contract_libraries: result.contract_libraries.into_iter().collect(),
+            abi: result.abi,
+            sources: result.sources.into_iter().collect(),
+            is_destroyed: result.is_destroyed,
+            append_cbor: result.append_cbor,
+            external_sources: result.external_sources,
+            source_normalization: result.source_normalization,
+            artifacts: result.artifacts.map(|artifacts| {
+                serde_json::to_string(&artifacts).expect("artifacts are valid json values")
+            }),
+        }
+    }
+}
+
+impl From<ContractError> for proto::ContractError {
+    fn from(error: ContractError) -> Self {
+        Self {
+            file_name: error.file_name,
+            contract_name: error.contract_name,
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::error;
+    use pretty_assertions::assert_eq;
+    use std::collections::{BTreeMap, HashMap};
+    use tonic::Code;
+
+    #[test]
+    fn multi_part_request() {
+        let request = VerifySolidityMultiPartRequest {
+            deployed_bytecode: "0x6001".into(),
+            creation_bytecode: "0x6002".into(),
+            compiler_version: "v0.8.17+commit.8df45f5f".into(),
+            sources: HashMap::from([("source.sol".into(), "pragma".into())]),
+            evm_version: "london".into(),
+            optimization_runs: Some(200),
+            optimizer_details: Some(r#"{"yul": true}"#.into()),
+            remappings: vec!["@lib=lib".into()],
+            ..Default::default()
+        };
+        let request = solidity_multi_part::VerificationRequest::try_from(request).unwrap();
+        assert_eq!(
+            BTreeMap::from([(PathBuf::from("source.sol"), "pragma".to_string())]),
+            request.content.sources
+        );
+        assert_eq!(Some(200), request.content.optimization_runs);
+        assert_eq!(
+            Some(true),
+            request
+                .content
+                .optimizer_details
+                .and_then(|details| details.yul)
+        );
+        assert_eq!(None, request.content.contract_libraries);
+
+        let request = VerifySolidityMultiPartRequest {
+            optimizer_details: Some("invalid".into()),
+            ..Default::default()
+        };
+        let status = solidity_multi_part::VerificationRequest::try_from(request).unwrap_err();
+        assert_eq!(Code::InvalidArgument, status.code());
+    }
+
+    #[test]
+    fn verification_response() {
+        let response = VerificationResponse {
+            errors: vec![ContractError {
+                file_name: "Main.sol".into(),
+                contract_name: Some("Main".into()),
+                code: "bytecode_mismatch".into(),
+                message: "bytecode does not match compilation output".into(),
+            }],
+            ..VerificationResponse::err("No contract could be verified with provided data")
+        };
+        let response = proto::VerifyResponse::from(response);
+        assert_eq!(verify_response::Status::Failed as i32, response.status);
+        assert_eq!(None, response.result);
+        assert_eq!("bytecode_mismatch", response.errors[0].code);
+    }
+
+    #[test]
+    fn errors_to_status() {
+        let status = into_status(error::ErrorBadRequest("Invalid bytecode"));
+        assert_eq!(Code::InvalidArgument, status.code());
+        assert_eq!("Invalid bytecode", status.message());
+        let status = into_status(error::ErrorInternalServerError("Compiler failed"));
+        assert_eq!(Code::Internal, status.code());
+    }
+}
@@ -0,0 +1,58 @@
+//! gRPC API mirroring the HTTP routes. Services share the compilers
+//! (and, thus, the compilation threads limit) with the HTTP server.
+
+mod conversion;
+mod services;
+
+pub mod proto {
+    tonic::include_proto!("blockscout.smart_contract_verifier.v1");
+}
+
+use crate::routers::AppRouter;
+use proto::{
+    solidity_verifier_server::SolidityVerifierServer,
+    sourcify_verifier_server::SourcifyVerifierServer, vyper_verifier_server::VyperVerifierServer,
+};
+use services::{SolidityVerifierService, SourcifyVerifierService, VyperVerifierService};
+use tonic::transport::{server::Router, Server};
+
+/// Services of the languages disabled in the settings are not registered.
+/// Standard `grpc.health.v1.Health` service reports the server (the empty service name)
+/// and each of the registered services as serving.
+pub async fn server(app_router: &AppRouter) -> Router {
+    let solidity = app_router.solidity().map(|router| {
+        SolidityVerifierServer::new(SolidityVerifierService::new(
+            router.compilers(),
+            router.packages(),
+        ))
+    });
+    let vyper = app_router
+        .vyper()
+        .map(|router| VyperVerifierServer::new(VyperVerifierService::new(router.compilers())));
+    let sourcify = app_router.sourcify().map(|router| {
+        SourcifyVerifierServer::new(SourcifyVerifierService::new(router.api_client()))
+    });
+
+    let (mut health_reporter, health_server) = tonic_health::server::health_reporter();
+    if solidity.is_some() {
+        health_reporter
+            .set_serving::<SolidityVerifierServer<SolidityVerifierService>>()
+            .await;
+    }
+    if vyper.is_some() {
+        health_reporter
+            .set_serving::<VyperVerifierServer<VyperVerifierService>>()
+            .await;
+    }
+    if sourcify.is_some() {
+        health_reporter
+            .set_serving::<SourcifyVerifierServer<SourcifyVerifierService>>()
+            .await;
+    }
+
+    Server::builder()
+        .add_service(health_server)
+        .add_optional_service(solidity)
+        .add_optional_service(vyper)
+        .add_optional_service(sourcify)
+}
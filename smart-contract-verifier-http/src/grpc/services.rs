@@ -0,0 +1,125 @@
+use super::{
+    conversion::into_status,
+    proto::{
+        solidity_verifier_server::SolidityVerifier, sourcify_verifier_server::SourcifyVerifier,
+        vyper_verifier_server::VyperVerifier, ListVersionsRequest, ListVersionsResponse,
+        VerifyResponse, VerifySolidityMultiPartRequest, VerifySolidityStandardJsonRequest,
+        VerifySourcifyRequest, VerifyVyperMultiPartRequest,
+    },
+};
+use crate::handlers::{solidity_multi_part, solidity_standard_json, sourcify, vyper_multi_part};
+use actix_web::web;
+use smart_contract_verifier::{
+    Compilers, PackageRegistry, SolidityCompiler, SourcifyApiClient, VyperCompiler,
+};
+use tonic::{Request, Response, Status};
+
+pub struct SolidityVerifierService {
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+}
+
+impl SolidityVerifierService {
+    pub fn new(
+        compilers: web::Data<Compilers<SolidityCompiler>>,
+        packages: Option<web::Data<PackageRegistry>>,
+    ) -> Self {
+        Self {
+            compilers,
+            packages,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl SolidityVerifier for SolidityVerifierService {
+    async fn verify_multi_part(
+        &self,
+        request: Request<VerifySolidityMultiPartRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let params = request.into_inner().try_into()?;
+        let response = solidity_multi_part::process_request(
+            self.compilers.clone(),
+            self.packages.clone(),
+            params,
+        )
+        .await
+        .map_err(into_status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn verify_standard_json(
+        &self,
+        request: Request<VerifySolidityStandardJsonRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let params = request.into_inner().into();
+        let response = solidity_standard_json::process_request(self.compilers.clone(), params)
+            .await
+            .map_err(into_status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn list_versions(
+        &self,
+        _request: Request<ListVersionsRequest>,
+    ) -> Result<Response<ListVersionsResponse>, Status> {
+        let versions = self.compilers.all_versions_sorted_str();
+        Ok(Response::new(ListVersionsResponse { versions }))
+    }
+}
+
+pub struct VyperVerifierService {
+    compilers: web::Data<Compilers<VyperCompiler>>,
+}
+
+impl VyperVerifierService {
+    pub fn new(compilers: web::Data<Compilers<VyperCompiler>>) -> Self {
+        Self { compilers }
+    }
+}
+
+#[tonic::async_trait]
+impl VyperVerifier for VyperVerifierService {
+    async fn verify_multi_part(
+        &self,
+        request: Request<VerifyVyperMultiPartRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let params = request.into_inner().into();
+        let response = vyper_multi_part::process_request(self.compilers.clone(), params)
+            .await
+            .map_err(into_status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn list_versions(
+        &self,
+        _request: Request<ListVersionsRequest>,
+    ) -> Result<Response<ListVersionsResponse>, Status> {
+        let versions = self.compilers.all_versions_sorted_str();
+        Ok(Response::new(ListVersionsResponse { versions }))
+    }
+}
+
+pub struct SourcifyVerifierService {
+    api_client: web::Data<SourcifyApiClient>,
+}
+
+impl SourcifyVerifierService {
+    pub fn new(api_client: web::Data<SourcifyApiClient>) -> Self {
+        Self { api_client }
+    }
+}
+
+#[tonic::async_trait]
+impl SourcifyVerifier for SourcifyVerifierService {
+    async fn verify(
+        &self,
+        request: Request<VerifySourcifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let params = request.into_inner().into();
+        let response = sourcify::process_request(self.api_client.clone(), params)
+            .await
+            .map_err(into_status)?;
+        Ok(Response::new(response.into()))
+    }
+}
@@ -0,0 +1,530 @@
+//! Verification of the project sources uploaded as a zip or tar.gz archive.
+//!
+//! The request is a `multipart/form-data` with the `archive` file and the `request` field
+//! containing the multi-part verification request without `sources`, which are collected
+//! from the archive instead.
+
+use super::{solidity_multi_part, vyper_multi_part};
+use crate::{settings::ArchiveSettings, verification_response::VerificationResponse};
+use actix_multipart::Multipart;
+use actix_web::{error, web, web::Json};
+use anyhow::{anyhow, Context};
+use flate2::read::GzDecoder;
+use futures::TryStreamExt;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde_json::{Map, Value};
+use smart_contract_verifier::{Compilers, PackageRegistry, SolidityCompiler, VyperCompiler};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    io::{Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+use tracing::instrument;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+
+const REMAPPINGS_TXT: &str = "remappings.txt";
+const FOUNDRY_TOML: &str = "foundry.toml";
+/// Files marking the root of the project, if it is put into a directory inside the archive
+const PROJECT_MARKERS: [&str; 5] = [
+    REMAPPINGS_TXT,
+    FOUNDRY_TOML,
+    "hardhat.config.js",
+    "hardhat.config.ts",
+    "package.json",
+];
+
+/// Schema of the form, used by the documentation only.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "ArchiveVerificationForm")]
+pub struct ArchiveForm {
+    /// Zip or tar.gz archive of the project directory
+    #[schemars(schema_with = "binary")]
+    archive: Vec<u8>,
+    /// Json of the corresponding multi-part verification request without `sources`
+    request: String,
+}
+
+fn binary(_: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(serde_json::json!({ "type": "string", "format": "binary" }))
+        .expect("valid schema")
+}
+
+#[instrument(skip(compilers, packages, settings, payload), level = "debug")]
+pub async fn verify_solidity(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    settings: web::Data<ArchiveSettings>,
+    payload: Multipart,
+) -> Result<Json<VerificationResponse>, actix_web::Error> {
+    let (mut request, project) = read_project(payload, settings, &["sol"]).await?;
+    if let Some(remappings) = project.remappings {
+        // Remappings of the request take precedence over the ones of the project
+        if request.get("remappings").map_or(true, Value::is_null) {
+            request.insert("remappings".into(), remappings.into());
+        }
+    }
+    let request: solidity_multi_part::VerificationRequest =
+        parse_request(request, project.sources)?;
+    solidity_multi_part::process_request(compilers, packages, request)
+        .await
+        .map(Json)
+}
+
+#[instrument(skip(compilers, settings, payload), level = "debug")]
+pub async fn verify_vyper(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    settings: web::Data<ArchiveSettings>,
+    payload: Multipart,
+) -> Result<Json<VerificationResponse>, actix_web::Error> {
+    let (request, project) = read_project(payload, settings, &["vy"]).await?;
+    let request: vyper_multi_part::VerificationRequest = parse_request(request, project.sources)?;
+    vyper_multi_part::process_request(compilers, request)
+        .await
+        .map(Json)
+}
+
+async fn read_project(
+    mut payload: Multipart,
+    settings: web::Data<ArchiveSettings>,
+    extensions: &'static [&'static str],
+) -> Result<(Map<String, Value>, Project), actix_web::Error> {
+    let mut request = None;
+    let mut archive = None;
+    while let Some(mut field) = payload.try_next().await? {
+        let name = field
+            .content_disposition()
+            .get_name()
+            .unwrap_or_default()
+            .to_string();
+        let mut content = Vec::new();
+        while let Some(chunk) = field.try_next().await? {
+            if content.len() + chunk.len() > settings.max_size {
+                return Err(error::ErrorPayloadTooLarge(format!(
+                    "`{}` exceeds the limit of {} bytes",
+                    name, settings.max_size
+                )));
+            }
+            content.extend_from_slice(&chunk);
+        }
+        match name.as_str() {
+            "archive" => archive = Some(content),
+            "request" => {
+                let value: Map<String, Value> = serde_json::from_slice(&content)
+                    .map_err(|err| error::ErrorBadRequest(format!("Invalid request: {}", err)))?;
+                request = Some(value);
+            }
+            _ => {
+                return Err(error::ErrorBadRequest(format!(
+                    "Unexpected field `{}`",
+                    name
+                )))
+            }
+        }
+    }
+    let request = request.ok_or_else(|| error::ErrorBadRequest("Missing `request` field"))?;
+    let archive = archive.ok_or_else(|| error::ErrorBadRequest("Missing `archive` field"))?;
+
+    // Decompression is cpu-bound, so is not run on the async runtime
+    let project = web::block(move || Project::unpack(&archive, extensions, &settings))
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| error::ErrorBadRequest(format!("{:#}", err)))?;
+    Ok((request, project))
+}
+
+fn parse_request<T: serde::de::DeserializeOwned>(
+    mut request: Map<String, Value>,
+    sources: BTreeMap<PathBuf, String>,
+) -> Result<T, actix_web::Error> {
+    if request.contains_key("sources") {
+        return Err(error::ErrorBadRequest(
+            "`sources` are taken from the archive and should not be specified",
+        ));
+    }
+    if sources.is_empty() {
+        return Err(error::ErrorBadRequest(
+            "No source files found in the archive",
+        ));
+    }
+    let sources = serde_json::to_value(sources).map_err(error::ErrorInternalServerError)?;
+    request.insert("sources".into(), sources);
+    serde_json::from_value(Value::Object(request))
+        .map_err(|err| error::ErrorBadRequest(format!("Invalid request: {}", err)))
+}
+
+/// Sources and settings collected from the archive.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Project {
+    /// Paths are relative to the root of the project. Files from the top-level
+    /// `node_modules` are put by the package name, as hardhat does
+    pub sources: BTreeMap<PathBuf, String>,
+    /// Taken from `remappings.txt`, or from the default profile of `foundry.toml`
+    pub remappings: Option<Vec<String>>,
+}
+
+impl Project {
+    /// Collects the files with the given `extensions` from the zip or tar.gz archive.
+    /// If the whole project is put into a single directory (e.g., archives downloaded
+    /// from GitHub), that directory is considered to be the root.
+    pub fn unpack(
+        archive: &[u8],
+        extensions: &[&str],
+        settings: &ArchiveSettings,
+    ) -> anyhow::Result<Self> {
+        let mut files = Files::new(extensions, settings);
+        if archive.starts_with(ZIP_MAGIC) {
+            let mut zip =
+                zip::ZipArchive::new(Cursor::new(archive)).context("invalid zip archive")?;
+            for i in 0..zip.len() {
+                let file = zip.by_index(i).context("invalid zip archive")?;
+                files.unpacked(file.size())?;
+                if file.is_dir() {
+                    continue;
+                }
+                let path = PathBuf::from(file.name());
+                files.add(&path, file)?;
+            }
+        } else if archive.starts_with(GZIP_MAGIC) {
+            let mut tar = tar::Archive::new(GzDecoder::new(archive));
+            for entry in tar.entries().context("invalid tar.gz archive")? {
+                let entry = entry.context("invalid tar.gz archive")?;
+                // Skipped entries are decompressed as well, so all of them are counted
+                files.unpacked(entry.header().size().context("invalid tar.gz archive")?)?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let path = entry.path().context("invalid tar.gz archive")?.into_owned();
+                files.add(&path, entry)?;
+            }
+        } else {
+            return Err(anyhow!("archive should be either zip or tar.gz"));
+        }
+        files.into_project()
+    }
+}
+
+/// Accumulates the files of the archive, checking the limits.
+struct Files<'a> {
+    extensions: &'a [&'a str],
+    settings: &'a ArchiveSettings,
+    count: usize,
+    size: usize,
+    unpacked_size: u64,
+    /// The first components of the paths of all the files
+    top_level: BTreeSet<OsString>,
+    /// Either sources or configuration files
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl<'a> Files<'a> {
+    fn new(extensions: &'a [&'a str], settings: &'a ArchiveSettings) -> Self {
+        Self {
+            extensions,
+            settings,
+            count: 0,
+            size: 0,
+            unpacked_size: 0,
+            top_level: BTreeSet::new(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Counts the size of the entry after decompression, whether it is added or not.
+    fn unpacked(&mut self, size: u64) -> anyhow::Result<()> {
+        self.unpacked_size = self.unpacked_size.saturating_add(size);
+        if self.unpacked_size > self.settings.max_unpacked_size as u64 {
+            return Err(anyhow!(
+                "unpacked archive exceeds {} bytes",
+                self.settings.max_unpacked_size
+            ));
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, path: &Path, content: impl Read) -> anyhow::Result<()> {
+        self.count += 1;
+        if self.count > self.settings.max_files {
+            return Err(anyhow!(
+                "archive contains more than {} files",
+                self.settings.max_files
+            ));
+        }
+        let path = normalize(path).ok_or_else(|| anyhow!("invalid path: {}", path.display()))?;
+        let mut components = path.components();
+        let first = components.next().map(|component| component.as_os_str());
+        match (first, components.next()) {
+            // Files at the top-level are not put into a common directory
+            (Some(_), None) => self.top_level.insert(OsString::new()),
+            (Some(first), Some(_)) => self.top_level.insert(first.to_os_string()),
+            (None, _) => return Ok(()),
+        };
+
+        let is_source = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| self.extensions.contains(&extension));
+        let is_config = path.components().count() <= 2
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| PROJECT_MARKERS.contains(&name));
+        if !is_source && !is_config {
+            return Ok(());
+        }
+
+        let remaining = self.settings.max_sources_size - self.size;
+        let mut buffer = Vec::new();
+        content
+            .take(remaining as u64 + 1)
+            .read_to_end(&mut buffer)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if buffer.len() > remaining {
+            return Err(anyhow!(
+                "total size of the sources exceeds {} bytes",
+                self.settings.max_sources_size
+            ));
+        }
+        self.size += buffer.len();
+        let content = String::from_utf8(buffer)
+            .map_err(|_| anyhow!("{} is not valid utf-8", path.display()))?;
+        self.files.insert(path, content);
+        Ok(())
+    }
+
+    fn into_project(self) -> anyhow::Result<Project> {
+        let root = match self.top_level.iter().collect::<Vec<_>>().as_slice() {
+            [dir] if !dir.is_empty() => {
+                let root = PathBuf::from(dir);
+                let has_marker = PROJECT_MARKERS
+                    .iter()
+                    .any(|marker| self.files.contains_key(&root.join(marker)));
+                has_marker.then_some(root)
+            }
+            _ => None,
+        };
+
+        let mut project = Project::default();
+        let mut configs = BTreeMap::new();
+        for (path, content) in self.files {
+            let path = match &root {
+                Some(root) => path.strip_prefix(root).map(Path::to_path_buf),
+                None => Ok(path),
+            }
+            .expect("all files are inside the root");
+            let is_source = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map_or(false, |extension| self.extensions.contains(&extension));
+            if is_source {
+                let path = match path.strip_prefix("node_modules") {
+                    Ok(package_path) => package_path.to_path_buf(),
+                    Err(_) => path,
+                };
+                project.sources.insert(path, content);
+            } else {
+                configs.insert(path, content);
+            }
+        }
+
+        project.remappings = match (
+            configs.get(Path::new(REMAPPINGS_TXT)),
+            configs.get(Path::new(FOUNDRY_TOML)),
+        ) {
+            (Some(remappings), _) => Some(
+                remappings
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(String::from)
+                    .collect(),
+            ),
+            (None, Some(foundry_toml)) => foundry_remappings(foundry_toml)?,
+            (None, None) => None,
+        };
+        Ok(project)
+    }
+}
+
+/// Returns remappings of the default profile of `foundry.toml`.
+fn foundry_remappings(foundry_toml: &str) -> anyhow::Result<Option<Vec<String>>> {
+    let config: toml::Value = toml::from_str(foundry_toml).context("invalid foundry.toml")?;
+    let remappings = match config
+        .get("profile")
+        .and_then(|profile| profile.get("default"))
+        .and_then(|profile| profile.get("remappings"))
+    {
+        Some(remappings) => remappings,
+        None => return Ok(None),
+    };
+    let remappings = remappings
+        .as_array()
+        .and_then(|remappings| {
+            remappings
+                .iter()
+                .map(|remapping| remapping.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| anyhow!("invalid foundry.toml: remappings should be a list of strings"))?;
+    Ok(Some(remappings))
+}
+
+/// Returns relative path without `.` components, or `None` if the path
+/// is absolute or points outside of the archive.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => normalized.push(component),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            zip.start_file(*path, Default::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn sources(files: &[(&str, &str)]) -> BTreeMap<PathBuf, String> {
+        files
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn unpack_hardhat_project() {
+        let archive = zip(&[
+            ("project/hardhat.config.ts", "export default {}"),
+            ("project/README.md", "# Project"),
+            ("project/contracts/A.sol", "import \"@oz/B.sol\";"),
+            ("project/node_modules/@oz/B.sol", "contract B {}"),
+        ]);
+        let project = Project::unpack(&archive, &["sol"], &Default::default()).unwrap();
+        assert_eq!(
+            Project {
+                sources: sources(&[
+                    ("@oz/B.sol", "contract B {}"),
+                    ("contracts/A.sol", "import \"@oz/B.sol\";"),
+                ]),
+                remappings: None,
+            },
+            project
+        );
+    }
+
+    #[test]
+    fn unpack_foundry_project() {
+        let foundry_toml = "[profile.default]\nremappings = [\"ds-test/=lib/ds-test/src/\"]\n";
+        let files = [
+            ("foundry.toml", foundry_toml),
+            ("src/A.sol", "contract A {}"),
+            ("lib/ds-test/src/test.sol", "contract DSTest {}"),
+        ];
+        let project = Project::unpack(&tar_gz(&files), &["sol"], &Default::default()).unwrap();
+        assert_eq!(
+            Some(vec!["ds-test/=lib/ds-test/src/".to_string()]),
+            project.remappings
+        );
+        assert_eq!(2, project.sources.len());
+
+        // `remappings.txt` takes precedence over `foundry.toml`
+        let files = [
+            ("foundry.toml", foundry_toml),
+            ("remappings.txt", "a/=lib/a/\n\n# comment\nb/=lib/b/\n"),
+            ("src/A.sol", "contract A {}"),
+        ];
+        let project = Project::unpack(&zip(&files), &["sol"], &Default::default()).unwrap();
+        assert_eq!(
+            Some(vec!["a/=lib/a/".to_string(), "b/=lib/b/".to_string()]),
+            project.remappings
+        );
+    }
+
+    #[test]
+    fn single_directory_without_markers_is_kept() {
+        let archive = zip(&[("contracts/A.sol", "contract A {}")]);
+        let project = Project::unpack(&archive, &["sol"], &Default::default()).unwrap();
+        assert_eq!(
+            sources(&[("contracts/A.sol", "contract A {}")]),
+            project.sources
+        );
+    }
+
+    #[test]
+    fn limits_are_checked() {
+        let settings = ArchiveSettings {
+            max_files: 2,
+            max_sources_size: 8,
+            ..Default::default()
+        };
+        let err = Project::unpack(
+            &zip(&[("a.txt", ""), ("b.txt", ""), ("c.sol", "")]),
+            &["sol"],
+            &settings,
+        )
+        .expect_err("too many files");
+        assert_eq!("archive contains more than 2 files", err.to_string());
+
+        let err = Project::unpack(&tar_gz(&[("a.sol", "contract A {}")]), &["sol"], &settings)
+            .expect_err("too large sources");
+        assert_eq!("total size of the sources exceeds 8 bytes", err.to_string());
+
+        let settings = ArchiveSettings {
+            max_unpacked_size: 8,
+            ..Default::default()
+        };
+        for archive in [
+            zip(&[("a.txt", "not a source")]),
+            tar_gz(&[("a.txt", "not a source")]),
+        ] {
+            let err =
+                Project::unpack(&archive, &["sol"], &settings).expect_err("too large archive");
+            assert_eq!("unpacked archive exceeds 8 bytes", err.to_string());
+        }
+
+        let err =
+            Project::unpack(b"not an archive", &["sol"], &settings).expect_err("unknown format");
+        assert_eq!("archive should be either zip or tar.gz", err.to_string());
+    }
+
+    #[test]
+    fn paths_outside_archive_are_rejected() {
+        assert_eq!(
+            Some(PathBuf::from("a/b.sol")),
+            normalize(Path::new("./a/b.sol"))
+        );
+        assert_eq!(None, normalize(Path::new("../b.sol")));
+        assert_eq!(None, normalize(Path::new("/etc/b.sol")));
+    }
+}
@@ -0,0 +1,187 @@
+use super::job;
+use crate::{
+    jobs::{JobRequest, JobRunner},
+    verification_response::VerificationResponse,
+};
+use actix_web::{error, web, web::Json, HttpResponse};
+use futures::{stream, StreamExt};
+use schemars::JsonSchema;
+use serde::Serialize;
+use smart_contract_verifier::{with_compilation_cache, CompilationCache};
+use std::{collections::HashMap, sync::Arc};
+use tracing::instrument;
+
+pub struct BatchVerifier {
+    pub runner: JobRunner,
+    /// Number of items verified concurrently
+    pub concurrency: usize,
+    pub max_items: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct BatchItemResult {
+    /// Position of the item in the request
+    pub index: usize,
+    pub response: Option<VerificationResponse>,
+    /// Reason the item could not be verified (e.g., invalid bytecode or internal error)
+    pub error: Option<String>,
+}
+
+impl BatchItemResult {
+    fn new(index: usize, result: Result<VerificationResponse, actix_web::Error>) -> Self {
+        match result {
+            Ok(response) => Self {
+                index,
+                response: Some(response),
+                error: None,
+            },
+            Err(err) => Self {
+                index,
+                response: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Results are streamed as newline delimited json in the order of the request items,
+/// each one as soon as it and all preceding items are verified.
+#[instrument(skip(verifier, params), level = "debug")]
+pub async fn verify(
+    verifier: web::Data<BatchVerifier>,
+    params: Json<Vec<JobRequest>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let items = params.into_inner();
+    if items.len() > verifier.max_items {
+        return Err(error::ErrorBadRequest(format!(
+            "Batch contains {} items, while at most {} are allowed",
+            items.len(),
+            verifier.max_items
+        )));
+    }
+    for (index, item) in items.iter().enumerate() {
+        job::reject_callback_url(item.callback_url())
+            .map_err(|err| error::ErrorBadRequest(format!("Item {}: {}", index, err)))?;
+    }
+
+    let caches = compilation_caches(&items);
+    let runner = verifier.runner.clone();
+    let results = stream::iter(items.into_iter().zip(caches).enumerate())
+        .map(move |(index, (request, cache))| {
+            let verification = runner.clone().run(request);
+            async move {
+                let result = match cache {
+                    Some(cache) => with_compilation_cache(cache, verification).await,
+                    None => verification.await,
+                };
+                BatchItemResult::new(index, result)
+            }
+        })
+        .buffered(verifier.concurrency)
+        .map(|result| {
+            let mut line = serde_json::to_vec(&result).map_err(error::ErrorInternalServerError)?;
+            line.push(b'\n');
+            Ok::<_, actix_web::Error>(web::Bytes::from(line))
+        });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(results))
+}
+
+/// Items sharing the sources and settings (i.e., differing in the bytecodes only)
+/// share the compilation cache, so that the sources are compiled once for all of them.
+/// The cache is dropped as soon as all items of the group are verified.
+fn compilation_caches(items: &[JobRequest]) -> Vec<Option<Arc<CompilationCache>>> {
+    let keys: Vec<_> = items.iter().map(compilation_key).collect();
+    let mut group_sizes: HashMap<&str, usize> = HashMap::new();
+    for key in keys.iter().flatten() {
+        *group_sizes.entry(key.as_str()).or_default() += 1;
+    }
+    let mut caches: HashMap<&str, Arc<CompilationCache>> = HashMap::new();
+    keys.iter()
+        .map(|key| {
+            let key = key.as_deref()?;
+            (group_sizes[key] > 1).then(|| caches.entry(key).or_default().clone())
+        })
+        .collect()
+}
+
+fn compilation_key(request: &JobRequest) -> Option<String> {
+    let mut request = request.clone();
+    match &mut request {
+        JobRequest::SolidityMultiPart(params) => {
+            params.deployed_bytecode.clear();
+            params.creation_bytecode.clear();
+            params.creation_transaction_hash = None;
+            params.contract_address = None;
+            params.callback_url = None;
+        }
+        JobRequest::SolidityStandardJson(params) => {
+            params.deployed_bytecode.clear();
+            params.creation_bytecode.clear();
+            params.creation_transaction_hash = None;
+            params.contract_address = None;
+            params.callback_url = None;
+        }
+        JobRequest::VyperMultiPart(params) => {
+            params.deployed_bytecode.clear();
+            params.creation_bytecode.clear();
+            params.callback_url = None;
+        }
+        JobRequest::Etherscan(_) => return None,
+    }
+    serde_json::to_string(&request).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse_items(items: serde_json::Value) -> Vec<JobRequest> {
+        serde_json::from_value(items).expect("valid batch items")
+    }
+
+    #[test]
+    fn groups_items_with_same_sources_and_settings() {
+        let item = |bytecode: &str, source: &str| {
+            serde_json::json!({
+                "solidity_multi_part": {
+                    "deployed_bytecode": bytecode,
+                    "creation_bytecode": bytecode,
+                    "compiler_version": "v0.8.17+commit.8df45f5f",
+                    "sources": { "main.sol": source },
+                    "evm_version": "london",
+                }
+            })
+        };
+        let items = parse_items(serde_json::json!([
+            item("0x01", "contract A {}"),
+            item("0x02", "contract B {}"),
+            item("0x03", "contract A {}"),
+            item("0x04", "contract C {}"),
+            item("0x05", "contract A {}"),
+        ]));
+
+        let caches = compilation_caches(&items);
+        let groups: Vec<_> = caches.iter().map(Option::is_some).collect();
+        assert_eq!(vec![true, false, true, false, true], groups);
+        let first = caches[0].as_ref().unwrap();
+        assert!(Arc::ptr_eq(first, caches[2].as_ref().unwrap()));
+        assert!(Arc::ptr_eq(first, caches[4].as_ref().unwrap()));
+    }
+
+    #[test]
+    fn item_results() {
+        let result = BatchItemResult::new(1, Err(error::ErrorBadRequest("Invalid bytecode")));
+        assert_eq!(
+            serde_json::json!({
+                "index": 1,
+                "response": null,
+                "error": "Invalid bytecode",
+            }),
+            serde_json::to_value(&result).unwrap()
+        );
+    }
+}
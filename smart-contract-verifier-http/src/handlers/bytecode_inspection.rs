@@ -0,0 +1,189 @@
+use crate::DisplayBytes;
+use actix_web::{error, web::Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{BytecodeInspection, MetadataSection, ProxyPattern};
+use std::str::FromStr;
+use tracing::instrument;
+
+#[derive(Debug, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct InspectionRequest {
+    pub creation_bytecode: Option<String>,
+    pub deployed_bytecode: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct InspectionResponse {
+    pub creation_bytecode: Option<BytecodeStructure>,
+    pub deployed_bytecode: Option<BytecodeStructure>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct BytecodeStructure {
+    pub compiler_version: Option<String>,
+    pub metadata_sections: Vec<MetadataSectionResponse>,
+    pub proxy_patterns: Vec<ProxyPatternResponse>,
+    /// Bytes following the last metadata section of the creation bytecode
+    #[schemars(with = "Option<String>")]
+    pub constructor_arguments: Option<DisplayBytes>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct MetadataSectionResponse {
+    pub offset: usize,
+    pub length: usize,
+    pub compiler_version: Option<String>,
+    pub ipfs: Option<String>,
+    #[schemars(with = "Option<String>")]
+    pub bzzr0: Option<DisplayBytes>,
+    #[schemars(with = "Option<String>")]
+    pub bzzr1: Option<DisplayBytes>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProxyPatternResponse {
+    MinimalProxy {
+        #[schemars(with = "String")]
+        implementation: DisplayBytes,
+    },
+    Eip1967,
+    Eip1967Beacon,
+    Eip1822,
+    ZeppelinOs,
+}
+
+impl From<BytecodeInspection> for BytecodeStructure {
+    fn from(inspection: BytecodeInspection) -> Self {
+        Self {
+            compiler_version: inspection.compiler_version,
+            metadata_sections: inspection
+                .metadata_sections
+                .into_iter()
+                .map(MetadataSectionResponse::from)
+                .collect(),
+            proxy_patterns: inspection
+                .proxy_patterns
+                .into_iter()
+                .map(ProxyPatternResponse::from)
+                .collect(),
+            constructor_arguments: inspection
+                .constructor_args_candidate
+                .map(DisplayBytes::from),
+        }
+    }
+}
+
+impl From<MetadataSection> for MetadataSectionResponse {
+    fn from(section: MetadataSection) -> Self {
+        Self {
+            offset: section.offset,
+            length: section.length,
+            compiler_version: section.compiler_version,
+            ipfs: section.ipfs,
+            bzzr0: section.bzzr0.map(DisplayBytes::from),
+            bzzr1: section.bzzr1.map(DisplayBytes::from),
+        }
+    }
+}
+
+impl From<ProxyPattern> for ProxyPatternResponse {
+    fn from(pattern: ProxyPattern) -> Self {
+        match pattern {
+            ProxyPattern::MinimalProxy { implementation } => Self::MinimalProxy {
+                implementation: implementation.into(),
+            },
+            ProxyPattern::Eip1967 => Self::Eip1967,
+            ProxyPattern::Eip1967Beacon => Self::Eip1967Beacon,
+            ProxyPattern::Eip1822 => Self::Eip1822,
+            ProxyPattern::ZeppelinOs => Self::ZeppelinOs,
+        }
+    }
+}
+
+#[instrument(skip(params), level = "debug")]
+pub async fn inspect(
+    params: Json<InspectionRequest>,
+) -> Result<Json<InspectionResponse>, actix_web::Error> {
+    let request = params.into_inner();
+    if request.creation_bytecode.is_none() && request.deployed_bytecode.is_none() {
+        return Err(error::ErrorBadRequest(
+            "Either creation or deployed bytecode should be specified",
+        ));
+    }
+
+    let parse = |bytecode: Option<String>, name: &str| {
+        bytecode
+            .map(|bytecode| {
+                DisplayBytes::from_str(&bytecode).map_err(|err| {
+                    error::ErrorBadRequest(format!("Invalid {} bytecode: {:?}", name, err))
+                })
+            })
+            .transpose()
+    };
+    let creation_bytecode = parse(request.creation_bytecode, "creation")?;
+    let deployed_bytecode = parse(request.deployed_bytecode, "deployed")?;
+
+    Ok(Json(InspectionResponse {
+        creation_bytecode: creation_bytecode
+            .map(|bytecode| BytecodeInspection::creation_tx_input(&bytecode.0).into()),
+        deployed_bytecode: deployed_bytecode
+            .map(|bytecode| BytecodeInspection::deployed_bytecode(&bytecode.0).into()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::parse::test_serialize_json_ok;
+    use serde_json::json;
+
+    #[test]
+    fn serialize_response() {
+        test_serialize_json_ok(vec![(
+            InspectionResponse {
+                creation_bytecode: None,
+                deployed_bytecode: Some(BytecodeStructure {
+                    compiler_version: Some("0.8.14".into()),
+                    metadata_sections: vec![MetadataSectionResponse {
+                        offset: 9,
+                        length: 51,
+                        compiler_version: Some("0.8.14".into()),
+                        ipfs: Some("Qmb3bbRhqQGFeTxDDMmDPcr2sZrKJTB9L3qKGhkcWaBxZi".into()),
+                        bzzr0: None,
+                        bzzr1: None,
+                    }],
+                    proxy_patterns: vec![
+                        ProxyPatternResponse::MinimalProxy {
+                            implementation: DisplayBytes::from([0xbe; 20]),
+                        },
+                        ProxyPatternResponse::Eip1967,
+                    ],
+                    constructor_arguments: None,
+                }),
+            },
+            json!({
+                "creation_bytecode": null,
+                "deployed_bytecode": {
+                    "compiler_version": "0.8.14",
+                    "metadata_sections": [{
+                        "offset": 9,
+                        "length": 51,
+                        "compiler_version": "0.8.14",
+                        "ipfs": "Qmb3bbRhqQGFeTxDDMmDPcr2sZrKJTB9L3qKGhkcWaBxZi",
+                        "bzzr0": null,
+                        "bzzr1": null,
+                    }],
+                    "proxy_patterns": [
+                        {
+                            "type": "minimal_proxy",
+                            "implementation": "0xbebebebebebebebebebebebebebebebebebebebe",
+                        },
+                        { "type": "eip1967" },
+                    ],
+                    "constructor_arguments": null,
+                },
+            }),
+        )]);
+    }
+}
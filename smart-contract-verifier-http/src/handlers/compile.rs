@@ -0,0 +1,50 @@
+//! Helpers shared by the compile endpoints of all languages.
+
+use ethers_solc::{artifacts::output_selection::OutputSelection, CompilerInput};
+use std::collections::BTreeMap;
+
+/// Selects the `outputs` for every contract if the input does not specify `outputSelection`.
+pub fn select_default_outputs(input: &mut CompilerInput, outputs: &[&str]) {
+    if !input.settings.output_selection.0.is_empty() {
+        return;
+    }
+    let outputs = outputs.iter().map(|output| output.to_string()).collect();
+    input.settings.output_selection = OutputSelection(BTreeMap::from([(
+        "*".to_string(),
+        BTreeMap::from([("*".to_string(), outputs)]),
+    )]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn input(settings: serde_json::Value) -> CompilerInput {
+        serde_json::from_value(json!({
+            "language": "Solidity",
+            "sources": {},
+            "settings": settings,
+        }))
+        .expect("Valid compiler input")
+    }
+
+    #[test]
+    fn default_outputs_are_selected_if_not_specified() {
+        let mut compiler_input = input(json!({}));
+        select_default_outputs(&mut compiler_input, &["abi", "evm.bytecode"]);
+        assert_eq!(
+            json!({ "*": { "*": ["abi", "evm.bytecode"] } }),
+            serde_json::to_value(&compiler_input.settings.output_selection).unwrap()
+        );
+
+        let selection = json!({ "*": { "*": ["evm.methodIdentifiers"] } });
+        let mut compiler_input = input(json!({ "outputSelection": selection }));
+        select_default_outputs(&mut compiler_input, &["abi", "evm.bytecode"]);
+        assert_eq!(
+            selection,
+            serde_json::to_value(&compiler_input.settings.output_selection).unwrap()
+        );
+    }
+}
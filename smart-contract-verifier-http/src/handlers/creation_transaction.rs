@@ -0,0 +1,107 @@
+//! Contracts created from other contracts (via CREATE/CREATE2) have no creation transaction
+//! input of their own, so their creation bytecodes may be retrieved from the traces
+//! of the transactions they have been created in.
+
+use crate::DisplayBytes;
+use actix_web::error;
+use ethers_core::types::{Address, H256};
+use smart_contract_verifier::{
+    traces::{self, api::CreationInputRequest},
+    TraceApiClient,
+};
+use std::{str::FromStr, sync::Arc};
+
+/// Parses the transaction the contract at `contract_address` has been created in.
+/// Returns `None` if the transaction is not specified, i.e. the creation bytecode is given as is.
+pub fn parse(
+    creation_transaction_hash: Option<&str>,
+    contract_address: Option<&str>,
+    creation_bytecode: &str,
+) -> Result<Option<CreationInputRequest>, actix_web::Error> {
+    let (transaction_hash, address) = match (creation_transaction_hash, contract_address) {
+        (None, None) => return Ok(None),
+        (Some(transaction_hash), Some(address)) => (transaction_hash, address),
+        (Some(_), None) => return Err(error::ErrorBadRequest("`contract_address` is required")),
+        (None, Some(_)) => {
+            let message = "`contract_address` requires `creation_transaction_hash`";
+            return Err(error::ErrorBadRequest(message));
+        }
+    };
+    if !creation_bytecode.is_empty() {
+        return Err(error::ErrorBadRequest(
+            "`creation_bytecode` should be omitted if `creation_transaction_hash` is specified",
+        ));
+    }
+    let transaction_hash = H256::from_str(transaction_hash).map_err(|err| {
+        error::ErrorBadRequest(format!("Invalid creation transaction hash: {}", err))
+    })?;
+    let address = Address::from_str(address)
+        .map_err(|err| error::ErrorBadRequest(format!("Invalid contract address: {}", err)))?;
+    Ok(Some(CreationInputRequest {
+        transaction_hash,
+        address,
+    }))
+}
+
+/// Retrieves the creation bytecode (init code with encoded constructor arguments)
+/// from the traces of the creation transaction.
+pub async fn creation_bytecode(
+    trace_client: Option<Arc<TraceApiClient>>,
+    request: CreationInputRequest,
+) -> Result<String, actix_web::Error> {
+    let trace_client = match trace_client {
+        Some(trace_client) => trace_client,
+        None => {
+            return Err(error::ErrorBadRequest(
+                "Retrieval of creation bytecodes is disabled on the server",
+            ))
+        }
+    };
+    match traces::api::creation_input(trace_client, request).await {
+        Ok(creation_input) => Ok(DisplayBytes::from(creation_input).to_string()),
+        Err(err @ traces::Error::Internal(_)) => Err(error::ErrorInternalServerError(err)),
+        Err(err) => Err(error::ErrorBadRequest(format!(
+            "Unable to retrieve the creation bytecode: {}",
+            err
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSACTION_HASH: &str =
+        "0x0000000000000000000000000000000000000000000000000000000000000002";
+    const ADDRESS: &str = "0x3333333333333333333333333333333333333333";
+
+    #[test]
+    fn creation_transaction_is_parsed() {
+        let request = parse(Some(TRANSACTION_HASH), Some(ADDRESS), "")
+            .expect("valid creation transaction")
+            .expect("creation transaction is specified");
+        assert_eq!(H256::from_low_u64_be(2), request.transaction_hash);
+        assert_eq!(Address::repeat_byte(0x33), request.address);
+
+        assert!(parse(None, None, "0x6080").unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_creation_transactions_are_rejected() {
+        for (transaction_hash, address, creation_bytecode) in [
+            (Some(TRANSACTION_HASH), None, ""),
+            (None, Some(ADDRESS), "0x6080"),
+            (Some(TRANSACTION_HASH), Some(ADDRESS), "0x6080"),
+            (Some("0x02"), Some(ADDRESS), ""),
+            (Some(TRANSACTION_HASH), Some("0x33"), ""),
+        ] {
+            assert!(
+                parse(transaction_hash, address, creation_bytecode).is_err(),
+                "{:?} {:?} {:?} should be rejected",
+                transaction_hash,
+                address,
+                creation_bytecode
+            );
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Types shared by the endpoints verifying several deployments of the same contract
+//! against a single compilation.
+
+use crate::{metrics, verification_response::VerificationResponse, DisplayBytes};
+use actix_web::error;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{VerificationError, VerificationSuccess};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct Deployment {
+    /// Returned with the result of the deployment as is
+    pub address: String,
+    pub creation_bytecode: String,
+    pub deployed_bytecode: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct DeploymentResponse {
+    pub address: String,
+    #[serde(flatten)]
+    pub response: VerificationResponse,
+}
+
+/// Splits the deployments into their addresses and bytecodes.
+pub fn parse(
+    deployments: Vec<Deployment>,
+) -> Result<(Vec<String>, Vec<smart_contract_verifier::Deployment>), actix_web::Error> {
+    if deployments.is_empty() {
+        return Err(error::ErrorBadRequest("No deployments to verify"));
+    }
+    let parse_bytecode = |address: &str, kind: &str, bytecode: &str| {
+        DisplayBytes::from_str(bytecode)
+            .map(|bytecode| bytecode.0)
+            .map_err(|err| {
+                error::ErrorBadRequest(format!("Invalid {} bytecode of {}: {}", kind, address, err))
+            })
+    };
+    let mut addresses = Vec::with_capacity(deployments.len());
+    let mut bytecodes = Vec::with_capacity(deployments.len());
+    for deployment in deployments {
+        bytecodes.push(smart_contract_verifier::Deployment {
+            creation_tx_input: parse_bytecode(
+                &deployment.address,
+                "creation",
+                &deployment.creation_bytecode,
+            )?,
+            deployed_bytecode: parse_bytecode(
+                &deployment.address,
+                "deployed",
+                &deployment.deployed_bytecode,
+            )?,
+        });
+        addresses.push(deployment.address);
+    }
+    Ok((addresses, bytecodes))
+}
+
+/// Returns the response of each deployment in the order of `addresses`.
+/// If the sources could not be compiled, all deployments share the failure response.
+pub fn responses(
+    addresses: Vec<String>,
+    result: Result<Vec<Result<VerificationSuccess, VerificationError>>, VerificationError>,
+    language: &str,
+    method: &str,
+) -> Result<Vec<DeploymentResponse>, actix_web::Error> {
+    let results = match result {
+        Ok(results) => results,
+        Err(
+            err @ (VerificationError::Compilation(_)
+            | VerificationError::NoMatchingContracts(_)
+            | VerificationError::ContractNotFound(_)
+            | VerificationError::ContractMismatch { .. }),
+        ) => {
+            return Ok(failed(
+                addresses,
+                VerificationResponse::verification_err(err),
+            ))
+        }
+        Err(
+            err @ (VerificationError::Initialization(_) | VerificationError::VersionNotFound(_)),
+        ) => return Err(error::ErrorBadRequest(err)),
+        Err(err @ VerificationError::Internal(_)) => {
+            return Err(error::ErrorInternalServerError(err))
+        }
+    };
+
+    let responses = addresses
+        .into_iter()
+        .zip(results)
+        .map(|(address, result)| {
+            let response = match result {
+                Ok(verification_success) => {
+                    let response = VerificationResponse::ok(verification_success.into());
+                    metrics::count_verify_contract(language, &response.status, method);
+                    response
+                }
+                Err(err) => VerificationResponse::verification_err(err),
+            };
+            DeploymentResponse { address, response }
+        })
+        .collect();
+    Ok(responses)
+}
+
+/// The same failure response for each of the deployments.
+pub fn failed(addresses: Vec<String>, response: VerificationResponse) -> Vec<DeploymentResponse> {
+    addresses
+        .into_iter()
+        .map(|address| DeploymentResponse {
+            address,
+            response: response.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn deployment(address: &str, bytecode: &str) -> Deployment {
+        Deployment {
+            address: address.into(),
+            creation_bytecode: bytecode.into(),
+            deployed_bytecode: bytecode.into(),
+        }
+    }
+
+    #[test]
+    fn parse_deployments() {
+        let (addresses, bytecodes) = parse(vec![
+            deployment("0x01", "0x6001"),
+            deployment("0x02", "0x6002"),
+        ])
+        .unwrap();
+        assert_eq!(vec!["0x01", "0x02"], addresses);
+        assert_eq!(
+            vec![0x60, 0x02],
+            bytecodes[1].deployed_bytecode.to_vec(),
+            "bytecodes should keep the order of deployments"
+        );
+
+        let err = parse(vec![
+            deployment("0x01", "0x6001"),
+            deployment("0x02", "0xzz"),
+        ])
+        .expect_err("invalid bytecode should fail");
+        assert!(
+            err.to_string()
+                .starts_with("Invalid creation bytecode of 0x02"),
+            "unexpected error: {}",
+            err
+        );
+        parse(vec![]).expect_err("empty deployments should fail");
+    }
+
+    #[test]
+    fn compilation_failure_is_shared() {
+        let responses = responses(
+            vec!["0x01".into(), "0x02".into()],
+            Err(VerificationError::Compilation(vec!["ParserError".into()])),
+            "solidity",
+            "multi-part",
+        )
+        .unwrap();
+        assert_eq!(2, responses.len());
+        assert_eq!(responses[0].response, responses[1].response);
+        assert_eq!(
+            json!({
+                "address": "0x02",
+                "message": "Compilation error: [\"ParserError\"]",
+                "result": null,
+                "status": "1",
+                "errors": [],
+            }),
+            serde_json::to_value(&responses[1]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,592 @@
+//! Etherscan-compatible contract verification API (`module=contract`),
+//! so that tools verifying contracts via Etherscan (e.g., hardhat-verify or
+//! `forge verify-contract`) could be pointed to the service directly.
+//!
+//! Parameters are accepted both in the query and in the url-encoded form.
+//! The verification is run as a regular job, whose id is returned as the GUID.
+//! Bytecodes of the contract are retrieved from the node inside the job.
+
+use super::{solidity_multi_part, solidity_standard_json};
+use crate::{
+    jobs::{JobRequest, JobRunner, JobStatus, Jobs},
+    verification_response::{VerificationResult, VerificationStatus},
+    DisplayBytes,
+};
+use actix_web::{web, web::Json};
+use anyhow::anyhow;
+use ethers_core::types::Address;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use smart_contract_verifier::{
+    traces::{self, api::DeploymentRequest},
+    Deployment, TraceApiClient,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::instrument;
+
+/// Maximum number of libraries Etherscan accepts (`libraryname1`..`libraryname10`).
+const MAX_LIBRARIES: usize = 10;
+
+/// Verification of the contract whose bytecodes are retrieved
+/// from the node by its address when the job is run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerificationRequest {
+    pub contract_address: Address,
+    /// Request with empty bytecodes
+    pub request: Box<JobRequest>,
+}
+
+pub struct EtherscanApi {
+    runner: JobRunner,
+    /// Contracts verified via the api, returned by `getsourcecode`. Not persisted.
+    /// Jobs resumed after restart are run without the api, so their results are not added.
+    verified: Mutex<HashMap<Address, (VerificationResult, Instant)>>,
+    /// Period the verified contracts are kept for, the same as the one of job results
+    ttl: Duration,
+}
+
+impl EtherscanApi {
+    pub fn new(runner: JobRunner, ttl: Duration) -> Self {
+        Self {
+            runner,
+            verified: Default::default(),
+            ttl,
+        }
+    }
+
+    fn verified(&self, address: &Address) -> Option<VerificationResult> {
+        let mut verified = self.lock();
+        self.remove_expired(&mut verified);
+        verified.get(address).map(|(result, _)| result.clone())
+    }
+
+    fn insert_verified(&self, address: Address, result: VerificationResult) {
+        let mut verified = self.lock();
+        self.remove_expired(&mut verified);
+        verified.insert(address, (result, Instant::now()));
+    }
+
+    fn remove_expired(&self, verified: &mut HashMap<Address, (VerificationResult, Instant)>) {
+        let now = Instant::now();
+        verified.retain(|_, (_, verified_at)| now.duration_since(*verified_at) < self.ttl);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Address, (VerificationResult, Instant)>> {
+        self.verified
+            .lock()
+            .expect("verified contracts lock is poisoned")
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq, JsonSchema)]
+#[schemars(rename = "EtherscanResponse")]
+pub struct ApiResponse {
+    /// "1" on success, "0" otherwise
+    pub status: String,
+    /// "OK" on success, "NOTOK" otherwise
+    pub message: String,
+    pub result: serde_json::Value,
+}
+
+impl ApiResponse {
+    fn ok(result: impl Into<serde_json::Value>) -> Self {
+        Self {
+            status: "1".to_string(),
+            message: "OK".to_string(),
+            result: result.into(),
+        }
+    }
+
+    fn not_ok(result: impl ToString) -> Self {
+        Self {
+            status: "0".to_string(),
+            message: "NOTOK".to_string(),
+            result: result.to_string().into(),
+        }
+    }
+}
+
+type Params = HashMap<String, String>;
+
+#[instrument(skip(api, jobs, query, form), level = "debug")]
+pub async fn handle(
+    api: web::Data<EtherscanApi>,
+    jobs: web::Data<Jobs>,
+    query: web::Query<Params>,
+    form: Option<web::Form<Params>>,
+) -> Json<ApiResponse> {
+    let mut params = query.into_inner();
+    if let Some(form) = form {
+        params.extend(form.into_inner());
+    }
+
+    let response = match (param(&params, "module"), param(&params, "action")) {
+        (Some("contract"), Some("verifysourcecode")) => {
+            match verify_source_code(api.into_inner(), jobs.into_inner(), &params).await {
+                Ok(guid) => ApiResponse::ok(guid),
+                Err(err) => ApiResponse::not_ok(err),
+            }
+        }
+        (Some("contract"), Some("checkverifystatus")) => check_verify_status(&jobs, &params),
+        (Some("contract"), Some("getsourcecode")) => get_source_code(&api, &params),
+        (Some("contract"), _) => ApiResponse::not_ok("Error! Missing Or invalid Action name"),
+        _ => ApiResponse::not_ok("Error! Missing Or invalid Module name"),
+    };
+    Json(response)
+}
+
+/// Returns the job id the verification is run with.
+async fn verify_source_code(
+    api: Arc<EtherscanApi>,
+    jobs: Arc<Jobs>,
+    params: &Params,
+) -> anyhow::Result<String> {
+    let address = required(params, "contractaddress")?;
+    let address =
+        Address::from_str(address).map_err(|_| anyhow!("Invalid contractaddress: {}", address))?;
+    let request = JobRequest::Etherscan(VerificationRequest {
+        contract_address: address,
+        request: Box::new(job_request(params)?),
+    });
+
+    let verification = {
+        let verification = api.runner.clone().run(request.clone());
+        async move {
+            let response = verification.await?;
+            if let Some(result) = &response.result {
+                api.insert_verified(address, result.clone());
+            }
+            Ok(response)
+        }
+    };
+    jobs.spawn(request, verification, None).await
+}
+
+/// Returns the request of the job with the bytecodes retrieved from the node.
+pub async fn with_bytecodes(
+    trace_client: Arc<TraceApiClient>,
+    request: VerificationRequest,
+) -> anyhow::Result<JobRequest> {
+    let address = request.contract_address;
+    let deployment = traces::api::deployment(trace_client, DeploymentRequest { address })
+        .await
+        .map_err(|err| match err {
+            traces::Error::ContractNotFound(_) => {
+                anyhow!("Unable to locate ContractCode at {:?}", address)
+            }
+            err => anyhow!("Unable to retrieve the contract bytecode: {}", err),
+        })?;
+    let mut request = *request.request;
+    set_bytecodes(&mut request, deployment)?;
+    Ok(request)
+}
+
+fn check_verify_status(jobs: &Jobs, params: &Params) -> ApiResponse {
+    let job = match param(params, "guid").and_then(|guid| jobs.get(guid)) {
+        Some(job) => job,
+        None => return ApiResponse::not_ok("Unknown UID"),
+    };
+    if !matches!(job.status, JobStatus::Done | JobStatus::Failed) {
+        return ApiResponse::not_ok("Pending in queue");
+    }
+    match (job.response, job.error) {
+        (Some(response), _) if response.status == VerificationStatus::Ok => {
+            ApiResponse::ok("Pass - Verified")
+        }
+        (Some(response), _) => {
+            ApiResponse::not_ok(format!("Fail - Unable to verify. {}", response.message))
+        }
+        (None, error) => ApiResponse::not_ok(format!(
+            "Fail - Unable to verify. {}",
+            error.unwrap_or_default()
+        )),
+    }
+}
+
+fn get_source_code(api: &EtherscanApi, params: &Params) -> ApiResponse {
+    let address = match param(params, "address").and_then(|address| Address::from_str(address).ok())
+    {
+        Some(address) => address,
+        None => return ApiResponse::not_ok("Invalid Address format"),
+    };
+    let source_code = match api.verified(&address) {
+        Some(result) => SourceCode::from(result),
+        None => SourceCode::unverified(),
+    };
+    ApiResponse::ok(json!([source_code]))
+}
+
+/// Builds the verification request with empty bytecodes, which are to be set
+/// after they are retrieved from the node.
+fn job_request(params: &Params) -> anyhow::Result<JobRequest> {
+    let source_code = required(params, "sourceCode")?.to_string();
+    let compiler_version = required(params, "compilerversion")?.to_string();
+    let contract_name = param(params, "contractname").map(String::from);
+
+    match param(params, "codeformat").unwrap_or("solidity-single-file") {
+        "solidity-single-file" => {
+            let file_name = match contract_name.as_deref().map(|name| name.rsplit_once(':')) {
+                Some(Some((path, _))) => path.to_string(),
+                Some(None) => format!("{}.sol", contract_name.as_deref().unwrap_or_default()),
+                None => "contract.sol".to_string(),
+            };
+            let optimization = match param(params, "optimizationUsed") {
+                Some("1") => Some(true),
+                Some("0") => Some(false),
+                Some(value) => return Err(anyhow!("Invalid optimizationUsed: {}", value)),
+                None => None,
+            };
+            let optimization_runs = param(params, "runs")
+                .map(|runs| usize::from_str(runs).map_err(|_| anyhow!("Invalid runs: {}", runs)))
+                .transpose()?;
+            let evm_version = match param(params, "evmversion") {
+                Some(version) if !version.eq_ignore_ascii_case("default") => version.to_string(),
+                _ => "default".to_string(),
+            };
+            let libraries = libraries(params)?;
+
+            Ok(JobRequest::SolidityMultiPart(
+                solidity_multi_part::VerificationRequest {
+                    deployed_bytecode: String::new(),
+                    creation_bytecode: String::new(),
+                    creation_transaction_hash: None,
+                    contract_address: None,
+                    compiler_version,
+                    contract_name,
+                    normalize_sources: None,
+                    artifacts: None,
+                    callback_url: None,
+                    content: solidity_multi_part::MultiPartFiles {
+                        sources: BTreeMap::from([(file_name.into(), source_code)]),
+                        evm_version,
+                        optimization,
+                        optimization_runs,
+                        optimizer_details: None,
+                        via_ir: None,
+                        remappings: None,
+                        contract_libraries: (!libraries.is_empty()).then_some(libraries),
+                    },
+                },
+            ))
+        }
+        "solidity-standard-json-input" => Ok(JobRequest::SolidityStandardJson(
+            solidity_standard_json::VerificationRequest {
+                deployed_bytecode: String::new(),
+                creation_bytecode: String::new(),
+                creation_transaction_hash: None,
+                contract_address: None,
+                compiler_version,
+                contract_name,
+                normalize_sources: None,
+                artifacts: None,
+                callback_url: None,
+                content: solidity_standard_json::StandardJson { input: source_code },
+            },
+        )),
+        format => Err(anyhow!("Unsupported code format: {}", format)),
+    }
+}
+
+fn libraries(params: &Params) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut libraries = BTreeMap::new();
+    for i in 1..=MAX_LIBRARIES {
+        let name = param(params, &format!("libraryname{}", i));
+        let address = param(params, &format!("libraryaddress{}", i));
+        match (name, address) {
+            (Some(name), Some(address)) => {
+                libraries.insert(name.to_string(), address.to_string());
+            }
+            (None, None) => {}
+            _ => {
+                return Err(anyhow!(
+                    "Both libraryname{i} and libraryaddress{i} are required"
+                ))
+            }
+        }
+    }
+    Ok(libraries)
+}
+
+fn set_bytecodes(request: &mut JobRequest, deployment: Deployment) -> anyhow::Result<()> {
+    let creation_bytecode = DisplayBytes::from(deployment.creation_tx_input).to_string();
+    let deployed_bytecode = DisplayBytes::from(deployment.deployed_bytecode).to_string();
+    let (creation, deployed) = match request {
+        JobRequest::SolidityMultiPart(request) => (
+            &mut request.creation_bytecode,
+            &mut request.deployed_bytecode,
+        ),
+        JobRequest::SolidityStandardJson(request) => (
+            &mut request.creation_bytecode,
+            &mut request.deployed_bytecode,
+        ),
+        JobRequest::VyperMultiPart(request) => (
+            &mut request.creation_bytecode,
+            &mut request.deployed_bytecode,
+        ),
+        JobRequest::Etherscan(_) => {
+            return Err(anyhow!("Nested etherscan requests are not supported"))
+        }
+    };
+    *creation = creation_bytecode;
+    *deployed = deployed_bytecode;
+    Ok(())
+}
+
+fn param<'a>(params: &'a Params, name: &str) -> Option<&'a str> {
+    params
+        .get(name)
+        .map(String::as_str)
+        .filter(|value| !value.is_empty())
+}
+
+fn required<'a>(params: &'a Params, name: &str) -> anyhow::Result<&'a str> {
+    param(params, name).ok_or_else(|| anyhow!("Missing {}", name))
+}
+
+/// Item of the `getsourcecode` result in the format of Etherscan.
+#[derive(Clone, Debug, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+struct SourceCode {
+    source_code: String,
+    #[serde(rename = "ABI")]
+    abi: String,
+    contract_name: String,
+    compiler_version: String,
+    optimization_used: String,
+    runs: String,
+    constructor_arguments: String,
+    #[serde(rename = "EVMVersion")]
+    evm_version: String,
+    library: String,
+    license_type: String,
+    proxy: String,
+    implementation: String,
+    swarm_source: String,
+}
+
+impl SourceCode {
+    fn unverified() -> Self {
+        Self {
+            abi: "Contract source code not verified".to_string(),
+            proxy: "0".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<VerificationResult> for SourceCode {
+    fn from(result: VerificationResult) -> Self {
+        // Several sources are returned as the standard json input wrapped into
+        // the additional braces, the same way Etherscan does
+        let source_code = if result.sources.len() == 1 {
+            result.sources.into_values().next().unwrap_or_default()
+        } else {
+            let sources: BTreeMap<_, _> = result
+                .sources
+                .into_iter()
+                .map(|(path, content)| (path, json!({ "content": content })))
+                .collect();
+            format!(
+                "{{{}}}",
+                json!({ "language": "Solidity", "sources": sources })
+            )
+        };
+        // Etherscan identifies libraries by their bare names
+        let library = result
+            .contract_libraries
+            .into_iter()
+            .map(|(name, address)| {
+                let name = name
+                    .rsplit_once(':')
+                    .map_or(name.as_str(), |(_, name)| name);
+                format!("{}:{}", name, address)
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .join(";");
+        let evm_version = match result.evm_version.as_str() {
+            "default" => "Default".to_string(),
+            _ => result.evm_version,
+        };
+        Self {
+            source_code,
+            abi: result.abi,
+            contract_name: result.contract_name,
+            compiler_version: result.compiler_version,
+            optimization_used: if result.optimization.unwrap_or_default() {
+                "1"
+            } else {
+                "0"
+            }
+            .to_string(),
+            runs: result
+                .optimization_runs
+                .map(|runs| runs.to_string())
+                .unwrap_or_default(),
+            constructor_arguments: result
+                .constructor_arguments
+                .map(|args| args.to_string().trim_start_matches("0x").to_string())
+                .unwrap_or_default(),
+            evm_version,
+            library,
+            proxy: "0".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification_response::VerificationResponse;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn params(params: &[(&str, &str)]) -> Params {
+        params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn single_file_request() {
+        let request = job_request(&params(&[
+            ("sourceCode", "contract A {}"),
+            ("codeformat", "solidity-single-file"),
+            ("contractname", "contracts/A.sol:A"),
+            ("compilerversion", "v0.8.7+commit.e28d00a7"),
+            ("optimizationUsed", "1"),
+            ("runs", "200"),
+            ("evmversion", ""),
+            ("libraryname1", "Lib"),
+            ("libraryaddress1", "0x01"),
+        ]))
+        .unwrap();
+        let request = match request {
+            JobRequest::SolidityMultiPart(request) => request,
+            request => panic!("unexpected request: {:?}", request),
+        };
+        assert_eq!(Some("contracts/A.sol:A"), request.contract_name.as_deref());
+        assert_eq!(
+            BTreeMap::from([("contracts/A.sol".into(), "contract A {}".to_string())]),
+            request.content.sources
+        );
+        assert_eq!("default", request.content.evm_version);
+        assert_eq!(Some(true), request.content.optimization);
+        assert_eq!(Some(200), request.content.optimization_runs);
+        assert_eq!(
+            Some(BTreeMap::from([("Lib".to_string(), "0x01".to_string())])),
+            request.content.contract_libraries
+        );
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let base = [
+            ("sourceCode", "{}"),
+            ("compilerversion", "v0.8.7+commit.e28d00a7"),
+        ];
+        let request = |extra: &[(&str, &str)]| {
+            job_request(&params(&[&base[..], extra].concat()))
+                .expect_err("request should be invalid")
+                .to_string()
+        };
+        assert_eq!(
+            "Unsupported code format: vyper-json",
+            request(&[("codeformat", "vyper-json")])
+        );
+        assert_eq!("Invalid runs: many", request(&[("runs", "many")]));
+        assert_eq!(
+            "Both libraryname2 and libraryaddress2 are required",
+            request(&[("libraryname2", "Lib")])
+        );
+        assert!(matches!(
+            job_request(&params(
+                &[&base[..], &[("codeformat", "solidity-standard-json-input")]].concat()
+            )),
+            Ok(JobRequest::SolidityStandardJson(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn verify_status() {
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), None, None));
+        let check = |guid: &str| check_verify_status(&jobs, &params(&[("guid", guid)]));
+        assert_eq!(ApiResponse::not_ok("Unknown UID"), check("unknown"));
+
+        let request = job_request(&params(&[
+            ("sourceCode", "contract A {}"),
+            ("compilerversion", "v0.8.7+commit.e28d00a7"),
+        ]))
+        .unwrap();
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let guid = jobs
+            .clone()
+            .spawn(
+                request,
+                async move {
+                    receiver.await.ok();
+                    Ok(VerificationResponse::err("Compilation error"))
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ApiResponse::not_ok("Pending in queue"), check(&guid));
+
+        sender.send(()).unwrap();
+        while jobs.get(&guid).unwrap().status != JobStatus::Done {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            ApiResponse::not_ok("Fail - Unable to verify. Compilation error"),
+            check(&guid)
+        );
+    }
+
+    #[test]
+    fn source_code_of_verified_contract() {
+        let result: VerificationResult = serde_json::from_value(json!({
+            "file_name": "A.sol",
+            "contract_name": "A",
+            "compiler_version": "v0.8.7+commit.e28d00a7",
+            "evm_version": "default",
+            "constructor_arguments": "0x0001",
+            "optimization": true,
+            "optimization_runs": 200,
+            "contract_libraries": {"A.sol:Lib": "0x01", "B.sol:Lib": "0x01", "Other": "0x02"},
+            "abi": "[]",
+            "sources": {"A.sol": "contract A {}", "B.sol": "contract B {}"},
+            "is_destroyed": false,
+            "external_sources": [],
+        }))
+        .unwrap();
+        let source_code = serde_json::to_value(SourceCode::from(result)).unwrap();
+        assert_eq!(
+            json!({
+                "SourceCode": "{{\"language\":\"Solidity\",\"sources\":{\"A.sol\":{\"content\":\"contract A {}\"},\"B.sol\":{\"content\":\"contract B {}\"}}}}",
+                "ABI": "[]",
+                "ContractName": "A",
+                "CompilerVersion": "v0.8.7+commit.e28d00a7",
+                "OptimizationUsed": "1",
+                "Runs": "200",
+                "ConstructorArguments": "0001",
+                "EVMVersion": "Default",
+                "Library": "Lib:0x01;Other:0x02",
+                "LicenseType": "",
+                "Proxy": "0",
+                "Implementation": "",
+                "SwarmSource": "",
+            }),
+            source_code
+        );
+    }
+}
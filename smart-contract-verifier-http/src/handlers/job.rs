@@ -0,0 +1,55 @@
+use crate::jobs::{Job, Jobs};
+use actix_web::{error, web, web::Json, HttpResponse};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::instrument;
+use url::Url;
+
+#[derive(Debug, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct JobCreated {
+    pub id: String,
+}
+
+/// Response of the endpoints starting the verification in background.
+pub fn accepted(id: String) -> HttpResponse {
+    HttpResponse::Accepted().json(JobCreated { id })
+}
+
+/// Checks that the callback may be delivered to the `callback_url`.
+pub fn validate_callback_url(
+    jobs: &Jobs,
+    callback_url: Option<Url>,
+) -> Result<Option<Url>, actix_web::Error> {
+    let url = match callback_url {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+    let webhooks = jobs
+        .webhooks()
+        .ok_or_else(|| error::ErrorBadRequest("Callbacks are disabled on the server"))?;
+    webhooks
+        .validate_url(&url)
+        .map_err(error::ErrorBadRequest)?;
+    Ok(Some(url))
+}
+
+/// Callbacks are posted by the asynchronous endpoints only,
+/// so the synchronous requests specifying them are rejected rather than silently ignored.
+pub fn reject_callback_url(callback_url: Option<&Url>) -> Result<(), actix_web::Error> {
+    match callback_url {
+        Some(_) => Err(error::ErrorBadRequest(
+            "Callbacks are supported by asynchronous endpoints only",
+        )),
+        None => Ok(()),
+    }
+}
+
+#[instrument(skip(jobs), level = "debug")]
+pub async fn get(
+    jobs: web::Data<Jobs>,
+    id: web::Path<String>,
+) -> Result<Json<Job>, actix_web::Error> {
+    jobs.get(&id)
+        .map(Json)
+        .ok_or_else(|| error::ErrorNotFound(format!("Job {} not found", id)))
+}
@@ -1,9 +1,22 @@
 pub mod status;
 
+pub mod archive;
+pub mod batch;
+pub mod bytecode_inspection;
+mod compile;
+pub mod creation_transaction;
+pub mod deployments;
+pub mod etherscan;
+pub mod job;
+pub mod openapi;
+
+pub mod solidity_build_info;
+pub mod solidity_compile;
 pub mod solidity_multi_part;
 pub mod solidity_standard_json;
 pub mod solidity_version_list;
 
+pub mod vyper_compile;
 pub mod vyper_multi_part;
 pub mod vyper_version_list;
 
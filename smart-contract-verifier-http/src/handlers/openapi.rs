@@ -0,0 +1,15 @@
+use crate::openapi::SWAGGER_UI;
+use actix_web::{http::header::ContentType, web, HttpResponse};
+
+/// OpenAPI document describing the routes registered on the server.
+pub struct Document(pub serde_json::Value);
+
+pub async fn get(document: web::Data<Document>) -> HttpResponse {
+    HttpResponse::Ok().json(&document.0)
+}
+
+pub async fn swagger_ui() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(SWAGGER_UI)
+}
@@ -0,0 +1,135 @@
+use super::solidity_standard_json::{self, StandardJson};
+use crate::verification_response::VerificationResponse;
+use actix_web::{error, web, web::Json};
+use anyhow::anyhow;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{Compilers, SolidityCompiler};
+use tracing::instrument;
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "SolidityBuildInfoVerificationRequest")]
+pub struct VerificationRequest {
+    pub deployed_bytecode: String,
+    pub creation_bytecode: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+    /// Content of the hardhat (`artifacts/build-info/*.json`)
+    /// or foundry (`out/build-info/*.json`) build-info file
+    pub build_info: BuildInfo,
+}
+
+/// Fields of the build-info file required for the verification.
+/// The rest (e.g., the compilation output) are ignored.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub solc_version: Option<String>,
+    pub solc_long_version: Option<String>,
+    /// Standard json input the contracts were compiled with
+    pub input: serde_json::Value,
+}
+
+impl TryFrom<VerificationRequest> for solidity_standard_json::VerificationRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(value: VerificationRequest) -> Result<Self, Self::Error> {
+        let build_info = value.build_info;
+        // Only the long version contains the commit hash required to find the compiler
+        let compiler_version = match (build_info.solc_long_version, build_info.solc_version) {
+            (Some(long_version), _) => long_version,
+            (None, Some(version)) => {
+                let message = format!(
+                    "Build info does not contain `solcLongVersion`, and the commit of {} is unknown",
+                    version
+                );
+                return Err(anyhow!(message));
+            }
+            (None, None) => {
+                return Err(anyhow!("Build info does not contain the compiler version"))
+            }
+        };
+        Ok(Self {
+            deployed_bytecode: value.deployed_bytecode,
+            creation_bytecode: value.creation_bytecode,
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version,
+            contract_name: value.contract_name,
+            normalize_sources: value.normalize_sources,
+            artifacts: value.artifacts,
+            callback_url: None,
+            content: StandardJson {
+                input: build_info.input.to_string(),
+            },
+        })
+    }
+}
+
+/// Verifies the standard json input and the compiler version taken from the build-info file.
+/// Responds the same way the standard json endpoint does, while build-info files
+/// the request could not be built of are rejected as bad requests.
+#[instrument(skip(compilers, params), level = "debug")]
+pub async fn verify(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    params: Json<VerificationRequest>,
+) -> Result<Json<VerificationResponse>, actix_web::Error> {
+    let request: solidity_standard_json::VerificationRequest = params
+        .into_inner()
+        .try_into()
+        .map_err(error::ErrorBadRequest)?;
+    solidity_standard_json::process_request(compilers, request)
+        .await
+        .map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn request(build_info: serde_json::Value) -> VerificationRequest {
+        serde_json::from_value(json!({
+            "deployed_bytecode": "0x6001",
+            "creation_bytecode": "0x6002",
+            "contract_name": "contracts/A.sol:A",
+            "build_info": build_info,
+        }))
+        .expect("valid request")
+    }
+
+    #[test]
+    fn hardhat_build_info() {
+        let input = json!({"language": "Solidity", "sources": {}, "settings": {}});
+        let request: solidity_standard_json::VerificationRequest = request(json!({
+            "_format": "hh-sol-build-info-1",
+            "id": "0b9d5c8a4b0e1f5c",
+            "solcVersion": "0.8.9",
+            "solcLongVersion": "0.8.9+commit.e5eed63a",
+            "input": input,
+            "output": {"contracts": {}, "sources": {}},
+        }))
+        .try_into()
+        .unwrap();
+
+        assert_eq!("0.8.9+commit.e5eed63a", request.compiler_version);
+        assert_eq!(Some("contracts/A.sol:A"), request.contract_name.as_deref());
+        assert_eq!("0x6001", request.deployed_bytecode);
+        assert_eq!(
+            input,
+            serde_json::from_str::<serde_json::Value>(&request.content.input).unwrap()
+        );
+    }
+
+    #[test]
+    fn build_info_without_long_version() {
+        let result: Result<solidity_standard_json::VerificationRequest, _> =
+            request(json!({"solcVersion": "0.8.9", "input": {}})).try_into();
+        assert_eq!(
+            "Build info does not contain `solcLongVersion`, and the commit of 0.8.9 is unknown",
+            result.expect_err("commit is required").to_string()
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use super::{compile::select_default_outputs, solidity_standard_json::StandardJson};
+use crate::metrics;
+use actix_web::{error, web, web::Json};
+use ethers_solc::CompilerOutput;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use smart_contract_verifier::{solidity, CompilerError, Compilers, SolidityCompiler, Version};
+use std::str::FromStr;
+use tracing::instrument;
+
+/// Outputs selected for every contract if the input does not specify `outputSelection`
+const DEFAULT_CONTRACT_OUTPUTS: [&str; 4] =
+    ["abi", "evm.bytecode", "evm.deployedBytecode", "metadata"];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(rename = "SolidityCompileRequest")]
+pub struct CompileRequest {
+    pub compiler_version: String,
+
+    #[serde(flatten)]
+    pub content: StandardJson,
+}
+
+#[instrument(skip(compilers, params), level = "debug")]
+pub async fn compile(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    params: Json<CompileRequest>,
+) -> Result<Json<CompilerOutput>, actix_web::Error> {
+    let request = params.into_inner();
+    let compiler_version = Version::from_str(&request.compiler_version)
+        .map_err(|err| error::ErrorBadRequest(format!("Invalid compiler version: {}", err)))?;
+    let content: solidity::standard_json::StandardJsonContent =
+        request.content.try_into().map_err(error::ErrorBadRequest)?;
+
+    let mut input = content.input;
+    select_default_outputs(&mut input, &DEFAULT_CONTRACT_OUTPUTS);
+
+    let result = compilers
+        .compile_output(&compiler_version, &input, &content.extra_settings)
+        .await;
+    match result {
+        Ok(output) => {
+            metrics::count_compile_contract("solidity", !output.has_error());
+            Ok(Json(output))
+        }
+        Err(err @ CompilerError::VersionNotFound(_)) => Err(error::ErrorBadRequest(err)),
+        Err(err) => Err(error::ErrorInternalServerError(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_compile_request() {
+        let input = r#"{
+            "compiler_version": "v0.8.2+commit.661d1103",
+            "input": "{\"language\": \"Solidity\", \"sources\": {\"Foo.sol\": {\"content\": \"pragma solidity ^0.8.2;\\n\\ncontract Foo {}\\n\"}}, \"settings\": {\"optimizer\": {\"enabled\": true, \"runs\": 200}}}"
+        }"#;
+
+        let deserialized: CompileRequest = serde_json::from_str(input).expect("Valid json");
+        assert_eq!(
+            deserialized.compiler_version, "v0.8.2+commit.661d1103",
+            "Invalid compiler version"
+        );
+        let content: solidity::standard_json::StandardJsonContent = deserialized
+            .content
+            .try_into()
+            .expect("failed to convert to standard json");
+        assert!(
+            content.input.settings.output_selection.0.is_empty(),
+            "Output selection is not specified, so that the default one is used"
+        );
+    }
+}
@@ -1,29 +1,119 @@
-use crate::{metrics, verification_response::VerificationResponse, DisplayBytes};
-use actix_web::{error, web, web::Json};
-use ethers_solc::EvmVersion;
-use serde::Deserialize;
-use smart_contract_verifier::{solidity, Compilers, SolidityCompiler, VerificationError, Version};
-use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+use super::{
+    creation_transaction,
+    deployments::{self, Deployment, DeploymentResponse},
+    job,
+};
+use crate::{
+    jobs::{JobRequest, Jobs},
+    metrics,
+    verification_response::VerificationResponse,
+    DisplayBytes,
+};
+use actix_web::{error, web, web::Json, HttpResponse};
+use ethers_solc::{artifacts::OptimizerDetails, remappings::Remapping, EvmVersion};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{
+    solidity, traces::api::CreationInputRequest, ArtifactKind, Compilers, ContractName,
+    PackageRegistry, SolidityCompiler, TraceApiClient, VerificationError, Version,
+};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr, sync::Arc};
 use tracing::instrument;
+use url::Url;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+#[schemars(rename = "SolidityMultiPartVerificationRequest")]
 pub struct VerificationRequest {
     pub deployed_bytecode: String,
+    /// May be omitted if `creation_transaction_hash` is specified
+    #[serde(default)]
     pub creation_bytecode: String,
+    /// Transaction the contract has been created in from another contract (via CREATE/CREATE2).
+    /// If specified, the creation bytecode is retrieved from the transaction traces
+    pub creation_transaction_hash: Option<String>,
+    /// Address of the contract created in `creation_transaction_hash`
+    pub contract_address: Option<String>,
     pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+    /// Url the finished job is posted to. Accepted by asynchronous endpoints only
+    pub callback_url: Option<Url>,
 
     #[serde(flatten)]
     pub content: MultiPartFiles,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+#[schemars(rename = "SolidityMultiPartFiles")]
 pub struct MultiPartFiles {
     pub sources: BTreeMap<PathBuf, String>,
     pub evm_version: String,
+    pub optimization: Option<bool>,
     pub optimization_runs: Option<usize>,
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub optimizer_details: Option<OptimizerDetails>,
+    pub via_ir: Option<bool>,
+    pub remappings: Option<Vec<String>>,
     pub contract_libraries: Option<BTreeMap<String, String>>,
 }
 
+/// Same as [`VerificationRequest`], but with the bytecodes of several deployments.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+#[schemars(rename = "SolidityMultiPartDeploymentsRequest")]
+pub struct DeploymentsRequest {
+    pub deployments: Vec<Deployment>,
+    pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub content: MultiPartFiles,
+}
+
+impl DeploymentsRequest {
+    /// Bytecodes of the returned request are left empty, as they are taken from the deployments.
+    fn split(self) -> (Vec<Deployment>, VerificationRequest) {
+        let request = VerificationRequest {
+            deployed_bytecode: String::new(),
+            creation_bytecode: String::new(),
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version: self.compiler_version,
+            contract_name: self.contract_name,
+            normalize_sources: self.normalize_sources,
+            artifacts: self.artifacts,
+            callback_url: None,
+            content: self.content,
+        };
+        (self.deployments, request)
+    }
+}
+
+impl VerificationRequest {
+    fn creation_transaction(&self) -> Result<Option<CreationInputRequest>, actix_web::Error> {
+        creation_transaction::parse(
+            self.creation_transaction_hash.as_deref(),
+            self.contract_address.as_deref(),
+            &self.creation_bytecode,
+        )
+    }
+
+    /// Retrieves the creation bytecode from the traces of the creation transaction, if one is specified.
+    pub async fn with_creation_bytecode(
+        mut self,
+        trace_client: Option<Arc<TraceApiClient>>,
+    ) -> Result<Self, actix_web::Error> {
+        let creation_transaction = self.creation_transaction()?;
+        if let Some(request) = creation_transaction {
+            self.creation_bytecode =
+                creation_transaction::creation_bytecode(trace_client, request).await?;
+        }
+        Ok(self)
+    }
+}
+
 impl TryFrom<VerificationRequest> for solidity::multi_part::VerificationRequest {
     type Error = actix_web::Error;
 
@@ -36,10 +126,26 @@ impl TryFrom<VerificationRequest> for solidity::multi_part::VerificationRequest
             .0;
         let compiler_version = Version::from_str(&value.compiler_version)
             .map_err(|err| error::ErrorBadRequest(format!("Invalid compiler version: {}", err)))?;
+        let contract_name = value
+            .contract_name
+            .as_deref()
+            .map(ContractName::from_str)
+            .transpose()
+            .map_err(|err| error::ErrorBadRequest(format!("Invalid contract name: {}", err)))?;
+        let artifacts = value
+            .artifacts
+            .unwrap_or_default()
+            .iter()
+            .map(|artifact| ArtifactKind::from_str(artifact))
+            .collect::<Result<_, _>>()
+            .map_err(|err| error::ErrorBadRequest(format!("Invalid artifacts: {}", err)))?;
         Ok(Self {
             deployed_bytecode,
             creation_bytecode,
             compiler_version,
+            contract_name,
+            normalize_sources: value.normalize_sources.unwrap_or_default(),
+            artifacts,
             content: value.content.try_into()?,
         })
     }
@@ -61,34 +167,135 @@ impl TryFrom<MultiPartFiles> for solidity::multi_part::MultiFileContent {
             None
         };
 
+        let remappings = value
+            .remappings
+            .unwrap_or_default()
+            .iter()
+            .map(|remapping| {
+                Remapping::from_str(remapping).map_err(|err| {
+                    error::ErrorBadRequest(format!("Invalid remapping {}: {}", remapping, err))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
         Ok(Self {
             sources,
             evm_version,
+            optimization: value.optimization,
             optimization_runs: value.optimization_runs,
+            optimizer_details: value.optimizer_details,
+            via_ir: value.via_ir,
+            remappings,
             contract_libraries: value.contract_libraries,
         })
     }
 }
 
-#[instrument(skip(compilers, params), level = "debug")]
+#[instrument(skip(compilers, packages, trace_client, params), level = "debug")]
 pub async fn verify(
     compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    trace_client: Option<web::Data<TraceApiClient>>,
     params: Json<VerificationRequest>,
 ) -> Result<Json<VerificationResponse>, actix_web::Error> {
-    let request = params.into_inner().try_into()?;
+    job::reject_callback_url(params.callback_url.as_ref())?;
+    let request = params
+        .into_inner()
+        .with_creation_bytecode(trace_client.map(web::Data::into_inner))
+        .await?
+        .try_into()?;
 
-    let result = solidity::multi_part::verify(compilers.into_inner(), request).await;
+    verify_request(compilers, packages, request).await.map(Json)
+}
+
+/// Starts the verification in background, returning the id of the job to poll.
+#[instrument(skip(compilers, packages, trace_client, jobs, params), level = "debug")]
+pub async fn verify_async(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    trace_client: Option<web::Data<TraceApiClient>>,
+    jobs: web::Data<Jobs>,
+    params: Json<VerificationRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut params = params.into_inner();
+    let callback_url = job::validate_callback_url(&jobs, params.callback_url.take())?;
+    // Invalid requests are rejected before the job is created.
+    // The creation bytecode, if needed, is retrieved as part of the job
+    params.creation_transaction()?;
+    let _: solidity::multi_part::VerificationRequest = params.clone().try_into()?;
+
+    let trace_client = trace_client.map(web::Data::into_inner);
+    let verification = {
+        let params = params.clone();
+        async move {
+            let params = params.with_creation_bytecode(trace_client).await?;
+            verify_request(compilers, packages, params.try_into()?).await
+        }
+    };
+    let id = jobs
+        .into_inner()
+        .spawn(
+            JobRequest::SolidityMultiPart(params),
+            verification,
+            callback_url,
+        )
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(job::accepted(id))
+}
+
+/// Compiles the sources once and verifies them against each of the deployments.
+#[instrument(skip(compilers, packages, params), level = "debug")]
+pub async fn verify_deployments(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    params: Json<DeploymentsRequest>,
+) -> Result<Json<Vec<DeploymentResponse>>, actix_web::Error> {
+    let (deployments, request) = params.into_inner().split();
+    let (addresses, deployments) = deployments::parse(deployments)?;
+    let request = request.try_into()?;
+
+    let packages = packages.as_ref().map(|packages| packages.get_ref());
+    let result = solidity::multi_part::verify_deployments(
+        compilers.into_inner(),
+        request,
+        deployments,
+        packages,
+    )
+    .await;
+    deployments::responses(addresses, result, "solidity", "multi-part").map(Json)
+}
+
+/// Parses and verifies the request received not via HTTP (e.g., restored jobs or gRPC calls).
+pub async fn process_request(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    params: VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
+    verify_request(compilers, packages, params.try_into()?).await
+}
+
+async fn verify_request(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    request: solidity::multi_part::VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
+    let packages = packages.as_ref().map(|packages| packages.get_ref());
+    let result = solidity::multi_part::verify(compilers.into_inner(), request, packages).await;
 
     if let Ok(verification_success) = result {
         let response = VerificationResponse::ok(verification_success.into());
         metrics::count_verify_contract("solidity", &response.status, "multi-part");
-        return Ok(Json(response));
+        return Ok(response);
     }
 
     let err = result.unwrap_err();
     match err {
-        VerificationError::Compilation(_) | VerificationError::NoMatchingContracts => {
-            Ok(Json(VerificationResponse::err(err)))
+        VerificationError::Compilation(_)
+        | VerificationError::NoMatchingContracts(_)
+        | VerificationError::ContractNotFound(_)
+        | VerificationError::ContractMismatch { .. } => {
+            Ok(VerificationResponse::verification_err(err))
         }
         VerificationError::Initialization(_) | VerificationError::VersionNotFound(_) => {
             Err(error::ErrorBadRequest(err))
@@ -101,6 +308,7 @@ pub async fn verify(
 mod tests {
     use super::*;
     use crate::tests::parse::test_deserialize_ok;
+    use ethers_solc::artifacts::YulDetails;
     use pretty_assertions::assert_eq;
 
     fn sources(sources: &[(&str, &str)]) -> BTreeMap<PathBuf, String> {
@@ -127,11 +335,21 @@ mod tests {
                 VerificationRequest {
                     deployed_bytecode: "0x6001".into(),
                     creation_bytecode: "0x6001".into(),
+                    creation_transaction_hash: None,
+                    contract_address: None,
                     compiler_version: "0.8.3".into(),
+                    contract_name: None,
+                    normalize_sources: None,
+                    artifacts: None,
+                    callback_url: None,
                     content: MultiPartFiles {
                         sources: sources(&[("source.sol", "pragma")]),
                         evm_version: format!("{}", EvmVersion::London),
+                        optimization: None,
                         optimization_runs: Some(200),
+                        optimizer_details: None,
+                        via_ir: None,
+                        remappings: None,
                         contract_libraries: None,
                     },
                 },
@@ -155,7 +373,13 @@ mod tests {
                 VerificationRequest {
                     deployed_bytecode: "0x6001".into(),
                     creation_bytecode: "0x6001".into(),
+                    creation_transaction_hash: None,
+                    contract_address: None,
                     compiler_version: "0.8.3".into(),
+                    contract_name: None,
+                    normalize_sources: None,
+                    artifacts: None,
+                    callback_url: None,
                     content: MultiPartFiles {
                         sources: sources(&[
                             ("source.sol", "source"),
@@ -164,7 +388,11 @@ mod tests {
                             ("metadata.json", "metadata"),
                         ]),
                         evm_version: format!("{}", ethers_solc::EvmVersion::SpuriousDragon),
+                        optimization: None,
                         optimization_runs: None,
+                        optimizer_details: None,
+                        via_ir: None,
+                        remappings: None,
                         contract_libraries: Some(BTreeMap::from([(
                             "Lib.sol".into(),
                             "0x1234567890123456789012345678901234567890".into(),
@@ -172,16 +400,92 @@ mod tests {
                     },
                 },
             ),
+            (
+                r#"{
+                    "deployed_bytecode": "0x6001",
+                    "creation_bytecode": "0x6001",
+                    "compiler_version": "0.8.17",
+                    "sources": {
+                        "source.sol": "pragma"
+                    },
+                    "evm_version": "default",
+                    "optimization": true,
+                    "optimization_runs": 1000,
+                    "optimizer_details": {
+                        "yul": true,
+                        "yulDetails": {
+                            "stackAllocation": true
+                        }
+                    },
+                    "via_ir": true,
+                    "remappings": ["@openzeppelin/=lib/openzeppelin-contracts/"],
+                    "contract_libraries": {
+                        "lib/Lib.sol:Lib": "0x1234567890123456789012345678901234567890"
+                    },
+                    "artifacts": ["warnings", "source_maps"]
+                }"#,
+                VerificationRequest {
+                    deployed_bytecode: "0x6001".into(),
+                    creation_bytecode: "0x6001".into(),
+                    creation_transaction_hash: None,
+                    contract_address: None,
+                    compiler_version: "0.8.17".into(),
+                    contract_name: None,
+                    normalize_sources: None,
+                    artifacts: Some(vec!["warnings".into(), "source_maps".into()]),
+                    callback_url: None,
+                    content: MultiPartFiles {
+                        sources: sources(&[("source.sol", "pragma")]),
+                        evm_version: "default".into(),
+                        optimization: Some(true),
+                        optimization_runs: Some(1000),
+                        optimizer_details: Some(OptimizerDetails {
+                            yul: Some(true),
+                            yul_details: Some(YulDetails {
+                                stack_allocation: Some(true),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                        via_ir: Some(true),
+                        remappings: Some(vec!["@openzeppelin/=lib/openzeppelin-contracts/".into()]),
+                        contract_libraries: Some(BTreeMap::from([(
+                            "lib/Lib.sol:Lib".into(),
+                            "0x1234567890123456789012345678901234567890".into(),
+                        )])),
+                    },
+                },
+            ),
         ])
     }
 
+    #[test]
+    fn invalid_remapping_should_fail() {
+        let multi_part = MultiPartFiles {
+            sources: BTreeMap::new(),
+            evm_version: "default".to_string(),
+            optimization: None,
+            optimization_runs: None,
+            optimizer_details: None,
+            via_ir: None,
+            remappings: Some(vec!["no_separator".into()]),
+            contract_libraries: None,
+        };
+        solidity::multi_part::MultiFileContent::try_from(multi_part)
+            .expect_err("Remapping without '=' should be rejected");
+    }
+
     #[test]
     // 'default' should result in None in MultiFileContent
     fn default_evm_version() {
         let multi_part = MultiPartFiles {
             sources: BTreeMap::new(),
             evm_version: "default".to_string(),
+            optimization: None,
             optimization_runs: None,
+            optimizer_details: None,
+            via_ir: None,
+            remappings: None,
             contract_libraries: None,
         };
         let content = solidity::multi_part::MultiFileContent::try_from(multi_part)
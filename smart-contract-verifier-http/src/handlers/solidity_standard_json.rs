@@ -1,26 +1,110 @@
-use crate::{metrics, verification_response::VerificationResponse, DisplayBytes};
-use actix_web::{error, web, web::Json};
+use super::{
+    creation_transaction,
+    deployments::{self, Deployment, DeploymentResponse},
+    job,
+};
+use crate::{
+    jobs::{JobRequest, Jobs},
+    metrics,
+    verification_response::VerificationResponse,
+    DisplayBytes,
+};
+use actix_web::{error, web, web::Json, HttpResponse};
 use anyhow::anyhow;
 use ethers_solc::CompilerInput;
-use serde::Deserialize;
-use smart_contract_verifier::{solidity, Compilers, SolidityCompiler, VerificationError, Version};
-use std::str::FromStr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{
+    solidity, traces::api::CreationInputRequest, ArtifactKind, Compilers, ContractName,
+    ExtraSettings, SolidityCompiler, TraceApiClient, VerificationError, Version,
+};
+use std::{str::FromStr, sync::Arc};
 use thiserror::Error;
 use tracing::instrument;
+use url::Url;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "SolidityStandardJsonVerificationRequest")]
 pub struct VerificationRequest {
     pub deployed_bytecode: String,
+    /// May be omitted if `creation_transaction_hash` is specified
+    #[serde(default)]
     pub creation_bytecode: String,
+    /// Transaction the contract has been created in from another contract (via CREATE/CREATE2).
+    /// If specified, the creation bytecode is retrieved from the transaction traces
+    pub creation_transaction_hash: Option<String>,
+    /// Address of the contract created in `creation_transaction_hash`
+    pub contract_address: Option<String>,
     pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+    /// Url the finished job is posted to. Accepted by asynchronous endpoints only
+    pub callback_url: Option<Url>,
 
     #[serde(flatten)]
     pub content: StandardJson,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct StandardJson {
-    input: String,
+    pub input: String,
+}
+
+/// Same as [`VerificationRequest`], but with the bytecodes of several deployments.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "SolidityStandardJsonDeploymentsRequest")]
+pub struct DeploymentsRequest {
+    pub deployments: Vec<Deployment>,
+    pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub content: StandardJson,
+}
+
+impl DeploymentsRequest {
+    /// Bytecodes of the returned request are left empty, as they are taken from the deployments.
+    fn split(self) -> (Vec<Deployment>, VerificationRequest) {
+        let request = VerificationRequest {
+            deployed_bytecode: String::new(),
+            creation_bytecode: String::new(),
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version: self.compiler_version,
+            contract_name: self.contract_name,
+            normalize_sources: self.normalize_sources,
+            artifacts: self.artifacts,
+            callback_url: None,
+            content: self.content,
+        };
+        (self.deployments, request)
+    }
+}
+
+impl VerificationRequest {
+    fn creation_transaction(&self) -> Result<Option<CreationInputRequest>, actix_web::Error> {
+        creation_transaction::parse(
+            self.creation_transaction_hash.as_deref(),
+            self.contract_address.as_deref(),
+            &self.creation_bytecode,
+        )
+    }
+
+    /// Retrieves the creation bytecode from the traces of the creation transaction, if one is specified.
+    pub async fn with_creation_bytecode(
+        mut self,
+        trace_client: Option<Arc<TraceApiClient>>,
+    ) -> Result<Self, actix_web::Error> {
+        let creation_transaction = self.creation_transaction()?;
+        if let Some(request) = creation_transaction {
+            self.creation_bytecode =
+                creation_transaction::creation_bytecode(trace_client, request).await?;
+        }
+        Ok(self)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -43,10 +127,26 @@ impl TryFrom<VerificationRequest> for solidity::standard_json::VerificationReque
             .0;
         let compiler_version = Version::from_str(&value.compiler_version)
             .map_err(|err| anyhow!("Invalid compiler version: {}", err))?;
+        let contract_name = value
+            .contract_name
+            .as_deref()
+            .map(ContractName::from_str)
+            .transpose()
+            .map_err(|err| anyhow!("Invalid contract name: {}", err))?;
+        let artifacts = value
+            .artifacts
+            .unwrap_or_default()
+            .iter()
+            .map(|artifact| ArtifactKind::from_str(artifact))
+            .collect::<Result<_, _>>()
+            .map_err(|err| anyhow!("Invalid artifacts: {}", err))?;
         Ok(Self {
             deployed_bytecode,
             creation_bytecode,
             compiler_version,
+            contract_name,
+            normalize_sources: value.normalize_sources.unwrap_or_default(),
+            artifacts,
             content: value.content.try_into()?,
         })
     }
@@ -56,19 +156,33 @@ impl TryFrom<StandardJson> for solidity::standard_json::StandardJsonContent {
     type Error = ParseError;
 
     fn try_from(value: StandardJson) -> Result<Self, Self::Error> {
-        let input: CompilerInput = serde_json::from_str(&value.input)?;
+        let input: serde_json::Value = serde_json::from_str(&value.input)?;
+        // Not supported by `CompilerInput`, so would be lost during deserialization
+        let append_cbor = input
+            .pointer("/settings/metadata/appendCBOR")
+            .and_then(serde_json::Value::as_bool);
+        let input: CompilerInput = serde_json::from_value(input)?;
 
-        Ok(Self { input })
+        Ok(Self {
+            input,
+            extra_settings: ExtraSettings { append_cbor },
+        })
     }
 }
 
-#[instrument(skip(compilers, params), level = "debug")]
+#[instrument(skip(compilers, trace_client, params), level = "debug")]
 pub async fn verify(
     compilers: web::Data<Compilers<SolidityCompiler>>,
+    trace_client: Option<web::Data<TraceApiClient>>,
     params: Json<VerificationRequest>,
 ) -> Result<Json<VerificationResponse>, actix_web::Error> {
+    job::reject_callback_url(params.callback_url.as_ref())?;
+    let params = params
+        .into_inner()
+        .with_creation_bytecode(trace_client.map(web::Data::into_inner))
+        .await?;
     let request = {
-        let request: Result<_, ParseError> = params.into_inner().try_into();
+        let request: Result<_, ParseError> = params.try_into();
         if let Err(err) = request {
             match err {
                 ParseError::InvalidContent(_) => return Err(error::ErrorBadRequest(err)),
@@ -78,18 +192,117 @@ pub async fn verify(
         request.unwrap()
     };
 
+    verify_request(compilers, request).await.map(Json)
+}
+
+/// Starts the verification in background, returning the id of the job to poll.
+#[instrument(skip(compilers, trace_client, jobs, params), level = "debug")]
+pub async fn verify_async(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    trace_client: Option<web::Data<TraceApiClient>>,
+    jobs: web::Data<Jobs>,
+    params: Json<VerificationRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut params = params.into_inner();
+    let callback_url = job::validate_callback_url(&jobs, params.callback_url.take())?;
+    // Invalid requests are rejected before the job is created.
+    // The creation bytecode, if needed, is retrieved as part of the job
+    params.creation_transaction()?;
+    let request: Result<solidity::standard_json::VerificationRequest, _> =
+        params.clone().try_into();
+    if let Err(err @ ParseError::InvalidContent(_)) = request {
+        return Err(error::ErrorBadRequest(err));
+    }
+
+    let trace_client = trace_client.map(web::Data::into_inner);
+    let verification = {
+        let params = params.clone();
+        async move {
+            let params = params.with_creation_bytecode(trace_client).await?;
+            process_request(compilers, params).await
+        }
+    };
+    let id = jobs
+        .into_inner()
+        .spawn(
+            JobRequest::SolidityStandardJson(params),
+            verification,
+            callback_url,
+        )
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(job::accepted(id))
+}
+
+/// Compiles the input once and verifies it against each of the deployments.
+/// As in the single deployment endpoint, invalid verification data
+/// results in the failure responses rather than in the bad request.
+#[instrument(skip(compilers, params), level = "debug")]
+pub async fn verify_deployments(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    params: Json<DeploymentsRequest>,
+) -> Result<Json<Vec<DeploymentResponse>>, actix_web::Error> {
+    let (deployments, request) = params.into_inner().split();
+    let (addresses, deployments) = deployments::parse(deployments)?;
+    let request: Result<solidity::standard_json::VerificationRequest, _> = request.try_into();
+    let request = match request {
+        Ok(request) => request,
+        Err(err @ ParseError::InvalidContent(_)) => return Err(error::ErrorBadRequest(err)),
+        Err(err @ ParseError::BadRequest(_)) => {
+            let response = VerificationResponse::err(err);
+            return Ok(Json(deployments::failed(addresses, response)));
+        }
+    };
+
+    let result =
+        solidity::standard_json::verify_deployments(compilers.into_inner(), request, deployments)
+            .await;
+    deployments::responses(addresses, result, "solidity", "json").map(Json)
+}
+
+/// Parses and verifies the request received not via HTTP (e.g., restored jobs or gRPC calls),
+/// or converted from the other input format (e.g., build-info files).
+pub async fn process_request(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    params: VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
+    match params.try_into() {
+        Err(err @ ParseError::InvalidContent(_)) => Err(error::ErrorBadRequest(err)),
+        request => verify_parsed(compilers, request).await,
+    }
+}
+
+/// As in the synchronous endpoint, invalid verification data
+/// results in the failure response rather than in the bad request.
+async fn verify_parsed(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    request: Result<solidity::standard_json::VerificationRequest, ParseError>,
+) -> Result<VerificationResponse, actix_web::Error> {
+    match request {
+        Ok(request) => verify_request(compilers, request).await,
+        Err(err) => Ok(VerificationResponse::err(err)),
+    }
+}
+
+async fn verify_request(
+    compilers: web::Data<Compilers<SolidityCompiler>>,
+    request: solidity::standard_json::VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
     let result = solidity::standard_json::verify(compilers.into_inner(), request).await;
 
     if let Ok(verification_success) = result {
         let response = VerificationResponse::ok(verification_success.into());
         metrics::count_verify_contract("solidity", &response.status, "json");
-        return Ok(Json(response));
+        return Ok(response);
     }
 
     let err = result.unwrap_err();
     match err {
-        VerificationError::Compilation(_) | VerificationError::NoMatchingContracts => {
-            Ok(Json(VerificationResponse::err(err)))
+        VerificationError::Compilation(_)
+        | VerificationError::NoMatchingContracts(_)
+        | VerificationError::ContractNotFound(_)
+        | VerificationError::ContractMismatch { .. } => {
+            Ok(VerificationResponse::verification_err(err))
         }
         VerificationError::Initialization(_) | VerificationError::VersionNotFound(_) => {
             Err(error::ErrorBadRequest(err))
@@ -130,4 +343,58 @@ mod tests {
             .try_into()
             .expect("failed to convert to standard json");
     }
+
+    #[test]
+    fn parse_standard_json_with_append_cbor() {
+        let content = StandardJson {
+            input: r#"{"language": "Solidity", "sources": {}, "settings": {"metadata": {"appendCBOR": false}, "optimizer": {}}}"#.into(),
+        };
+
+        let content: solidity::standard_json::StandardJsonContent = content
+            .try_into()
+            .expect("failed to convert to standard json");
+        assert_eq!(
+            Some(false),
+            content.extra_settings.append_cbor,
+            "Invalid appendCBOR"
+        );
+    }
+
+    #[test]
+    fn parse_standard_json_with_contract_name() {
+        let request = |contract_name: &str| VerificationRequest {
+            deployed_bytecode: "0x6001".into(),
+            creation_bytecode: "0x6001".into(),
+            creation_transaction_hash: None,
+            contract_address: None,
+            compiler_version: "v0.8.2+commit.661d1103".into(),
+            contract_name: Some(contract_name.into()),
+            normalize_sources: None,
+            artifacts: None,
+            callback_url: None,
+            content: StandardJson {
+                input: r#"{"language": "Solidity", "sources": {}, "settings": {"optimizer": {}}}"#
+                    .into(),
+            },
+        };
+
+        let parsed: solidity::standard_json::VerificationRequest = request("src/Foo.sol:Foo")
+            .try_into()
+            .expect("failed to convert to standard json request");
+        assert_eq!(
+            Some(ContractName {
+                file_path: Some("src/Foo.sol".into()),
+                name: "Foo".into()
+            }),
+            parsed.contract_name,
+            "Invalid contract name"
+        );
+
+        let result: Result<solidity::standard_json::VerificationRequest, _> =
+            request("src/Foo.sol:").try_into();
+        assert!(
+            matches!(result, Err(ParseError::BadRequest(_))),
+            "Empty contract name should be rejected"
+        );
+    }
 }
@@ -4,9 +4,11 @@ use actix_web::{
     web::{self, Json},
     Error,
 };
+use schemars::JsonSchema;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(rename = "SolidityVersionsResponse")]
 pub struct VersionsResponse {
     pub versions: Vec<String>,
 }
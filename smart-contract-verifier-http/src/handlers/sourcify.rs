@@ -1,5 +1,6 @@
 use crate::{metrics, verification_response::VerificationResponse};
 use actix_web::{error, web, web::Json};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use smart_contract_verifier::{
     sourcify::{api, Error},
@@ -8,8 +9,9 @@ use smart_contract_verifier::{
 use std::collections::BTreeMap;
 use tracing::instrument;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+#[schemars(rename = "SourcifyApiRequest")]
 pub struct ApiRequest {
     pub address: String,
     pub chain: String,
@@ -33,7 +35,17 @@ pub async fn verify(
     sourcify_client: web::Data<SourcifyApiClient>,
     params: Json<ApiRequest>,
 ) -> Result<Json<VerificationResponse>, actix_web::Error> {
-    let request = params.into_inner().try_into()?;
+    process_request(sourcify_client, params.into_inner())
+        .await
+        .map(Json)
+}
+
+/// Parses and verifies the request received not via HTTP (e.g., gRPC calls).
+pub async fn process_request(
+    sourcify_client: web::Data<SourcifyApiClient>,
+    params: ApiRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
+    let request = params.into();
 
     let response = api::verify(sourcify_client.into_inner(), request).await;
     let response = match response {
@@ -45,5 +57,5 @@ pub async fn verify(
         },
     }?;
     metrics::count_verify_contract("solidity", &response.status, "sourcify");
-    Ok(Json(response))
+    Ok(response)
 }
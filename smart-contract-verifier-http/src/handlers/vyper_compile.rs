@@ -0,0 +1,46 @@
+use super::compile::select_default_outputs;
+use crate::metrics;
+use actix_web::{error, web, web::Json};
+use ethers_solc::{CompilerInput, CompilerOutput};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use smart_contract_verifier::{CompilerError, Compilers, ExtraSettings, Version, VyperCompiler};
+use std::str::FromStr;
+use tracing::instrument;
+
+/// Outputs selected for every contract if the input does not specify `outputSelection`
+const DEFAULT_CONTRACT_OUTPUTS: [&str; 3] = ["abi", "evm.bytecode", "evm.deployedBytecode"];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(rename = "VyperCompileRequest")]
+pub struct CompileRequest {
+    pub compiler_version: String,
+    /// Vyper standard json input
+    pub input: String,
+}
+
+#[instrument(skip(compilers, params), level = "debug")]
+pub async fn compile(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    params: Json<CompileRequest>,
+) -> Result<Json<CompilerOutput>, actix_web::Error> {
+    let request = params.into_inner();
+    let compiler_version = Version::from_str(&request.compiler_version)
+        .map_err(|err| error::ErrorBadRequest(format!("Invalid compiler version: {}", err)))?;
+    let mut input: CompilerInput = serde_json::from_str(&request.input).map_err(|err| {
+        error::ErrorBadRequest(format!("content is not valid standard json: {}", err))
+    })?;
+    select_default_outputs(&mut input, &DEFAULT_CONTRACT_OUTPUTS);
+
+    let result = compilers
+        .compile_output(&compiler_version, &input, &ExtraSettings::default())
+        .await;
+    match result {
+        Ok(output) => {
+            metrics::count_compile_contract("vyper", !output.has_error());
+            Ok(Json(output))
+        }
+        Err(err @ CompilerError::VersionNotFound(_)) => Err(error::ErrorBadRequest(err)),
+        Err(err) => Err(error::ErrorInternalServerError(err)),
+    }
+}
@@ -1,27 +1,78 @@
-use crate::{metrics, verification_response::VerificationResponse, DisplayBytes};
-use actix_web::{error, web, web::Json};
+use super::{
+    deployments::{self, Deployment, DeploymentResponse},
+    job,
+};
+use crate::{
+    jobs::{JobRequest, Jobs},
+    metrics,
+    verification_response::VerificationResponse,
+    DisplayBytes,
+};
+use actix_web::{error, web, web::Json, HttpResponse};
 use ethers_solc::EvmVersion;
-use serde::Deserialize;
-use smart_contract_verifier::{vyper, Compilers, VerificationError, Version, VyperCompiler};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smart_contract_verifier::{
+    vyper, ArtifactKind, Compilers, ContractName, VerificationError, Version, VyperCompiler,
+};
 use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 use tracing::instrument;
+use url::Url;
 
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema)]
+#[schemars(rename = "VyperMultiPartVerificationRequest")]
 pub struct VerificationRequest {
     pub deployed_bytecode: String,
     pub creation_bytecode: String,
     pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+    /// Url the finished job is posted to. Accepted by asynchronous endpoints only
+    pub callback_url: Option<Url>,
 
     #[serde(flatten)]
     pub content: MultiPartFiles,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema)]
+#[schemars(rename = "VyperMultiPartFiles")]
 pub struct MultiPartFiles {
     pub sources: BTreeMap<PathBuf, String>,
     pub evm_version: Option<String>,
 }
 
+/// Same as [`VerificationRequest`], but with the bytecodes of several deployments.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema)]
+#[schemars(rename = "VyperMultiPartDeploymentsRequest")]
+pub struct DeploymentsRequest {
+    pub deployments: Vec<Deployment>,
+    pub compiler_version: String,
+    pub contract_name: Option<String>,
+    pub normalize_sources: Option<bool>,
+    pub artifacts: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub content: MultiPartFiles,
+}
+
+impl DeploymentsRequest {
+    /// Bytecodes of the returned request are left empty, as they are taken from the deployments.
+    fn split(self) -> (Vec<Deployment>, VerificationRequest) {
+        let request = VerificationRequest {
+            deployed_bytecode: String::new(),
+            creation_bytecode: String::new(),
+            compiler_version: self.compiler_version,
+            contract_name: self.contract_name,
+            normalize_sources: self.normalize_sources,
+            artifacts: self.artifacts,
+            callback_url: None,
+            content: self.content,
+        };
+        (self.deployments, request)
+    }
+}
+
 impl TryFrom<VerificationRequest> for vyper::multi_part::VerificationRequest {
     type Error = actix_web::Error;
 
@@ -34,10 +85,26 @@ impl TryFrom<VerificationRequest> for vyper::multi_part::VerificationRequest {
             .0;
         let compiler_version = Version::from_str(&value.compiler_version)
             .map_err(|err| error::ErrorBadRequest(format!("Invalid compiler version: {}", err)))?;
+        let contract_name = value
+            .contract_name
+            .as_deref()
+            .map(ContractName::from_str)
+            .transpose()
+            .map_err(|err| error::ErrorBadRequest(format!("Invalid contract name: {}", err)))?;
+        let artifacts = value
+            .artifacts
+            .unwrap_or_default()
+            .iter()
+            .map(|artifact| ArtifactKind::from_str(artifact))
+            .collect::<Result<_, _>>()
+            .map_err(|err| error::ErrorBadRequest(format!("Invalid artifacts: {}", err)))?;
         Ok(Self {
             deployed_bytecode,
             creation_bytecode,
             compiler_version,
+            contract_name,
+            normalize_sources: value.normalize_sources.unwrap_or_default(),
+            artifacts,
             content: value.content.try_into()?,
         })
     }
@@ -72,20 +139,77 @@ pub async fn verify(
     compilers: web::Data<Compilers<VyperCompiler>>,
     params: Json<VerificationRequest>,
 ) -> Result<Json<VerificationResponse>, actix_web::Error> {
+    job::reject_callback_url(params.callback_url.as_ref())?;
     let request = params.into_inner().try_into()?;
 
+    verify_request(compilers, request).await.map(Json)
+}
+
+/// Starts the verification in background, returning the id of the job to poll.
+#[instrument(skip(compilers, jobs, params), level = "debug")]
+pub async fn verify_async(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    jobs: web::Data<Jobs>,
+    params: Json<VerificationRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut params = params.into_inner();
+    let callback_url = job::validate_callback_url(&jobs, params.callback_url.take())?;
+    let request = params.clone().try_into()?;
+
+    let id = jobs
+        .into_inner()
+        .spawn(
+            JobRequest::VyperMultiPart(params),
+            verify_request(compilers, request),
+            callback_url,
+        )
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(job::accepted(id))
+}
+
+/// Compiles the sources once and verifies them against each of the deployments.
+#[instrument(skip(compilers, params), level = "debug")]
+pub async fn verify_deployments(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    params: Json<DeploymentsRequest>,
+) -> Result<Json<Vec<DeploymentResponse>>, actix_web::Error> {
+    let (deployments, request) = params.into_inner().split();
+    let (addresses, deployments) = deployments::parse(deployments)?;
+    let request = request.try_into()?;
+
+    let result =
+        vyper::multi_part::verify_deployments(compilers.into_inner(), request, deployments).await;
+    deployments::responses(addresses, result, "vyper", "multi-part").map(Json)
+}
+
+/// Parses and verifies the request received not via HTTP (e.g., restored jobs or gRPC calls).
+pub async fn process_request(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    params: VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
+    verify_request(compilers, params.try_into()?).await
+}
+
+async fn verify_request(
+    compilers: web::Data<Compilers<VyperCompiler>>,
+    request: vyper::multi_part::VerificationRequest,
+) -> Result<VerificationResponse, actix_web::Error> {
     let result = vyper::multi_part::verify(compilers.into_inner(), request).await;
 
     if let Ok(verification_success) = result {
         let response = VerificationResponse::ok(verification_success.into());
         metrics::count_verify_contract("vyper", &response.status, "multi-part");
-        return Ok(Json(response));
+        return Ok(response);
     }
 
     let err = result.unwrap_err();
     match err {
-        VerificationError::Compilation(_) | VerificationError::NoMatchingContracts => {
-            Ok(Json(VerificationResponse::err(err)))
+        VerificationError::Compilation(_)
+        | VerificationError::NoMatchingContracts(_)
+        | VerificationError::ContractNotFound(_)
+        | VerificationError::ContractMismatch { .. } => {
+            Ok(VerificationResponse::verification_err(err))
         }
         VerificationError::Initialization(_) | VerificationError::VersionNotFound(_) => {
             Err(error::ErrorBadRequest(err))
@@ -0,0 +1,629 @@
+mod queue;
+mod runner;
+
+pub use queue::{JobQueue, JobRequest, PendingJob};
+pub use runner::JobRunner;
+
+use crate::{
+    metrics::JOBS_IN_QUEUE, verification_response::VerificationResponse, webhooks::Webhooks,
+};
+use actix_web::{error, web};
+use anyhow::anyhow;
+use futures::FutureExt;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::Serialize;
+use smart_contract_verifier::{with_progress_listener, ProgressListener, VerificationStage};
+use std::{
+    collections::HashMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use url::Url;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    FetchingCompiler,
+    Compiling,
+    Comparing,
+    /// Verification has finished, and its result is available in `response`.
+    Done,
+    /// Verification could not be performed (e.g., invalid bytecode or internal error),
+    /// the reason is available in `error`.
+    Failed,
+}
+
+impl From<VerificationStage> for JobStatus {
+    fn from(stage: VerificationStage) -> Self {
+        match stage {
+            VerificationStage::FetchingCompiler => Self::FetchingCompiler,
+            VerificationStage::Compiling => Self::Compiling,
+            VerificationStage::Comparing => Self::Comparing,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub response: Option<VerificationResponse>,
+    pub error: Option<String>,
+}
+
+struct Entry {
+    job: Job,
+    finished_at: Option<Instant>,
+}
+
+/// Verifications run in background. Results of finished verifications
+/// are kept for `ttl`, while unfinished jobs never expire.
+///
+/// If the `queue` is configured, requests of unfinished jobs are persisted,
+/// so that the jobs are resumed after restart via [`Jobs::resume`].
+pub struct Jobs {
+    jobs: Mutex<HashMap<String, Entry>>,
+    ttl: Duration,
+    webhooks: Option<Webhooks>,
+    queue: Option<JobQueue>,
+}
+
+impl Jobs {
+    pub fn new(ttl: Duration, webhooks: Option<Webhooks>, queue: Option<JobQueue>) -> Self {
+        Self {
+            jobs: Default::default(),
+            ttl,
+            webhooks,
+            queue,
+        }
+    }
+
+    /// Callbacks are accepted only if the webhooks are configured.
+    pub fn webhooks(&self) -> Option<&Webhooks> {
+        self.webhooks.as_ref()
+    }
+
+    /// Runs the `verification` in background, returning the id of the created job.
+    /// If `callback_url` is specified, the finished job is posted there.
+    /// The `request` is stored to the queue until the job finishes.
+    ///
+    /// The verification is run on the current thread, so that the future is not required to be `Send`.
+    pub async fn spawn<F>(
+        self: Arc<Self>,
+        request: JobRequest,
+        verification: F,
+        callback_url: Option<Url>,
+    ) -> anyhow::Result<String>
+    where
+        F: Future<Output = Result<VerificationResponse, actix_web::Error>> + 'static,
+    {
+        let id = {
+            let mut jobs = self.lock();
+            self.remove_expired(&mut jobs);
+            let id = loop {
+                let id = new_job_id();
+                if !jobs.contains_key(&id) {
+                    break id;
+                }
+            };
+            insert_queued(&mut jobs, id.clone());
+            id
+        };
+        // Persisted without holding the lock and outside of the worker thread,
+        // as the write waits for the data to reach the disk
+        if let Some(queue) = self.queue.clone() {
+            let job = PendingJob {
+                request,
+                callback_url: callback_url.clone(),
+                resumes: 0,
+            };
+            let job_id = id.clone();
+            let persisted = web::block(move || queue.push(&job_id, &job))
+                .await
+                .map_err(|err| anyhow!("{}", err))
+                .and_then(|result| result);
+            if let Err(err) = persisted {
+                self.remove_unfinished(&id);
+                return Err(err);
+            }
+        }
+
+        actix_web::rt::spawn(self.run(id.clone(), verification, callback_url));
+        Ok(id)
+    }
+
+    /// Restarts the jobs left unfinished by the previous run of the service,
+    /// returning their number. Verifications are created via `verification`,
+    /// and at most `concurrency` of them are run at the same time.
+    ///
+    /// Jobs which have been already resumed `max_resumes` times are failed instead,
+    /// as they may be the ones interrupting the service.
+    pub fn resume<F, Fut>(
+        self: Arc<Self>,
+        verification: F,
+        concurrency: usize,
+        max_resumes: u32,
+    ) -> anyhow::Result<usize>
+    where
+        F: Fn(JobRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<VerificationResponse, actix_web::Error>> + Send + 'static,
+    {
+        let queue = match &self.queue {
+            Some(queue) => queue.clone(),
+            None => return Ok(0),
+        };
+        let pending = queue.load()?;
+        {
+            let mut jobs = self.lock();
+            for (id, _) in &pending {
+                insert_queued(&mut jobs, id.clone());
+            }
+        }
+
+        let count = pending.len();
+        let verification = Arc::new(verification);
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        for (id, mut job) in pending {
+            let jobs = self.clone();
+            let queue = queue.clone();
+            let verification = verification.clone();
+            let semaphore = semaphore.clone();
+            // The service is not necessarily started inside actix runtime,
+            // so the jobs are spawned via tokio on the current runtime
+            tokio::spawn(async move {
+                let callback_url = job.callback_url.clone();
+                if job.resumes >= max_resumes {
+                    let message = format!(
+                        "verification has been interrupted by restart {} times",
+                        job.resumes + 1
+                    );
+                    let failure = async move { Err(error::ErrorInternalServerError(message)) };
+                    jobs.run(id, failure, callback_url).await;
+                    return;
+                }
+
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                // The counter is persisted before the verification is started,
+                // so that the job is not retried forever if it crashes the service
+                job.resumes += 1;
+                let request = job.request.clone();
+                let job_id = id.clone();
+                let persisted = tokio::task::spawn_blocking(move || queue.push(&job_id, &job))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result);
+                if let Err(err) = persisted {
+                    tracing::warn!(job_id = %id, "cannot persist resumed job: {:#}", err);
+                }
+                jobs.run(id, verification(request), callback_url).await;
+            });
+        }
+        Ok(count)
+    }
+
+    async fn run<F>(self: Arc<Self>, id: String, verification: F, callback_url: Option<Url>)
+    where
+        F: Future<Output = Result<VerificationResponse, actix_web::Error>>,
+    {
+        let listener = Arc::new(JobProgress {
+            jobs: self.clone(),
+            id: id.clone(),
+        });
+        // Otherwise, the job of the panicked verification would be never finished
+        let verification = AssertUnwindSafe(verification).catch_unwind();
+        // Only the message is kept across the awaits below, as `actix_web::Error` is not `Send`
+        let result = with_progress_listener(listener, verification)
+            .await
+            .unwrap_or_else(|_| Err(error::ErrorInternalServerError("verification has panicked")))
+            .map_err(|err| err.to_string());
+        // Removed before the job is reported as finished, so that the finished job is never resumed
+        self.dequeue(&id).await;
+        let job = self.finish(&id, result);
+        if let (Some(job), Some(url), Some(webhooks)) = (job, callback_url, &self.webhooks) {
+            if let Err(err) = webhooks.deliver(&url, &job).await {
+                tracing::warn!(job_id = %id, %url, "callback delivery failed: {:#}", err);
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        let mut jobs = self.lock();
+        self.remove_expired(&mut jobs);
+        jobs.get(id).map(|entry| entry.job.clone())
+    }
+
+    /// Removes the job which could not be started.
+    fn remove_unfinished(&self, id: &str) {
+        if self.lock().remove(id).is_some() {
+            JOBS_IN_QUEUE.dec();
+        }
+    }
+
+    fn set_status(&self, id: &str, status: JobStatus) {
+        if let Some(entry) = self.lock().get_mut(id) {
+            entry.job.status = status;
+        }
+    }
+
+    fn finish(&self, id: &str, result: Result<VerificationResponse, String>) -> Option<Job> {
+        let mut jobs = self.lock();
+        self.remove_expired(&mut jobs);
+        jobs.get_mut(id).map(|entry| {
+            match result {
+                Ok(response) => {
+                    entry.job.status = JobStatus::Done;
+                    entry.job.response = Some(response);
+                }
+                Err(err) => {
+                    entry.job.status = JobStatus::Failed;
+                    entry.job.error = Some(err);
+                }
+            }
+            entry.finished_at = Some(Instant::now());
+            JOBS_IN_QUEUE.dec();
+            entry.job.clone()
+        })
+    }
+
+    /// Removes the job from the queue. Run outside of the worker thread and
+    /// without holding the jobs lock, as the removal touches the disk.
+    async fn dequeue(&self, id: &str) {
+        if let Some(queue) = self.queue.clone() {
+            let job_id = id.to_string();
+            let removed = tokio::task::spawn_blocking(move || queue.remove(&job_id))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result.map_err(anyhow::Error::from));
+            if let Err(err) = removed {
+                tracing::warn!(
+                    job_id = id,
+                    "cannot remove finished job from queue: {:#}",
+                    err
+                );
+            }
+        }
+    }
+
+    /// Removes the results which have been kept for longer than `ttl`,
+    /// so that the jobs nobody polls do not pile up.
+    fn remove_expired(&self, jobs: &mut HashMap<String, Entry>) {
+        let now = Instant::now();
+        jobs.retain(|_, entry| match entry.finished_at {
+            Some(finished_at) => now.duration_since(finished_at) < self.ttl,
+            None => true,
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.jobs.lock().expect("jobs lock is poisoned")
+    }
+}
+
+struct JobProgress {
+    jobs: Arc<Jobs>,
+    id: String,
+}
+
+impl ProgressListener for JobProgress {
+    fn on_stage(&self, stage: VerificationStage) {
+        self.jobs.set_status(&self.id, stage.into());
+    }
+}
+
+fn insert_queued(jobs: &mut HashMap<String, Entry>, id: String) {
+    let job = Job {
+        id: id.clone(),
+        status: JobStatus::Queued,
+        response: None,
+        error: None,
+    };
+    jobs.insert(
+        id,
+        Entry {
+            job,
+            finished_at: None,
+        },
+    );
+    JOBS_IN_QUEUE.inc();
+}
+
+/// Returns 128-bit random hex string.
+fn new_job_id() -> String {
+    // `ThreadRng` is a cryptographically secure generator, so that ids are not guessable
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handlers::vyper_multi_part,
+        settings::WebhooksSettings,
+        webhooks::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+    };
+    use actix_web::{error, web, App, HttpRequest, HttpResponse, HttpServer};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use tokio::sync::{mpsc, oneshot};
+
+    pub fn vyper_request() -> JobRequest {
+        JobRequest::VyperMultiPart(vyper_multi_part::VerificationRequest {
+            deployed_bytecode: "0x6001".into(),
+            creation_bytecode: "0x6001".into(),
+            compiler_version: "0.3.6+commit.4a2124d0".into(),
+            contract_name: None,
+            normalize_sources: None,
+            artifacts: None,
+            callback_url: None,
+            content: vyper_multi_part::MultiPartFiles {
+                sources: BTreeMap::from([("source.vy".into(), "".into())]),
+                evm_version: None,
+            },
+        })
+    }
+
+    async fn wait_finished(jobs: &Jobs, id: &str) -> Job {
+        loop {
+            let job = jobs.get(id).expect("Job should exist");
+            if matches!(job.status, JobStatus::Done | JobStatus::Failed) {
+                return job;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[actix_rt::test]
+    async fn job_lifecycle() {
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), None, None));
+        let (sender, receiver) = oneshot::channel::<()>();
+        let id = jobs
+            .clone()
+            .spawn(
+                vyper_request(),
+                async move {
+                    receiver.await.expect("Sender is not dropped");
+                    Ok(VerificationResponse::err("No contract could be verified"))
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(JobStatus::Queued, jobs.get(&id).unwrap().status);
+        sender.send(()).unwrap();
+        let job = wait_finished(&jobs, &id).await;
+        assert_eq!(JobStatus::Done, job.status);
+        assert_eq!(
+            Some(VerificationResponse::err("No contract could be verified")),
+            job.response
+        );
+
+        let id = jobs
+            .clone()
+            .spawn(
+                vyper_request(),
+                async { Err(error::ErrorBadRequest("Invalid bytecode")) },
+                None,
+            )
+            .await
+            .unwrap();
+        let job = wait_finished(&jobs, &id).await;
+        assert_eq!(JobStatus::Failed, job.status);
+        assert_eq!(Some("Invalid bytecode".to_string()), job.error);
+    }
+
+    #[actix_rt::test]
+    async fn finished_jobs_expire() {
+        let jobs = Arc::new(Jobs::new(Duration::ZERO, None, None));
+        let id = jobs
+            .clone()
+            .spawn(
+                vyper_request(),
+                async { Ok(VerificationResponse::err("Compilation error")) },
+                None,
+            )
+            .await
+            .unwrap();
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(None, jobs.get(&id), "Finished job should be removed");
+    }
+
+    #[test]
+    fn job_ids_are_unique() {
+        assert_ne!(new_job_id(), new_job_id());
+    }
+
+    #[actix_rt::test]
+    async fn panicked_jobs_fail() {
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), None, None));
+        let id = jobs
+            .clone()
+            .spawn(vyper_request(), async { panic!("verification bug") }, None)
+            .await
+            .unwrap();
+        let job = wait_finished(&jobs, &id).await;
+        assert_eq!(JobStatus::Failed, job.status);
+        assert_eq!(Some("verification has panicked".to_string()), job.error);
+    }
+
+    #[actix_rt::test]
+    async fn unfinished_jobs_are_resumed() {
+        let dir = std::env::temp_dir().join(format!("jobs-queue-{}", new_job_id()));
+        let jobs = Arc::new(Jobs::new(
+            Duration::from_secs(60),
+            None,
+            Some(JobQueue::new(dir.clone()).unwrap()),
+        ));
+        // Verification is never finished, as if the service has been restarted
+        let id = jobs
+            .clone()
+            .spawn(vyper_request(), futures::future::pending(), None)
+            .await
+            .unwrap();
+        let queue = JobQueue::new(dir.clone()).unwrap();
+        assert_eq!(
+            vec![id.clone()],
+            queue
+                .load()
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        );
+
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), None, Some(queue)));
+        let resumed = jobs
+            .clone()
+            .resume(
+                |request| async move {
+                    assert!(matches!(request, JobRequest::VyperMultiPart(_)));
+                    Ok(VerificationResponse::err("Resumed"))
+                },
+                1,
+                3,
+            )
+            .unwrap();
+        assert_eq!(1, resumed);
+        let job = wait_finished(&jobs, &id).await;
+        assert_eq!(Some(VerificationResponse::err("Resumed")), job.response);
+        assert!(
+            JobQueue::new(dir.clone())
+                .unwrap()
+                .load()
+                .unwrap()
+                .is_empty(),
+            "Finished job should be removed from queue"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn jobs_resumed_too_many_times_fail() {
+        let dir = std::env::temp_dir().join(format!("jobs-queue-{}", new_job_id()));
+        let queue = JobQueue::new(dir.clone()).unwrap();
+        let id = new_job_id();
+        queue
+            .push(
+                &id,
+                &PendingJob {
+                    request: vyper_request(),
+                    callback_url: None,
+                    resumes: 2,
+                },
+            )
+            .unwrap();
+
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), None, Some(queue)));
+        let resumed = jobs
+            .clone()
+            .resume(
+                |_| async { Err(error::ErrorInternalServerError("Verified once more")) },
+                1,
+                2,
+            )
+            .unwrap();
+        assert_eq!(1, resumed);
+        let job = wait_finished(&jobs, &id).await;
+        assert_eq!(JobStatus::Failed, job.status);
+        assert_eq!(
+            Some("verification has been interrupted by restart 3 times".to_string()),
+            job.error
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn finished_job_is_posted_to_callback() {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(String, String, web::Bytes)>();
+        let server = HttpServer::new(move || {
+            let sender = sender.clone();
+            App::new().route(
+                "/callback",
+                web::post().to(move |request: HttpRequest, body: web::Bytes| {
+                    let header = |name: &str| {
+                        request
+                            .headers()
+                            .get(name)
+                            .and_then(|value| value.to_str().ok())
+                            .unwrap_or_default()
+                            .to_string()
+                    };
+                    let (signature, timestamp) =
+                        (header(SIGNATURE_HEADER), header(TIMESTAMP_HEADER));
+                    sender.send((signature, timestamp, body)).unwrap();
+                    async { HttpResponse::Ok().finish() }
+                }),
+            )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let callback_url = Url::parse(&format!("http://{}/callback", server.addrs()[0])).unwrap();
+        actix_web::rt::spawn(server.run());
+
+        let settings = WebhooksSettings {
+            secret: Some("secret".into()),
+            allowed_hosts: vec!["127.0.0.1".into()],
+            ..Default::default()
+        };
+        let webhooks = Webhooks::new(&settings).unwrap();
+        let jobs = Arc::new(Jobs::new(Duration::from_secs(60), webhooks, None));
+        let id = jobs
+            .clone()
+            .spawn(
+                vyper_request(),
+                async { Ok(VerificationResponse::err("Compilation error")) },
+                Some(callback_url),
+            )
+            .await
+            .unwrap();
+
+        let (signature, timestamp, body) =
+            receiver.recv().await.expect("Callback should be delivered");
+        let timestamp = timestamp.parse().expect("Timestamp should be sent");
+        assert_eq!(
+            format!("sha256={}", sign(b"secret", timestamp, &body)),
+            signature
+        );
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json!(id), job["id"]);
+        assert_eq!(json!("done"), job["status"]);
+        assert_eq!(json!("Compilation error"), job["response"]["message"]);
+    }
+
+    #[actix_rt::test]
+    async fn expired_jobs_are_removed_on_insertion() {
+        let jobs = Arc::new(Jobs::new(Duration::ZERO, None, None));
+        let id = jobs
+            .clone()
+            .spawn(
+                vyper_request(),
+                async { Ok(VerificationResponse::err("Compilation error")) },
+                None,
+            )
+            .await
+            .unwrap();
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        jobs.clone()
+            .spawn(vyper_request(), futures::future::pending(), None)
+            .await
+            .unwrap();
+        assert!(
+            !jobs.lock().contains_key(&id),
+            "Finished job should be removed without being polled"
+        );
+    }
+}
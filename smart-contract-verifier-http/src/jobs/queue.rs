@@ -0,0 +1,147 @@
+use crate::handlers::{etherscan, solidity_multi_part, solidity_standard_json, vyper_multi_part};
+use anyhow::Context;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// Request the job has been created with. Stored to be able to run the verification again.
+/// Also used as an item of the batch verification request.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[schemars(rename = "BatchItem")]
+pub enum JobRequest {
+    SolidityMultiPart(solidity_multi_part::VerificationRequest),
+    SolidityStandardJson(solidity_standard_json::VerificationRequest),
+    VyperMultiPart(vyper_multi_part::VerificationRequest),
+    /// Created by the Etherscan-compatible api only
+    #[schemars(skip)]
+    Etherscan(etherscan::VerificationRequest),
+}
+
+impl JobRequest {
+    pub fn callback_url(&self) -> Option<&Url> {
+        match self {
+            JobRequest::SolidityMultiPart(request) => request.callback_url.as_ref(),
+            JobRequest::SolidityStandardJson(request) => request.callback_url.as_ref(),
+            JobRequest::VyperMultiPart(request) => request.callback_url.as_ref(),
+            JobRequest::Etherscan(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingJob {
+    pub request: JobRequest,
+    pub callback_url: Option<Url>,
+    /// Number of times the job has been resumed after restart
+    #[serde(default)]
+    pub resumes: u32,
+}
+
+/// Unfinished jobs persisted on disk, one file per job,
+/// so that they could be resumed after the service restarts.
+#[derive(Clone)]
+pub struct JobQueue {
+    dir: PathBuf,
+}
+
+impl JobQueue {
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("cannot create jobs queue directory {:?}", dir))?;
+        Ok(Self { dir })
+    }
+
+    pub fn push(&self, id: &str, job: &PendingJob) -> anyhow::Result<()> {
+        let path = self.path(id);
+        // Written under temporary name first, so that partially written jobs are never loaded
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(job)?)?;
+        // The content must reach the disk before the job appears under its name
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        // Makes the rename itself durable
+        fs::File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the jobs left in the queue. Files which could not be parsed are skipped.
+    pub fn load(&self) -> anyhow::Result<Vec<(String, PendingJob)>> {
+        let mut jobs = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let id = match job_id(&path) {
+                Some(id) => id,
+                None => continue,
+            };
+            match fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_slice(&content)?))
+            {
+                Ok(job) => jobs.push((id, job)),
+                Err(err) => tracing::warn!("skipping invalid queued job {:?}: {:#}", path, err),
+            }
+        }
+        Ok(jobs)
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+fn job_id(path: &Path) -> Option<String> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    path.file_stem()?.to_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{new_job_id, tests::vyper_request};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn push_load_remove() {
+        let dir = std::env::temp_dir().join(format!("jobs-queue-{}", new_job_id()));
+        let queue = JobQueue::new(dir.clone()).unwrap();
+        let job = PendingJob {
+            request: vyper_request(),
+            callback_url: Some(Url::parse("https://example.com/callback").unwrap()),
+            resumes: 1,
+        };
+        queue.push("first", &job).unwrap();
+        fs::write(dir.join("second.json"), "invalid").unwrap();
+        fs::write(dir.join("third.json.tmp"), "partially written").unwrap();
+
+        let loaded = queue.load().unwrap();
+        assert_eq!(1, loaded.len());
+        assert_eq!("first", loaded[0].0);
+        assert_eq!(
+            serde_json::to_value(&job).unwrap(),
+            serde_json::to_value(&loaded[0].1).unwrap()
+        );
+
+        queue.remove("first").unwrap();
+        queue
+            .remove("first")
+            .expect("Removing missing job is not an error");
+        assert!(queue.load().unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,62 @@
+use super::JobRequest;
+use crate::{
+    handlers::{etherscan, solidity_multi_part, solidity_standard_json, vyper_multi_part},
+    verification_response::VerificationResponse,
+};
+use actix_web::{error, web};
+use smart_contract_verifier::{
+    Compilers, PackageRegistry, SolidityCompiler, TraceApiClient, VyperCompiler,
+};
+use std::sync::Arc;
+
+/// Verifies the requests of any type with the compilers enabled on the server.
+/// Runs the jobs restored from the queue and the items of batch requests.
+#[derive(Clone)]
+pub struct JobRunner {
+    pub solidity: Option<(
+        web::Data<Compilers<SolidityCompiler>>,
+        Option<web::Data<PackageRegistry>>,
+    )>,
+    pub vyper: Option<web::Data<Compilers<VyperCompiler>>>,
+    /// Retrieves the bytecodes of the contracts verified via the Etherscan-compatible api
+    /// and the creation bytecodes of the requests specifying the creation transactions
+    pub trace_client: Option<Arc<TraceApiClient>>,
+}
+
+impl JobRunner {
+    pub async fn run(self, request: JobRequest) -> Result<VerificationResponse, actix_web::Error> {
+        let request = match (request, self.trace_client) {
+            (JobRequest::Etherscan(request), Some(trace_client)) => {
+                etherscan::with_bytecodes(trace_client, request)
+                    .await
+                    .map_err(error::ErrorBadRequest)?
+            }
+            (JobRequest::Etherscan(_), None) => {
+                return Err(error::ErrorBadRequest(
+                    "Etherscan api is disabled on the server",
+                ))
+            }
+            (JobRequest::SolidityMultiPart(params), trace_client) => {
+                JobRequest::SolidityMultiPart(params.with_creation_bytecode(trace_client).await?)
+            }
+            (JobRequest::SolidityStandardJson(params), trace_client) => {
+                JobRequest::SolidityStandardJson(params.with_creation_bytecode(trace_client).await?)
+            }
+            (request, _) => request,
+        };
+        match (request, self.solidity, self.vyper) {
+            (JobRequest::SolidityMultiPart(params), Some((compilers, packages)), _) => {
+                solidity_multi_part::process_request(compilers, packages, params).await
+            }
+            (JobRequest::SolidityStandardJson(params), Some((compilers, _)), _) => {
+                solidity_standard_json::process_request(compilers, params).await
+            }
+            (JobRequest::VyperMultiPart(params), _, Some(compilers)) => {
+                vyper_multi_part::process_request(compilers, params).await
+            }
+            _ => Err(error::ErrorBadRequest(
+                "Verification of the language is disabled on the server",
+            )),
+        }
+    }
+}
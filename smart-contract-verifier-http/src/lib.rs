@@ -1,17 +1,25 @@
+mod grpc;
 mod handlers;
+mod jobs;
 mod metrics;
+mod openapi;
 mod routers;
 mod run;
 mod settings;
 mod tracer;
 mod verification_response;
+mod webhooks;
 
 #[cfg(test)]
 mod tests;
 
 pub use ethers_core::types::Bytes as DisplayBytes;
 
-pub use routers::{configure_router, AppRouter, Router};
+pub use grpc::proto;
+pub use handlers::deployments::DeploymentResponse;
+pub use openapi::{ApiDoc, Operation};
+
+pub use routers::{configure_router, AppRouter, Router, Routes};
 pub use run::run;
 pub use settings::Settings;
 pub use tracer::init_logs;
@@ -2,7 +2,7 @@ use crate::verification_response::VerificationStatus;
 use actix_web::{dev::Server, App, HttpServer};
 use actix_web_prom::{PrometheusMetrics, PrometheusMetricsBuilder};
 use lazy_static::lazy_static;
-use prometheus::{register_int_counter_vec, IntCounterVec};
+use prometheus::{register_gauge, register_int_counter_vec, Gauge, IntCounterVec};
 use std::net::SocketAddr;
 
 lazy_static! {
@@ -12,6 +12,17 @@ lazy_static! {
         &["language", "endpoint", "status"],
     )
     .unwrap();
+    pub static ref COMPILATION: IntCounterVec = register_int_counter_vec!(
+        "smart_contract_verifier_compile_contract",
+        "number of contract compilations requested via compile endpoints",
+        &["language", "status"],
+    )
+    .unwrap();
+    pub static ref JOBS_IN_QUEUE: Gauge = register_gauge!(
+        "smart_contract_verifier_jobs_in_queue",
+        "number of asynchronous verifications which have not finished yet",
+    )
+    .unwrap();
 }
 
 pub fn count_verify_contract(language: &str, status: &VerificationStatus, method: &str) {
@@ -24,6 +35,11 @@ pub fn count_verify_contract(language: &str, status: &VerificationStatus, method
         .inc();
 }
 
+pub fn count_compile_contract(language: &str, succeeded: bool) {
+    let status = if succeeded { "ok" } else { "fail" };
+    COMPILATION.with_label_values(&[language, status]).inc();
+}
+
 #[derive(Clone)]
 pub struct Metrics {
     metrics_middleware: PrometheusMetrics,
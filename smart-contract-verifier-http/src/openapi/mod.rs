@@ -0,0 +1,294 @@
+//! OpenAPI 3 document of the HTTP API. Routers describe the routes they register
+//! via [`Routes`], while schemas of the request and response bodies
+//! are derived from the corresponding types.
+
+use crate::routers::{Router, Routes};
+use actix_web::http::Method;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    visit::Visitor,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+/// Swagger UI page which loads the document from `/api/v1/openapi.json`
+pub const SWAGGER_UI: &str = include_str!("swagger_ui.html");
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+const JSON: &str = "application/json";
+const NDJSON: &str = "application/x-ndjson";
+const MULTIPART: &str = "multipart/form-data";
+
+/// Single operation of the document (an HTTP method of some path).
+pub struct Operation {
+    summary: &'static str,
+    path_params: Vec<&'static str>,
+    request: Option<(&'static str, SchemaFn)>,
+    responses: Vec<(u16, &'static str, Option<(&'static str, SchemaFn)>)>,
+    errors: Vec<(u16, &'static str)>,
+}
+
+impl Operation {
+    pub fn new(summary: &'static str) -> Self {
+        Self {
+            summary,
+            path_params: vec![],
+            request: None,
+            responses: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn path_param(mut self, name: &'static str) -> Self {
+        self.path_params.push(name);
+        self
+    }
+
+    /// Json body of the request.
+    pub fn request<T: JsonSchema>(mut self) -> Self {
+        self.request = Some((JSON, SchemaGenerator::subschema_for::<T>));
+        self
+    }
+
+    /// Multipart form body of the request, whose fields are described by `T`.
+    pub fn form_request<T: JsonSchema>(mut self) -> Self {
+        self.request = Some((MULTIPART, SchemaGenerator::subschema_for::<T>));
+        self
+    }
+
+    /// Response with json body.
+    pub fn response<T: JsonSchema>(mut self, status: u16, description: &'static str) -> Self {
+        self.responses.push((
+            status,
+            description,
+            Some((JSON, SchemaGenerator::subschema_for::<T>)),
+        ));
+        self
+    }
+
+    /// Response streaming newline delimited json values of type `T`.
+    pub fn ndjson_response<T: JsonSchema>(
+        mut self,
+        status: u16,
+        description: &'static str,
+    ) -> Self {
+        self.responses.push((
+            status,
+            description,
+            Some((NDJSON, SchemaGenerator::subschema_for::<T>)),
+        ));
+        self
+    }
+
+    pub fn empty_response(mut self, status: u16, description: &'static str) -> Self {
+        self.responses.push((status, description, None));
+        self
+    }
+
+    /// Error response with plain text body (as returned by `actix_web::error` helpers).
+    pub fn error(mut self, status: u16, description: &'static str) -> Self {
+        self.errors.push((status, description));
+        self
+    }
+}
+
+pub struct ApiDoc {
+    generator: SchemaGenerator,
+    prefix: String,
+    paths: Map<String, Value>,
+}
+
+impl Default for ApiDoc {
+    fn default() -> Self {
+        Self {
+            generator: SchemaSettings::openapi3().into_generator(),
+            prefix: String::new(),
+            paths: Map::new(),
+        }
+    }
+}
+
+impl ApiDoc {
+    /// Builds the document describing the routes of the router.
+    pub fn build(router: &impl Router) -> Value {
+        let mut doc = Self::default();
+        router.register_routes(&mut Routes::Docs(&mut doc));
+        doc.into_json()
+    }
+
+    /// Prefixes paths of all operations added by `f`, similar to [`actix_web::web::scope`].
+    pub fn scope(&mut self, prefix: &str, f: impl FnOnce(&mut Self)) -> &mut Self {
+        let outer_len = self.prefix.len();
+        self.prefix.push_str(prefix);
+        f(self);
+        self.prefix.truncate(outer_len);
+        self
+    }
+
+    pub fn get(&mut self, path: &str, operation: Operation) -> &mut Self {
+        self.add(Method::GET, path, operation)
+    }
+
+    pub fn post(&mut self, path: &str, operation: Operation) -> &mut Self {
+        self.add(Method::POST, path, operation)
+    }
+
+    pub fn add(&mut self, method: Method, path: &str, operation: Operation) -> &mut Self {
+        let mut value = Map::new();
+        value.insert("summary".into(), operation.summary.into());
+        if !operation.path_params.is_empty() {
+            let params: Vec<_> = operation
+                .path_params
+                .iter()
+                .map(|name| {
+                    json!({
+                        "name": name,
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    })
+                })
+                .collect();
+            value.insert("parameters".into(), params.into());
+        }
+        if let Some((content_type, schema_fn)) = operation.request {
+            let request_body = json!({
+                "required": true,
+                "content": self.content(content_type, schema_fn),
+            });
+            value.insert("requestBody".into(), request_body);
+        }
+        let mut responses = Map::new();
+        for (status, description, content) in operation.responses {
+            let mut response = json!({ "description": description });
+            if let Some((content_type, schema_fn)) = content {
+                response["content"] = self.content(content_type, schema_fn);
+            }
+            responses.insert(status.to_string(), response);
+        }
+        for (status, description) in operation.errors {
+            let response = json!({
+                "description": description,
+                "content": { "text/plain": { "schema": { "type": "string" } } },
+            });
+            responses.insert(status.to_string(), response);
+        }
+        value.insert("responses".into(), responses.into());
+
+        let path = format!("{}{}", self.prefix, path);
+        let item = self
+            .paths
+            .entry(path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[method.as_str().to_lowercase()] = value.into();
+        self
+    }
+
+    fn content(&mut self, content_type: &str, schema_fn: SchemaFn) -> Value {
+        let mut schema = schema_fn(&mut self.generator);
+        self.visit(&mut schema);
+        json!({ content_type: { "schema": schema } })
+    }
+
+    /// Applies the OpenAPI specific adjustments to the schema (e.g., replaces boolean schemas).
+    fn visit(&mut self, schema: &mut Schema) {
+        for visitor in self.generator.visitors_mut() {
+            visitor.visit_schema(schema);
+        }
+    }
+
+    pub fn into_json(mut self) -> Value {
+        let mut schemas = self.generator.take_definitions();
+        for schema in schemas.values_mut() {
+            self.visit(schema);
+        }
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "Smart-contract verifier",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": self.paths,
+            "components": { "schemas": schemas },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handlers::{job::JobCreated, solidity_multi_part},
+        verification_response::VerificationResponse,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn document_operations() {
+        let mut doc = ApiDoc::default();
+        doc.get(
+            "/health",
+            Operation::new("Health").empty_response(200, "OK"),
+        )
+        .scope("/api/v1/solidity", |doc| {
+            doc.post(
+                "/verify/multiple-files",
+                Operation::new("Verify")
+                    .request::<solidity_multi_part::VerificationRequest>()
+                    .response::<VerificationResponse>(200, "Verification result")
+                    .error(400, "Invalid request"),
+            )
+            .post(
+                "/verify/async/multiple-files",
+                Operation::new("Verify in background")
+                    .request::<solidity_multi_part::VerificationRequest>()
+                    .response::<JobCreated>(202, "Verification job has been created"),
+            );
+        });
+        let doc = doc.into_json();
+
+        let mut paths: Vec<_> = doc["paths"].as_object().unwrap().keys().cloned().collect();
+        paths.sort();
+        assert_eq!(
+            vec![
+                "/api/v1/solidity/verify/async/multiple-files",
+                "/api/v1/solidity/verify/multiple-files",
+                "/health",
+            ],
+            paths
+        );
+        let operation = &doc["paths"]["/api/v1/solidity/verify/multiple-files"]["post"];
+        assert_eq!(
+            json!({ "$ref": "#/components/schemas/SolidityMultiPartVerificationRequest" }),
+            operation["requestBody"]["content"]["application/json"]["schema"]
+        );
+        assert_eq!(
+            json!({ "$ref": "#/components/schemas/VerificationResponse" }),
+            operation["responses"]["200"]["content"]["application/json"]["schema"]
+        );
+        assert_eq!(
+            json!("Invalid request"),
+            operation["responses"]["400"]["description"]
+        );
+
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        for name in [
+            "SolidityMultiPartVerificationRequest",
+            "VerificationResponse",
+            "VerificationResult",
+            "JobCreated",
+        ] {
+            assert!(schemas.contains_key(name), "schema {} is missing", name);
+        }
+        let request = &schemas["SolidityMultiPartVerificationRequest"]["properties"];
+        for field in ["deployed_bytecode", "sources", "evm_version"] {
+            assert!(
+                request.get(field).is_some(),
+                "flattened field {} is missing",
+                field
+            );
+        }
+    }
+}
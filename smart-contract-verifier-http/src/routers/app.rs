@@ -1,22 +1,48 @@
 use super::{
-    router::{configure_router, Router},
+    etherscan::EtherscanRouter,
+    router::{Router, Routes},
     solidity::SolidityRouter,
     sourcify::SourcifyRouter,
     vyper::VyperRouter,
 };
-use crate::{handlers::status, settings::Settings};
+use crate::{
+    handlers::{
+        batch::{self, BatchItemResult, BatchVerifier},
+        bytecode_inspection, job, openapi, status,
+    },
+    jobs::{Job, JobQueue, JobRequest, JobRunner, Jobs},
+    openapi::{ApiDoc, Operation},
+    settings::{ArchiveSettings, RpcSettings, Settings},
+    webhooks::Webhooks,
+};
 use actix_web::web;
-use std::sync::Arc;
+use anyhow::Context;
+use smart_contract_verifier::TraceApiClient;
+use std::{sync::Arc, time::Duration};
 use tokio::sync::Semaphore;
 
 pub struct AppRouter {
     solidity: Option<SolidityRouter>,
     vyper: Option<VyperRouter>,
     sourcify: Option<SourcifyRouter>,
+    etherscan: Option<EtherscanRouter>,
+    trace_client: Option<web::Data<TraceApiClient>>,
+    jobs: web::Data<Jobs>,
+    archive: web::Data<ArchiveSettings>,
+    batch: web::Data<BatchVerifier>,
+    batch_max_body_size: usize,
+    swagger_ui: bool,
 }
 
 impl AppRouter {
     pub async fn new(settings: Settings) -> anyhow::Result<Self> {
+        let webhooks = Webhooks::new(&settings.webhooks)?;
+        let queue = settings.jobs.queue_dir.map(JobQueue::new).transpose()?;
+        let jobs = web::Data::new(Jobs::new(
+            Duration::from_secs(settings.jobs.result_ttl),
+            webhooks,
+            queue,
+        ));
         let compilers_lock = Arc::new(Semaphore::new(settings.compilers.max_threads.get()));
         let solidity = match settings.solidity.enabled {
             false => None,
@@ -30,23 +56,145 @@ impl AppRouter {
             .sourcify
             .enabled
             .then(|| SourcifyRouter::new(settings.sourcify));
+        let trace_client = trace_client(settings.rpc)?.map(Arc::new);
+
+        let runner = JobRunner {
+            solidity: solidity
+                .as_ref()
+                .map(|router| (router.compilers(), router.packages())),
+            vyper: vyper.as_ref().map(|router| router.compilers()),
+            trace_client: trace_client.clone(),
+        };
+        let batch = web::Data::new(BatchVerifier {
+            runner: runner.clone(),
+            concurrency: settings.compilers.max_threads.get(),
+            max_items: settings.batch.max_items,
+        });
+        let etherscan = settings.etherscan.enabled.then(|| {
+            EtherscanRouter::new(
+                runner.clone(),
+                Duration::from_secs(settings.jobs.result_ttl),
+            )
+        });
+        let resumed = jobs.clone().into_inner().resume(
+            move |request| runner.clone().run(request),
+            settings.compilers.max_threads.get(),
+            settings.jobs.max_resumes,
+        )?;
+        if resumed > 0 {
+            tracing::info!("resumed {} unfinished verification jobs", resumed);
+        }
+
         Ok(Self {
             solidity,
             vyper,
             sourcify,
+            etherscan,
+            trace_client: trace_client.map(web::Data::from),
+            jobs,
+            archive: web::Data::new(settings.archive),
+            batch,
+            batch_max_body_size: settings.batch.max_body_size,
+            swagger_ui: settings.swagger_ui.enabled,
         })
     }
+
+    pub fn solidity(&self) -> Option<&SolidityRouter> {
+        self.solidity.as_ref()
+    }
+
+    pub fn vyper(&self) -> Option<&VyperRouter> {
+        self.vyper.as_ref()
+    }
+
+    pub fn sourcify(&self) -> Option<&SourcifyRouter> {
+        self.sourcify.as_ref()
+    }
+}
+
+/// Client of the node the contracts are retrieved from, if one is configured.
+fn trace_client(settings: RpcSettings) -> anyhow::Result<Option<TraceApiClient>> {
+    settings
+        .url
+        .map(|url| {
+            TraceApiClient::new(url, settings.request_timeout, settings.request_attempts)
+                .context("failed to build trace client")
+        })
+        .transpose()
 }
 
 impl Router for AppRouter {
-    fn register_routes(&self, service_config: &mut web::ServiceConfig) {
-        service_config
-            .route("/health", web::get().to(status::status))
-            .service(
-                web::scope("/api/v1")
-                    .service(web::scope("/solidity").configure(configure_router(&self.solidity)))
-                    .service(web::scope("/vyper").configure(configure_router(&self.vyper)))
-                    .service(web::scope("/sourcify").configure(configure_router(&self.sourcify))),
-            );
+    fn register_routes(&self, routes: &mut Routes) {
+        if let Some(trace_client) = &self.trace_client {
+            routes.app_data(trace_client.clone());
+        }
+        routes
+            .app_data(self.jobs.clone())
+            .app_data(self.archive.clone())
+            .get(
+                "/health",
+                status::status,
+                Operation::new("Check that the server is running").empty_response(200, "OK"),
+            )
+            .scope("/api/v1", |routes| {
+                // The document is built from the routes themselves, thus only when they are served
+                routes
+                    .app_data_with(|| web::Data::new(openapi::Document(ApiDoc::build(self))))
+                    .get(
+                        "/openapi.json",
+                        openapi::get,
+                        Operation::new("OpenAPI document of the server")
+                            .response::<serde_json::Value>(200, "OpenAPI document"),
+                    )
+                    .get(
+                        "/jobs/{id}",
+                        job::get,
+                        Operation::new("Get status of the verification job")
+                            .path_param("id")
+                            .response::<Job>(200, "Verification job")
+                            .error(404, "Job not found"),
+                    )
+                    .post(
+                        "/bytecode/inspect",
+                        bytecode_inspection::inspect,
+                        Operation::new("Inspect metadata and proxy patterns of the bytecode")
+                            .request::<bytecode_inspection::InspectionRequest>()
+                            .response::<bytecode_inspection::InspectionResponse>(
+                                200,
+                                "Bytecode structure",
+                            )
+                            .error(400, "Invalid request"),
+                    )
+                    .post_resource(
+                        "/verify/batch",
+                        batch::verify,
+                        Operation::new("Verify a batch of solidity and vyper contracts")
+                            .request::<Vec<JobRequest>>()
+                            .ndjson_response::<BatchItemResult>(
+                                200,
+                                "Results of the items in the order of the request, one per line",
+                            )
+                            .error(400, "Invalid request"),
+                        |resource| {
+                            resource.app_data(self.batch.clone()).app_data(
+                                web::JsonConfig::default().limit(self.batch_max_body_size),
+                            )
+                        },
+                    );
+                if self.swagger_ui {
+                    routes.get(
+                        "/swagger-ui",
+                        openapi::swagger_ui,
+                        Operation::new("Swagger UI").empty_response(200, "Swagger UI page"),
+                    );
+                }
+                routes
+                    .scope("/solidity", |routes| self.solidity.register_routes(routes))
+                    .scope("/vyper", |routes| self.vyper.register_routes(routes))
+                    .scope("/sourcify", |routes| self.sourcify.register_routes(routes))
+                    .scope("/etherscan", |routes| {
+                        self.etherscan.register_routes(routes)
+                    });
+            });
     }
 }
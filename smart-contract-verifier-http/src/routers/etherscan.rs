@@ -0,0 +1,43 @@
+use super::router::{Router, Routes};
+use crate::{
+    handlers::etherscan::{self, EtherscanApi},
+    jobs::JobRunner,
+    openapi::Operation,
+};
+use actix_web::web;
+use std::time::Duration;
+
+pub struct EtherscanRouter {
+    api: web::Data<EtherscanApi>,
+}
+
+impl EtherscanRouter {
+    /// The `runner` is expected to be built with the trace client,
+    /// and verified contracts are kept for `ttl`.
+    pub fn new(runner: JobRunner, ttl: Duration) -> Self {
+        Self {
+            api: web::Data::new(EtherscanApi::new(runner, ttl)),
+        }
+    }
+}
+
+impl Router for EtherscanRouter {
+    fn register_routes(&self, routes: &mut Routes) {
+        routes
+            .app_data(self.api.clone())
+            .get(
+                "",
+                etherscan::handle,
+                Operation::new("Etherscan-compatible api, parameters are passed in the query")
+                    .response::<etherscan::ApiResponse>(200, "Etherscan-style response"),
+            )
+            .post(
+                "",
+                etherscan::handle,
+                Operation::new(
+                    "Etherscan-compatible api, parameters are passed in the url-encoded form",
+                )
+                .response::<etherscan::ApiResponse>(200, "Etherscan-style response"),
+            );
+    }
+}
@@ -1,8 +1,9 @@
 mod app;
+mod etherscan;
 mod router;
 mod solidity;
 mod sourcify;
 mod vyper;
 
 pub use app::AppRouter;
-pub use router::{configure_router, Router};
+pub use router::{configure_router, Router, Routes};
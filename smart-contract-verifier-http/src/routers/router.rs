@@ -1,17 +1,178 @@
+use crate::openapi::{ApiDoc, Operation};
+use actix_web::{http::Method, web, FromRequest, Handler, Responder};
+
 pub trait Router {
-    fn register_routes(&self, service_config: &mut actix_web::web::ServiceConfig);
+    /// Declares the routes of the router, each along with its description in the OpenAPI document.
+    fn register_routes(&self, routes: &mut Routes);
 }
 
 impl<T: Router> Router for Option<T> {
-    fn register_routes(&self, service_config: &mut actix_web::web::ServiceConfig) {
+    fn register_routes(&self, routes: &mut Routes) {
         if let Some(router) = self {
-            router.register_routes(service_config)
+            router.register_routes(routes)
+        }
+    }
+}
+
+pub fn configure_router(router: &impl Router) -> impl FnOnce(&mut web::ServiceConfig) + '_ {
+    |service_config| router.register_routes(&mut Routes::Service(service_config))
+}
+
+/// Single table of the routes, which are either registered in the service or
+/// described in the OpenAPI document, so that the document follows the actual routes.
+pub enum Routes<'a> {
+    Service(&'a mut web::ServiceConfig),
+    Docs(&'a mut ApiDoc),
+}
+
+impl Routes<'_> {
+    /// Adds application data for the routes (ignored by the document).
+    pub fn app_data<U: 'static>(&mut self, data: U) -> &mut Self {
+        self.app_data_with(|| data)
+    }
+
+    /// Same as [`Routes::app_data`], but the data is built only if the routes are registered.
+    pub fn app_data_with<U: 'static>(&mut self, data: impl FnOnce() -> U) -> &mut Self {
+        if let Routes::Service(service_config) = self {
+            service_config.app_data(data());
+        }
+        self
+    }
+
+    /// Prefixes paths of all routes added by `f`, see [`actix_web::web::scope`].
+    pub fn scope(&mut self, prefix: &str, f: impl FnOnce(&mut Routes)) -> &mut Self {
+        match self {
+            Routes::Service(service_config) => {
+                service_config.service(
+                    web::scope(prefix)
+                        .configure(|service_config| f(&mut Routes::Service(service_config))),
+                );
+            }
+            Routes::Docs(doc) => {
+                doc.scope(prefix, |doc| f(&mut Routes::Docs(doc)));
+            }
+        }
+        self
+    }
+
+    pub fn get<F, Args>(&mut self, path: &str, handler: F, operation: Operation) -> &mut Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        self.route(Method::GET, path, handler, operation)
+    }
+
+    pub fn post<F, Args>(&mut self, path: &str, handler: F, operation: Operation) -> &mut Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        self.route(Method::POST, path, handler, operation)
+    }
+
+    /// Same as [`Routes::post`], but the route is added to its own resource
+    /// set up by `configure` (e.g., with the app data for this route only).
+    pub fn post_resource<F, Args>(
+        &mut self,
+        path: &str,
+        handler: F,
+        operation: Operation,
+        configure: impl FnOnce(web::Resource) -> web::Resource,
+    ) -> &mut Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        match self {
+            Routes::Service(service_config) => {
+                service_config
+                    .service(configure(web::resource(path)).route(web::post().to(handler)));
+            }
+            Routes::Docs(doc) => {
+                doc.add(Method::POST, path, operation);
+            }
+        }
+        self
+    }
+
+    fn route<F, Args>(
+        &mut self,
+        method: Method,
+        path: &str,
+        handler: F,
+        operation: Operation,
+    ) -> &mut Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        match self {
+            Routes::Service(service_config) => {
+                service_config.route(path, web::method(method).to(handler));
+            }
+            Routes::Docs(doc) => {
+                doc.add(method, path, operation);
+            }
         }
+        self
     }
 }
 
-pub fn configure_router(
-    router: &impl Router,
-) -> impl FnOnce(&mut actix_web::web::ServiceConfig) + '_ {
-    |service_config| router.register_routes(service_config)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{
+        test::{self, TestRequest},
+        App, HttpResponse,
+    };
+    use pretty_assertions::assert_eq;
+
+    struct PingRouter;
+
+    impl Router for PingRouter {
+        fn register_routes(&self, routes: &mut Routes) {
+            routes.scope("/ping", |routes| {
+                routes
+                    .get(
+                        "",
+                        || async { HttpResponse::Ok().finish() },
+                        Operation::new("Ping").empty_response(200, "OK"),
+                    )
+                    .post_resource(
+                        "/limited",
+                        || async { HttpResponse::Ok().finish() },
+                        Operation::new("Ping with a limited body").empty_response(200, "OK"),
+                        |resource| resource.app_data(web::PayloadConfig::new(1)),
+                    );
+            });
+        }
+    }
+
+    #[actix_rt::test]
+    async fn routes_are_registered_and_documented() {
+        let doc = ApiDoc::build(&PingRouter);
+        let paths: Vec<_> = doc["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(path, item)| (path.as_str(), item.as_object().unwrap().keys().len()))
+            .collect();
+        assert_eq!(vec![("/ping", 1), ("/ping/limited", 1)], paths);
+        assert!(doc["paths"]["/ping"]["get"].is_object());
+        assert!(doc["paths"]["/ping/limited"]["post"].is_object());
+
+        let app = test::init_service(App::new().configure(configure_router(&PingRouter))).await;
+        for request in [
+            TestRequest::get().uri("/ping"),
+            TestRequest::post().uri("/ping/limited"),
+        ] {
+            let response = request.send_request(&app).await;
+            assert!(response.status().is_success(), "{:?}", response.status());
+        }
+    }
 }
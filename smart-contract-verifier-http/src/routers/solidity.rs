@@ -1,18 +1,25 @@
-use super::router::Router;
+use super::router::{Router, Routes};
 use crate::{
-    handlers::{solidity_multi_part, solidity_standard_json, solidity_version_list},
+    handlers::{
+        archive, deployments::DeploymentResponse, job::JobCreated, solidity_build_info,
+        solidity_compile, solidity_multi_part, solidity_standard_json, solidity_version_list,
+    },
+    openapi::Operation,
     settings::{FetcherSettings, S3FetcherSettings, SoliditySettings},
+    verification_response::VerificationResponse,
 };
 use actix_web::web;
 use s3::{creds::Credentials, Bucket, Region};
 use smart_contract_verifier::{
-    Compilers, Fetcher, ListFetcher, S3Fetcher, SolcValidator, SolidityCompiler,
+    Compilers, Fetcher, ListFetcher, PackageRegistry, S3Fetcher, SolcValidator, SolidityCompiler,
 };
 use std::{str::FromStr, sync::Arc};
 use tokio::sync::Semaphore;
 
 pub struct SolidityRouter {
     compilers: web::Data<Compilers<SolidityCompiler>>,
+    packages: Option<web::Data<PackageRegistry>>,
+    build_info_max_body_size: usize,
 }
 
 fn new_region(region: Option<String>, endpoint: Option<String>) -> Option<Region> {
@@ -55,6 +62,13 @@ impl SolidityRouter {
         compilers_threads_semaphore: Arc<Semaphore>,
     ) -> anyhow::Result<Self> {
         let dir = settings.compilers_dir.clone();
+        let build_info_max_body_size = settings.build_info_max_body_size;
+        let packages = settings
+            .packages_dir
+            .as_deref()
+            .map(PackageRegistry::load)
+            .transpose()?
+            .map(web::Data::new);
         let schedule = settings.refresh_versions_schedule;
         let validator = Arc::new(SolcValidator::default());
         let fetcher: Arc<dyn Fetcher> = match settings.fetcher {
@@ -85,28 +99,121 @@ impl SolidityRouter {
         compilers.load_from_dir(&dir).await;
         Ok(Self {
             compilers: web::Data::new(compilers),
+            packages,
+            build_info_max_body_size,
         })
     }
+
+    pub fn compilers(&self) -> web::Data<Compilers<SolidityCompiler>> {
+        self.compilers.clone()
+    }
+
+    pub fn packages(&self) -> Option<web::Data<PackageRegistry>> {
+        self.packages.clone()
+    }
 }
 
 impl Router for SolidityRouter {
-    fn register_routes(&self, service_config: &mut web::ServiceConfig) {
-        service_config
+    fn register_routes(&self, routes: &mut Routes) {
+        if let Some(packages) = &self.packages {
+            routes.app_data(packages.clone());
+        }
+        routes
             .app_data(self.compilers.clone())
-            .service(
-                web::scope("/verify")
-                    .route(
+            .scope("/verify", |routes| {
+                routes
+                    .post(
                         "/multiple-files",
-                        web::post().to(solidity_multi_part::verify),
+                        solidity_multi_part::verify,
+                        Operation::new("Verify solidity contract using multiple source files")
+                            .request::<solidity_multi_part::VerificationRequest>()
+                            .response::<VerificationResponse>(200, "Verification result")
+                            .error(400, "Invalid request"),
                     )
-                    .route(
+                    .post(
                         "/standard-json",
-                        web::post().to(solidity_standard_json::verify),
-                    ),
+                        solidity_standard_json::verify,
+                        Operation::new("Verify solidity contract using standard json input")
+                            .request::<solidity_standard_json::VerificationRequest>()
+                            .response::<VerificationResponse>(200, "Verification result")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/async/multiple-files",
+                        solidity_multi_part::verify_async,
+                        Operation::new("Verify solidity multiple source files in background")
+                            .request::<solidity_multi_part::VerificationRequest>()
+                            .response::<JobCreated>(202, "Verification job has been created")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/async/standard-json",
+                        solidity_standard_json::verify_async,
+                        Operation::new("Verify solidity standard json input in background")
+                            .request::<solidity_standard_json::VerificationRequest>()
+                            .response::<JobCreated>(202, "Verification job has been created")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/deployments/multiple-files",
+                        solidity_multi_part::verify_deployments,
+                        Operation::new(
+                            "Verify several deployments of solidity multiple source files",
+                        )
+                        .request::<solidity_multi_part::DeploymentsRequest>()
+                        .response::<Vec<DeploymentResponse>>(200, "Result of each deployment")
+                        .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/deployments/standard-json",
+                        solidity_standard_json::verify_deployments,
+                        Operation::new(
+                            "Verify several deployments of solidity standard json input",
+                        )
+                        .request::<solidity_standard_json::DeploymentsRequest>()
+                        .response::<Vec<DeploymentResponse>>(200, "Result of each deployment")
+                        .error(400, "Invalid request"),
+                    )
+                    .post_resource(
+                        "/build-info",
+                        solidity_build_info::verify,
+                        Operation::new(
+                            "Verify solidity contract using hardhat or foundry build-info file",
+                        )
+                        .request::<solidity_build_info::VerificationRequest>()
+                        .response::<VerificationResponse>(200, "Verification result")
+                        .error(400, "Invalid request"),
+                        |resource| {
+                            resource.app_data(
+                                web::JsonConfig::default().limit(self.build_info_max_body_size),
+                            )
+                        },
+                    )
+                    .post(
+                        "/archive",
+                        archive::verify_solidity,
+                        Operation::new(
+                            "Verify solidity contract using zip or tar.gz archive of the project",
+                        )
+                        .form_request::<archive::ArchiveForm>()
+                        .response::<VerificationResponse>(200, "Verification result")
+                        .error(400, "Invalid request")
+                        .error(413, "Archive is too large"),
+                    );
+            })
+            .post(
+                "/compile",
+                solidity_compile::compile,
+                Operation::new("Compile solidity standard json input")
+                    .request::<solidity_compile::CompileRequest>()
+                    .response::<serde_json::Value>(200, "Standard json output of the compiler")
+                    .error(400, "Invalid request"),
             )
-            .route(
+            .get(
                 "/versions",
-                web::get().to(solidity_version_list::get_version_list),
+                solidity_version_list::get_version_list,
+                Operation::new("List available solidity compiler versions")
+                    .response::<solidity_version_list::VersionsResponse>(200, "Compiler versions"),
             );
     }
 }
@@ -1,5 +1,8 @@
-use super::router::Router;
-use crate::{handlers::sourcify, settings::SourcifySettings};
+use super::router::{Router, Routes};
+use crate::{
+    handlers::sourcify, openapi::Operation, settings::SourcifySettings,
+    verification_response::VerificationResponse,
+};
 use actix_web::web;
 use smart_contract_verifier::SourcifyApiClient;
 
@@ -19,12 +22,21 @@ impl SourcifyRouter {
             api_client: web::Data::new(api_client),
         }
     }
+
+    pub fn api_client(&self) -> web::Data<SourcifyApiClient> {
+        self.api_client.clone()
+    }
 }
 
 impl Router for SourcifyRouter {
-    fn register_routes(&self, service_config: &mut web::ServiceConfig) {
-        service_config
-            .app_data(self.api_client.clone())
-            .route("/verify", web::post().to(sourcify::verify));
+    fn register_routes(&self, routes: &mut Routes) {
+        routes.app_data(self.api_client.clone()).post(
+            "/verify",
+            sourcify::verify,
+            Operation::new("Verify contract using sourcify")
+                .request::<sourcify::ApiRequest>()
+                .response::<VerificationResponse>(200, "Verification result")
+                .error(400, "Invalid request"),
+        );
     }
 }
@@ -1,7 +1,12 @@
-use super::router::Router;
+use super::router::{Router, Routes};
 use crate::{
-    handlers::{vyper_multi_part, vyper_version_list},
+    handlers::{
+        archive, deployments::DeploymentResponse, job::JobCreated, vyper_compile, vyper_multi_part,
+        vyper_version_list,
+    },
+    openapi::Operation,
     settings::{FetcherSettings, VyperSettings},
+    verification_response::VerificationResponse,
 };
 use actix_web::web;
 use smart_contract_verifier::{Compilers, ListFetcher, VyperCompiler};
@@ -39,19 +44,67 @@ impl VyperRouter {
             compilers: web::Data::new(compilers),
         })
     }
+
+    pub fn compilers(&self) -> web::Data<Compilers<VyperCompiler>> {
+        self.compilers.clone()
+    }
 }
 
 impl Router for VyperRouter {
-    fn register_routes(&self, service_config: &mut web::ServiceConfig) {
-        service_config
+    fn register_routes(&self, routes: &mut Routes) {
+        routes
             .app_data(self.compilers.clone())
-            .service(
-                web::scope("/verify")
-                    .route("/multiple-files", web::post().to(vyper_multi_part::verify)),
+            .scope("/verify", |routes| {
+                routes
+                    .post(
+                        "/multiple-files",
+                        vyper_multi_part::verify,
+                        Operation::new("Verify vyper contract using multiple source files")
+                            .request::<vyper_multi_part::VerificationRequest>()
+                            .response::<VerificationResponse>(200, "Verification result")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/async/multiple-files",
+                        vyper_multi_part::verify_async,
+                        Operation::new("Verify vyper multiple source files in background")
+                            .request::<vyper_multi_part::VerificationRequest>()
+                            .response::<JobCreated>(202, "Verification job has been created")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/deployments/multiple-files",
+                        vyper_multi_part::verify_deployments,
+                        Operation::new("Verify several deployments of vyper multiple source files")
+                            .request::<vyper_multi_part::DeploymentsRequest>()
+                            .response::<Vec<DeploymentResponse>>(200, "Result of each deployment")
+                            .error(400, "Invalid request"),
+                    )
+                    .post(
+                        "/archive",
+                        archive::verify_vyper,
+                        Operation::new(
+                            "Verify vyper contract using zip or tar.gz archive of the project",
+                        )
+                        .form_request::<archive::ArchiveForm>()
+                        .response::<VerificationResponse>(200, "Verification result")
+                        .error(400, "Invalid request")
+                        .error(413, "Archive is too large"),
+                    );
+            })
+            .post(
+                "/compile",
+                vyper_compile::compile,
+                Operation::new("Compile vyper standard json input")
+                    .request::<vyper_compile::CompileRequest>()
+                    .response::<serde_json::Value>(200, "Standard json output of the compiler")
+                    .error(400, "Invalid request"),
             )
-            .route(
+            .get(
                 "/versions",
-                web::get().to(vyper_version_list::get_version_list),
+                vyper_version_list::get_version_list,
+                Operation::new("List available vyper compiler versions")
+                    .response::<vyper_version_list::VersionsResponse>(200, "Compiler versions"),
             );
     }
 }
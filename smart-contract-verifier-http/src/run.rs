@@ -1,4 +1,5 @@
 use crate::{
+    grpc,
     metrics::Metrics,
     routers::{configure_router, AppRouter},
     settings::Settings,
@@ -13,6 +14,8 @@ pub async fn run(settings: Settings) -> std::io::Result<()> {
     let metrics_enabled = settings.metrics.enabled;
     let metrics_addr = settings.metrics.addr;
     let metrics_endpoint = settings.metrics.route.clone();
+    let grpc_enabled = settings.grpc.enabled;
+    let grpc_addr = settings.grpc.addr;
 
     tracing::info!("Smart-contract verifier is starting at {}", socket_addr);
     let app_router = Arc::new(
@@ -20,6 +23,11 @@ pub async fn run(settings: Settings) -> std::io::Result<()> {
             .await
             .expect("couldn't initialize the app"),
     );
+    // Built before the app router is moved into the HTTP server factory
+    let grpc_server = match grpc_enabled {
+        true => Some(grpc::server(&app_router).await),
+        false => None,
+    };
     let metrics = Metrics::new(metrics_endpoint);
     let server_future = {
         let middleware = metrics.middleware().clone();
@@ -33,6 +41,15 @@ pub async fn run(settings: Settings) -> std::io::Result<()> {
         .run()
     };
     let mut futures = vec![tokio::spawn(async move { server_future.await })];
+    if let Some(grpc_server) = grpc_server {
+        tracing::info!("gRPC server is starting at {}", grpc_addr);
+        futures.push(tokio::spawn(async move {
+            grpc_server
+                .serve(grpc_addr)
+                .await
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+        }));
+    }
     if metrics_enabled {
         futures.push(tokio::spawn(async move {
             metrics.run_server(metrics_addr).await
@@ -30,12 +30,20 @@ impl Eq for IgnoredAny {}
 #[serde(default, deny_unknown_fields)]
 pub struct Settings {
     pub server: ServerSettings,
+    pub grpc: GrpcSettings,
     pub solidity: SoliditySettings,
     pub vyper: VyperSettings,
     pub sourcify: SourcifySettings,
     pub metrics: MetricsSettings,
     pub jaeger: JaegerSettings,
     pub compilers: CompilersSettings,
+    pub jobs: JobsSettings,
+    pub webhooks: WebhooksSettings,
+    pub batch: BatchSettings,
+    pub swagger_ui: SwaggerUiSettings,
+    pub rpc: RpcSettings,
+    pub etherscan: EtherscanSettings,
+    pub archive: ArchiveSettings,
 
     // Is required as we deny unknown fields, but allow users provide
     // path to config through PREFIX__CONFIG env variable. If removed,
@@ -57,6 +65,22 @@ impl Default for ServerSettings {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub addr: SocketAddr,
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            addr: SocketAddr::from_str("0.0.0.0:8051").expect("should be valid url"),
+        }
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -66,6 +90,12 @@ pub struct SoliditySettings {
     #[serde_as(as = "DisplayFromStr")]
     pub refresh_versions_schedule: Schedule,
     pub fetcher: FetcherSettings,
+    /// Directory with well-known packages used to resolve imports
+    /// missing in multi-part requests. Disabled if not specified.
+    pub packages_dir: Option<PathBuf>,
+    /// The maximum size (in bytes) of the build-info verification request body.
+    /// Build-info files include the compilation output, so are usually much larger than the input
+    pub build_info_max_body_size: usize,
 }
 
 impl Default for SoliditySettings {
@@ -77,6 +107,8 @@ impl Default for SoliditySettings {
             compilers_dir: default_dir,
             refresh_versions_schedule: Schedule::from_str("0 0 * * * * *").unwrap(), // every hour
             fetcher: Default::default(),
+            packages_dir: None,
+            build_info_max_body_size: 100 * 1024 * 1024,
         }
     }
 }
@@ -217,6 +249,141 @@ impl Default for CompilersSettings {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobsSettings {
+    /// Number of seconds results of asynchronous verifications are kept for
+    /// after the verification has finished.
+    pub result_ttl: u64,
+    /// Directory requests of unfinished verifications are stored to,
+    /// so that they are resumed after restart. Jobs are kept in memory only if not set.
+    pub queue_dir: Option<PathBuf>,
+    /// Number of times an unfinished job is resumed after restart
+    /// before it is marked as failed.
+    pub max_resumes: u32,
+}
+
+impl Default for JobsSettings {
+    fn default() -> Self {
+        Self {
+            result_ttl: 3600,
+            queue_dir: None,
+            max_resumes: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhooksSettings {
+    /// Key used to sign the callbacks with HMAC-SHA256.
+    /// Callback urls are not accepted if the secret is not specified.
+    pub secret: Option<String>,
+    /// Number of attempts the server makes to deliver the callback.
+    /// Should be at least one. Set to `5` by default.
+    pub delivery_attempts: NonZeroU32,
+    /// The maximum period (in seconds) the server waits for the response to a single
+    /// delivery attempt. Set to `10` by default.
+    pub request_timeout: u64,
+    /// Hosts callbacks may be delivered to even if they are loopback or private ones.
+    /// By default, only the callbacks to public hosts are delivered.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for WebhooksSettings {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            delivery_attempts: NonZeroU32::new(5).expect("Is not zero"),
+            request_timeout: 10,
+            allowed_hosts: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchSettings {
+    /// The maximum number of items in a single batch verification request
+    pub max_items: usize,
+    /// The maximum size (in bytes) of the batch verification request body
+    pub max_body_size: usize,
+}
+
+impl Default for BatchSettings {
+    fn default() -> Self {
+        Self {
+            max_items: 1000,
+            max_body_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SwaggerUiSettings {
+    /// When enabled, Swagger UI for the OpenAPI document is served at `/api/v1/swagger-ui`
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcSettings {
+    /// JSON-RPC endpoint of the node the contracts are retrieved from.
+    /// Required by the Etherscan-compatible api (the node should be an archive one)
+    /// and to retrieve the creation bytecodes by the creation transactions
+    pub url: Option<Url>,
+    /// Number of attempts the server makes for each JSON-RPC request.
+    /// Should be at least one. Set to `3` by default.
+    pub request_attempts: NonZeroU32,
+    /// The maximum period (in seconds) the server waits for the JSON-RPC response.
+    /// Set to `30` by default.
+    pub request_timeout: u64,
+}
+
+impl Default for RpcSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            request_attempts: NonZeroU32::new(3).expect("Is not zero"),
+            request_timeout: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EtherscanSettings {
+    /// When enabled, Etherscan-compatible verification API is served at `/api/v1/etherscan`.
+    /// Requires solidity verification to be enabled and `rpc.url` to be defined
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArchiveSettings {
+    /// The maximum size (in bytes) of the uploaded archive
+    pub max_size: usize,
+    /// The maximum number of files in the archive, including the ones not used for verification
+    pub max_files: usize,
+    /// The maximum total size (in bytes) of the unpacked sources and configuration files
+    pub max_sources_size: usize,
+    /// The maximum total size (in bytes) of all files of the archive after decompression,
+    /// including the ones not used for verification
+    pub max_unpacked_size: usize,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        Self {
+            max_size: 50 * 1024 * 1024,
+            max_files: 10000,
+            max_sources_size: 50 * 1024 * 1024,
+            max_unpacked_size: 200 * 1024 * 1024,
+        }
+    }
+}
+
 impl Settings {
     pub fn new() -> anyhow::Result<Self> {
         let config_path = std::env::var("SMART_CONTRACT_VERIFIER__CONFIG");
@@ -245,6 +412,13 @@ impl Settings {
             }
         };
 
+        if self.etherscan.enabled && !self.solidity.enabled {
+            return Err(anyhow!("etherscan api requires solidity to be enabled"));
+        }
+        if self.etherscan.enabled && self.rpc.url.is_none() {
+            return Err(anyhow!("etherscan api requires `rpc.url` to be defined"));
+        }
+
         Ok(())
     }
 }
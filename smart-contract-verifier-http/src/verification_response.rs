@@ -1,50 +1,132 @@
 use crate::DisplayBytes;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use smart_contract_verifier::{SourcifySuccess, VerificationSuccess};
+use smart_contract_verifier::{
+    Artifacts, ContractVerificationError, SourcifySuccess, VerificationError, VerificationSuccess,
+};
 use std::{collections::BTreeMap, fmt::Display};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 pub struct VerificationResponse {
     pub message: String,
     pub result: Option<VerificationResult>,
     pub status: VerificationStatus,
+    /// Reasons each of the compiled contracts has not matched the deployed bytecode
+    #[serde(default)]
+    pub errors: Vec<ContractError>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct ContractError {
+    pub file_name: String,
+    pub contract_name: Option<String>,
+    /// Machine readable reason of the failure (e.g., "library_missed", "bytecode_mismatch")
+    pub code: String,
+    pub message: String,
+}
+
+impl From<ContractVerificationError> for ContractError {
+    fn from(error: ContractVerificationError) -> Self {
+        Self {
+            file_name: error.file_path,
+            contract_name: error.contract_name,
+            code: error.kind.code().to_string(),
+            message: error.kind.message(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 pub struct VerificationResult {
     pub file_name: String,
     pub contract_name: String,
     pub compiler_version: String,
     pub evm_version: String,
+    #[schemars(with = "Option<String>")]
     pub constructor_arguments: Option<DisplayBytes>,
     pub optimization: Option<bool>,
     pub optimization_runs: Option<usize>,
     pub contract_libraries: BTreeMap<String, String>,
     pub abi: String,
     pub sources: BTreeMap<String, String>,
+    pub is_destroyed: bool,
+    /// `Some(false)` if the contract has been compiled without metadata appended to its bytecode
+    pub append_cbor: Option<bool>,
+    /// Sources from `sources` which were added from the local package registry
+    pub external_sources: Vec<String>,
+    /// Normalization of the sources which resulted in a full match (e.g., "lf", "crlf")
+    pub source_normalization: Option<String>,
+    /// Compilation artifacts requested via `artifacts` parameter
+    pub artifacts: Option<VerificationArtifacts>,
+}
+
+/// Only requested artifacts are serialized.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+pub struct VerificationArtifacts {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiler_input: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_map: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployed_source_map: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method_identifiers: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_layout: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userdoc: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devdoc: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl From<Artifacts> for VerificationArtifacts {
+    fn from(artifacts: Artifacts) -> Self {
+        Self {
+            compiler_input: artifacts.compiler_input,
+            warnings: artifacts.warnings,
+            source_map: artifacts.source_map,
+            deployed_source_map: artifacts.deployed_source_map,
+            method_identifiers: artifacts.method_identifiers,
+            storage_layout: artifacts.storage_layout,
+            userdoc: artifacts.userdoc,
+            devdoc: artifacts.devdoc,
+            metadata: artifacts.metadata,
+        }
+    }
 }
 
 impl From<VerificationSuccess> for VerificationResult {
     fn from(verification_success: VerificationSuccess) -> Self {
+        // Defaults applied by the compiler are resolved, so that the actual settings are returned
+        let evm_version = verification_success
+            .evm_version()
+            .unwrap_or_else(|| "default".to_string());
+        let optimization = verification_success.optimization();
+        let optimization_runs = verification_success.optimization_runs();
+
         let compiler_input = verification_success.compiler_input;
         VerificationResult {
             file_name: verification_success.file_path,
             contract_name: verification_success.contract_name,
             compiler_version: verification_success.compiler_version.to_string(),
-            evm_version: compiler_input
-                .settings
-                .evm_version
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "default".to_string()),
+            evm_version,
             constructor_arguments: verification_success.constructor_args,
-            optimization: compiler_input.settings.optimizer.enabled,
-            optimization_runs: compiler_input.settings.optimizer.runs,
+            optimization,
+            optimization_runs,
             contract_libraries: compiler_input
                 .settings
                 .libraries
                 .libs
                 .into_iter()
-                .flat_map(|(_path, libs)| libs)
+                .flat_map(|(path, libs)| {
+                    libs.into_iter().map(move |(name, address)| {
+                        (format!("{}:{}", path.to_string_lossy(), name), address)
+                    })
+                })
                 .collect(),
             abi: serde_json::to_string(&verification_success.abi)
                 .expect("Is result of local compilation and, thus, should be always valid"),
@@ -53,6 +135,18 @@ impl From<VerificationSuccess> for VerificationResult {
                 .into_iter()
                 .map(|(path, source)| (path.to_string_lossy().to_string(), source.content))
                 .collect(),
+            is_destroyed: verification_success.is_destroyed,
+            append_cbor: verification_success.extra_settings.append_cbor,
+            external_sources: verification_success
+                .external_sources
+                .into_iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+            source_normalization: verification_success
+                .source_normalization
+                .map(|normalization| normalization.to_string()),
+            artifacts: (verification_success.artifacts != Artifacts::default())
+                .then(|| verification_success.artifacts.into()),
         }
     }
 }
@@ -72,11 +166,16 @@ impl From<SourcifySuccess> for VerificationResult {
             contract_libraries: sourcify_success.contract_libraries,
             abi: sourcify_success.abi,
             sources: sourcify_success.sources,
+            is_destroyed: false,
+            append_cbor: None,
+            external_sources: vec![],
+            source_normalization: None,
+            artifacts: None,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 pub enum VerificationStatus {
     #[serde(rename = "0")]
     Ok,
@@ -90,6 +189,7 @@ impl VerificationResponse {
             message: "OK".to_string(),
             result: Some(result),
             status: VerificationStatus::Ok,
+            errors: vec![],
         }
     }
 
@@ -98,6 +198,21 @@ impl VerificationResponse {
             message: message.to_string(),
             result: None,
             status: VerificationStatus::Failed,
+            errors: vec![],
+        }
+    }
+
+    /// Failed response including the reasons each of the contracts has failed to be verified.
+    pub fn verification_err(err: VerificationError) -> Self {
+        let message = err.to_string();
+        let errors = match err {
+            VerificationError::NoMatchingContracts(errors)
+            | VerificationError::ContractMismatch { errors, .. } => errors,
+            _ => vec![],
+        };
+        Self {
+            errors: errors.into_iter().map(ContractError::from).collect(),
+            ..Self::err(message)
         }
     }
 }
@@ -107,6 +222,7 @@ mod tests {
     use super::*;
     use crate::tests::parse::test_serialize_json_ok;
     use serde_json::json;
+    use smart_contract_verifier::VerificationErrorKind;
 
     #[test]
     fn parse_response() {
@@ -121,7 +237,7 @@ mod tests {
                     optimization: Some(false),
                     optimization_runs: Some(200),
                     contract_libraries: BTreeMap::from([(
-                        "some_library".into(),
+                        "source.sol:some_library".into(),
                         "some_address".into(),
                     )]),
                     abi: "abi".to_string(),
@@ -131,6 +247,15 @@ mod tests {
                         }"#,
                     )
                     .unwrap(),
+                    is_destroyed: false,
+                    append_cbor: Some(false),
+                    external_sources: vec!["@openzeppelin/contracts/utils/Context.sol".into()],
+                    source_normalization: Some("lf".into()),
+                    artifacts: Some(VerificationArtifacts {
+                        warnings: Some(vec!["Warning: unused variable".into()]),
+                        source_map: Some("0:1:0:-:0".into()),
+                        ..Default::default()
+                    }),
                 }),
                 json!({
                     "message": "OK",
@@ -142,7 +267,7 @@ mod tests {
                         "evm_version": "evm_version",
                         "constructor_arguments": "0xcafe",
                         "contract_libraries": {
-                            "some_library": "some_address",
+                            "source.sol:some_library": "some_address",
                         },
                         "optimization": false,
                         "optimization_runs": 200,
@@ -150,7 +275,16 @@ mod tests {
                         "sources": {
                             "source.sol": "content",
                         },
+                        "is_destroyed": false,
+                        "append_cbor": false,
+                        "external_sources": ["@openzeppelin/contracts/utils/Context.sol"],
+                        "source_normalization": "lf",
+                        "artifacts": {
+                            "warnings": ["Warning: unused variable"],
+                            "source_map": "0:1:0:-:0",
+                        },
                     },
+                    "errors": [],
 
                 }),
             ),
@@ -160,6 +294,27 @@ mod tests {
                     "message": "Parse error",
                     "status": "1",
                     "result": null,
+                    "errors": [],
+                }),
+            ),
+            (
+                VerificationResponse::verification_err(VerificationError::NoMatchingContracts(
+                    vec![ContractVerificationError::with_contract(
+                        "contracts/Main.sol".into(),
+                        "Main".into(),
+                        VerificationErrorKind::LibraryMissed(vec!["contracts/Lib.sol:Lib".into()]),
+                    )],
+                )),
+                json!({
+                    "message": "No contract could be verified with provided data",
+                    "status": "1",
+                    "result": null,
+                    "errors": [{
+                        "file_name": "contracts/Main.sol",
+                        "contract_name": "Main",
+                        "code": "library_missed",
+                        "message": "library missed: contracts/Lib.sol:Lib",
+                    }],
                 }),
             ),
         ])
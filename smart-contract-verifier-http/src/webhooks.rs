@@ -0,0 +1,321 @@
+use crate::settings::WebhooksSettings;
+use hmac::{Hmac, Mac};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::Serialize;
+use sha2::Sha256;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use url::{Host, Url};
+
+/// Header containing hex encoded HMAC-SHA256 of the timestamp and the request body
+/// (see [`sign`]), prefixed with `sha256=`.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+/// Header containing the time the callback has been signed at, in seconds since the Unix epoch.
+/// Is signed along with the body, so that receivers could reject replayed callbacks.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CallbackUrlError {
+    #[error("Invalid callback url scheme: {0}")]
+    Scheme(String),
+    #[error("Callback url has no host")]
+    NoHost,
+    #[error("Callback url host is not public: {0}")]
+    NotPublic(String),
+}
+
+/// Notifies requesters about finished verifications by posting the results to their callback urls.
+pub struct Webhooks {
+    secret: Vec<u8>,
+    allowed_hosts: Vec<String>,
+    request_timeout: Duration,
+    retry_policy: ExponentialBackoff,
+}
+
+impl Webhooks {
+    /// Returns `None` if no secret is configured, as callbacks are always signed.
+    pub fn new(settings: &WebhooksSettings) -> anyhow::Result<Option<Self>> {
+        let secret = match &settings.secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => return Ok(None),
+        };
+        // The first attempt is not a retry
+        let retries = settings.delivery_attempts.get() - 1;
+        let webhooks = Self {
+            secret,
+            allowed_hosts: settings.allowed_hosts.clone(),
+            request_timeout: Duration::from_secs(settings.request_timeout),
+            retry_policy: ExponentialBackoff::builder().build_with_max_retries(retries),
+        };
+        // Clients are built for every delivery, so the settings are checked in advance
+        webhooks.client(None)?;
+
+        Ok(Some(webhooks))
+    }
+
+    /// Builds the client delivering callbacks. If `pinned` address is given,
+    /// the domain is connected to it instead of being resolved again.
+    fn client(&self, pinned: Option<(&str, SocketAddr)>) -> reqwest::Result<ClientWithMiddleware> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.request_timeout)
+            // Redirects could lead to the hosts callbacks are not allowed to be delivered to
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some((domain, addr)) = pinned {
+            builder = builder.resolve(domain, addr);
+        }
+        Ok(ClientBuilder::new(builder.build()?)
+            .with(RetryTransientMiddleware::new_with_policy(self.retry_policy))
+            .build())
+    }
+
+    /// Checks that the callback may be delivered to the `url`: the scheme is http(s)
+    /// and the host is either allowed explicitly or is not a loopback or private one.
+    pub fn validate_url(&self, url: &Url) -> Result<(), CallbackUrlError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(CallbackUrlError::Scheme(url.scheme().to_string()));
+        }
+        let host = url.host().ok_or(CallbackUrlError::NoHost)?;
+        if self.is_allowed(url) {
+            return Ok(());
+        }
+        let is_public = match host {
+            Host::Domain(domain) => {
+                let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+                domain != "localhost" && !domain.ends_with(".localhost")
+            }
+            Host::Ipv4(ip) => is_public_ipv4(&ip),
+            Host::Ipv6(ip) => is_public_ipv6(&ip),
+        };
+        if !is_public {
+            return Err(CallbackUrlError::NotPublic(host.to_string()));
+        }
+        Ok(())
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        url.host_str()
+            .map(|host| self.allowed_hosts.iter().any(|allowed| allowed == host))
+            .unwrap_or_default()
+    }
+
+    /// Domain names are checked again when the callback is delivered,
+    /// as they may resolve to the addresses callbacks are not allowed to be delivered to.
+    ///
+    /// Returns the checked address the domain should be connected to, so that
+    /// the name could not be rebound to another address between the check and the delivery.
+    async fn resolve(&self, url: &Url) -> anyhow::Result<Option<SocketAddr>> {
+        self.validate_url(url)?;
+        if self.is_allowed(url) {
+            return Ok(None);
+        }
+        let domain = match url.host() {
+            Some(Host::Domain(domain)) => domain,
+            _ => return Ok(None),
+        };
+        let port = url.port_or_known_default().unwrap_or_default();
+        let addrs: Vec<_> = tokio::net::lookup_host((domain, port)).await?.collect();
+        if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(&addr.ip())) {
+            return Err(CallbackUrlError::NotPublic(format!(
+                "{} resolves to {}",
+                domain,
+                addr.ip()
+            ))
+            .into());
+        }
+        match addrs.first() {
+            Some(addr) => Ok(Some(*addr)),
+            None => Err(anyhow::anyhow!(
+                "{} does not resolve to any address",
+                domain
+            )),
+        }
+    }
+
+    /// Posts the `payload` to the `url`. Transient failures are retried with exponential backoff.
+    pub async fn deliver(&self, url: &Url, payload: &impl Serialize) -> anyhow::Result<()> {
+        let pinned = self.resolve(url).await?;
+        let client = self.client(url.host_str().zip(pinned))?;
+        let body = serde_json::to_vec(payload)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signature = format!("sha256={}", sign(&self.secret, timestamp, &body));
+        client
+            .post(url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    // "This network" (0.0.0.0/8) and shared address space (100.64.0.0/10)
+    let is_reserved = first == 0 || (first == 100 && (second & 0b1100_0000) == 64);
+    !(is_reserved
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast())
+}
+
+fn is_public_ipv6(ip: &Ipv6Addr) -> bool {
+    if let [0, 0, 0, 0, 0, 0xffff, ..] = ip.segments() {
+        let [.., a, b, c, d] = ip.octets();
+        return is_public_ipv4(&Ipv4Addr::new(a, b, c, d));
+    }
+    let first = ip.segments()[0];
+    // Unique local (fc00::/7) and link-local (fe80::/10) addresses
+    let is_local = (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80;
+    !(is_local || ip.is_loopback() || ip.is_unspecified() || ip.is_multicast())
+}
+
+/// Returns hex encoded HMAC-SHA256 of the `timestamp` and the `body` joined with a dot.
+pub fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC can take key of any size");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn webhooks(allowed_hosts: Vec<String>) -> Webhooks {
+        let settings = WebhooksSettings {
+            secret: Some("secret".into()),
+            allowed_hosts,
+            ..Default::default()
+        };
+        Webhooks::new(&settings).unwrap().unwrap()
+    }
+
+    fn validate(webhooks: &Webhooks, url: &str) -> Result<(), CallbackUrlError> {
+        webhooks.validate_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn public_callback_urls_are_accepted() {
+        let webhooks = webhooks(vec![]);
+        for url in [
+            "https://example.com/callback",
+            "http://8.8.8.8:8080/callback",
+            "http://[2001:4860:4860::8888]/callback",
+        ] {
+            assert_eq!(Ok(()), validate(&webhooks, url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn non_public_callback_urls_are_rejected() {
+        let webhooks = webhooks(vec![]);
+        for url in [
+            "http://localhost/callback",
+            "http://api.localhost./callback",
+            "http://127.0.0.1:8080/callback",
+            "http://0.0.0.0/callback",
+            "http://10.1.2.3/callback",
+            "http://172.16.0.1/callback",
+            "http://192.168.1.1/callback",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/callback",
+            "http://[::1]/callback",
+            "http://[::ffff:127.0.0.1]/callback",
+            "http://[fd00::1]/callback",
+            "http://[fe80::1]/callback",
+        ] {
+            assert!(
+                matches!(
+                    validate(&webhooks, url),
+                    Err(CallbackUrlError::NotPublic(_))
+                ),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(
+            Err(CallbackUrlError::Scheme("ftp".into())),
+            validate(&webhooks, "ftp://example.com/callback")
+        );
+    }
+
+    #[test]
+    fn allowed_hosts_are_accepted() {
+        let webhooks = webhooks(vec!["127.0.0.1".into(), "verifier.internal".into()]);
+        assert_eq!(
+            Ok(()),
+            validate(&webhooks, "http://127.0.0.1:8080/callback")
+        );
+        assert_eq!(
+            Ok(()),
+            validate(&webhooks, "http://verifier.internal/callback")
+        );
+        assert!(validate(&webhooks, "http://127.0.0.2/callback").is_err());
+    }
+
+    #[tokio::test]
+    async fn pinned_address_is_connected() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/callback"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The domain does not exist, so the request could only be made to the pinned address
+        let url = format!(
+            "http://callback.invalid:{}/callback",
+            server.address().port()
+        );
+        webhooks(vec![])
+            .client(Some(("callback.invalid", *server.address())))
+            .unwrap()
+            .post(url)
+            .send()
+            .await
+            .expect("request should be made to the pinned address")
+            .error_for_status()
+            .unwrap();
+    }
+
+    #[test]
+    fn hmac_signature() {
+        // Key and data of the test case 2 from RFC 4231, prefixed with the timestamp
+        let body = b"what do ya want for nothing?";
+        assert_eq!(
+            "1cdd0650c8be1cb0974b1788d458b1e781206cfef59b85faafc582d2e182c57e",
+            sign(b"Jefe", 1700000000, body)
+        );
+        assert_ne!(
+            sign(b"Jefe", 1700000000, body),
+            sign(b"Jefe", 1700000001, body)
+        );
+    }
+}
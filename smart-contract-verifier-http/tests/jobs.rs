@@ -0,0 +1,126 @@
+use actix_web::{
+    http::StatusCode,
+    test::{self, read_body_json, TestRequest},
+    App,
+};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use smart_contract_verifier_http::{configure_router, AppRouter, Settings};
+use std::{fs, time::Duration};
+use tokio::sync::OnceCell;
+
+const CONTRACTS_DIR: &'static str = "tests/contracts";
+
+async fn global_app_router() -> &'static AppRouter {
+    static APP_ROUTER: OnceCell<AppRouter> = OnceCell::const_new();
+    APP_ROUTER
+        .get_or_init(|| async {
+            let mut settings = Settings::default();
+            settings.sourcify.enabled = false;
+            AppRouter::new(settings)
+                .await
+                .expect("couldn't initialize the app")
+        })
+        .await
+}
+
+fn read(dir: &str, file: &str) -> String {
+    fs::read_to_string(format!("{}/{}/{}", CONTRACTS_DIR, dir, file))
+        .unwrap_or_else(|err| panic!("Error while reading {}: {}", file, err))
+}
+
+#[actix_rt::test]
+async fn async_verification_is_polled_until_finished() {
+    let app_router = global_app_router().await;
+    let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+    let request = json!({
+        "deployed_bytecode": read("issue_5748", "deployed_bytecode"),
+        "creation_bytecode": read("issue_5748", "creation_tx_input"),
+        "compiler_version": "v0.6.8+commit.0bbfe453",
+        "input": read("issue_5748", "standard_input.json"),
+    });
+    let response = TestRequest::post()
+        .uri("/api/v1/solidity/verify/async/standard-json")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(StatusCode::ACCEPTED, response.status());
+    let created: Value = read_body_json(response).await;
+    let id = created["id"].as_str().expect("Job id should be returned");
+
+    let job = loop {
+        let response = TestRequest::get()
+            .uri(&format!("/api/v1/jobs/{}", id))
+            .send_request(&app)
+            .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let job: Value = read_body_json(response).await;
+        match job["status"].as_str() {
+            Some("done") | Some("failed") => break job,
+            _ => actix_rt::time::sleep(Duration::from_millis(100)).await,
+        }
+    };
+    assert_eq!(Some("done"), job["status"].as_str(), "Invalid job: {}", job);
+    assert_eq!(Some(id), job["id"].as_str());
+    assert_eq!(
+        Some("ExternalTestJson"),
+        job["response"]["result"]["contract_name"].as_str(),
+        "Invalid job: {}",
+        job
+    );
+}
+
+#[actix_rt::test]
+async fn invalid_async_requests_are_rejected() {
+    let app_router = global_app_router().await;
+    let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+    let response = TestRequest::get()
+        .uri("/api/v1/jobs/unknown")
+        .send_request(&app)
+        .await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    // Callbacks are accepted only if the webhooks secret is configured
+    let request = json!({
+        "deployed_bytecode": read("issue_5748", "deployed_bytecode"),
+        "creation_bytecode": read("issue_5748", "creation_tx_input"),
+        "compiler_version": "v0.6.8+commit.0bbfe453",
+        "input": read("issue_5748", "standard_input.json"),
+        "callback_url": "https://example.com/callback",
+    });
+    let response = TestRequest::post()
+        .uri("/api/v1/solidity/verify/async/standard-json")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+}
+
+#[actix_rt::test]
+async fn callbacks_are_rejected_by_sync_endpoints() {
+    let app_router = global_app_router().await;
+    let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+    let request = json!({
+        "deployed_bytecode": read("issue_5748", "deployed_bytecode"),
+        "creation_bytecode": read("issue_5748", "creation_tx_input"),
+        "compiler_version": "v0.6.8+commit.0bbfe453",
+        "input": read("issue_5748", "standard_input.json"),
+        "callback_url": "https://example.com/callback",
+    });
+    let response = TestRequest::post()
+        .uri("/api/v1/solidity/verify/standard-json")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+    let response = TestRequest::post()
+        .uri("/api/v1/verify/batch")
+        .set_json(&json!([{ "solidity_standard_json": request }]))
+        .send_request(&app)
+        .await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+}
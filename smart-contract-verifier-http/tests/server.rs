@@ -29,6 +29,22 @@ async fn server_start() {
         .expect("failed to connect to server");
     assert_eq!(resp.status(), 200);
 
+    let resp = client
+        .get(format!("{base}/api/v1/openapi.json"))
+        .send()
+        .await
+        .expect("failed to connect to server");
+    assert_eq!(resp.status(), 200);
+    let document: serde_json::Value = resp.json().await.expect("document should be valid json");
+    let paths = document["paths"]
+        .as_object()
+        .expect("paths should be an object");
+    assert!(paths.contains_key("/api/v1/vyper/verify/multiple-files"));
+    assert!(
+        !paths.contains_key("/api/v1/solidity/verify/multiple-files"),
+        "routes of disabled languages should not be documented"
+    );
+
     let resp = client
         .get(format!("{metrics_base}/metrics"))
         .send()
@@ -8,6 +8,7 @@ use actix_web::{
 };
 use pretty_assertions::assert_eq;
 use serde_json::json;
+use smart_contract_verifier::{solidity, Version};
 use smart_contract_verifier_http::{
     configure_router, AppRouter, DisplayBytes, Settings, VerificationResponse, VerificationStatus,
 };
@@ -36,7 +37,14 @@ async fn global_app_router() -> &'static AppRouter {
 }
 
 async fn test_setup(dir: &str, input: &mut TestInput) -> (ServiceResponse, Option<DisplayBytes>) {
-    let app_router = global_app_router().await;
+    test_setup_with(global_app_router().await, dir, input).await
+}
+
+async fn test_setup_with(
+    app_router: &AppRouter,
+    dir: &str,
+    input: &mut TestInput,
+) -> (ServiceResponse, Option<DisplayBytes>) {
     let app = test::init_service(App::new().configure(configure_router(app_router))).await;
 
     let prefix = format!("{}/{}", CONTRACTS_DIR, dir);
@@ -45,10 +53,12 @@ async fn test_setup(dir: &str, input: &mut TestInput) -> (ServiceResponse, Optio
     input.source_code = Some(input.source_code.clone().unwrap_or_else(|| {
         fs::read_to_string(&contract_path).expect("Error while reading source")
     }));
-    input.creation_tx_input = Some(input.creation_tx_input.clone().unwrap_or_else(|| {
-        fs::read_to_string(format!("{}/creation_tx_input", prefix))
-            .expect("Error while reading creation_tx_input")
-    }));
+    if input.creation_transaction.is_none() {
+        input.creation_tx_input = Some(input.creation_tx_input.clone().unwrap_or_else(|| {
+            fs::read_to_string(format!("{}/creation_tx_input", prefix))
+                .expect("Error while reading creation_tx_input")
+        }));
+    }
     input.deployed_bytecode = Some(input.deployed_bytecode.clone().unwrap_or_else(|| {
         fs::read_to_string(format!("{}/deployed_bytecode", prefix))
             .expect("Error while reading deployed_bytecode")
@@ -61,10 +71,10 @@ async fn test_setup(dir: &str, input: &mut TestInput) -> (ServiceResponse, Optio
         .expect("Expected constructor args must be valid")
     });
 
-    let request = if let Some(optimization_runs) = input.optimization_runs {
+    let mut request = if let Some(optimization_runs) = input.optimization_runs {
         json!({
             "deployed_bytecode": input.deployed_bytecode.as_ref().unwrap(),
-            "creation_bytecode": input.creation_tx_input.as_ref().unwrap(),
+            "creation_bytecode": input.creation_tx_input,
             "compiler_version": input.compiler_version,
             "sources": BTreeMap::from([(contract_path, input.source_code.as_ref().unwrap())]),
             "evm_version": input.evm_version,
@@ -74,13 +84,19 @@ async fn test_setup(dir: &str, input: &mut TestInput) -> (ServiceResponse, Optio
     } else {
         json!({
             "deployed_bytecode": input.deployed_bytecode.as_ref().unwrap(),
-            "creation_bytecode": input.creation_tx_input.as_ref().unwrap(),
+            "creation_bytecode": input.creation_tx_input,
             "compiler_version": input.compiler_version,
             "sources": BTreeMap::from([(contract_path, input.source_code.as_ref().unwrap())]),
             "evm_version": input.evm_version,
             "contract_libraries": input.contract_libraries
         })
     };
+    if let Some((transaction_hash, contract_address)) = input.creation_transaction {
+        let request = request.as_object_mut().unwrap();
+        request.remove("creation_bytecode");
+        request.insert("creation_transaction_hash".into(), json!(transaction_hash));
+        request.insert("contract_address".into(), json!(contract_address));
+    }
 
     let response = TestRequest::post()
         .uri(ROUTE)
@@ -91,8 +107,13 @@ async fn test_setup(dir: &str, input: &mut TestInput) -> (ServiceResponse, Optio
     (response, expected_constructor_argument)
 }
 
-async fn test_success(dir: &'static str, mut input: TestInput) {
-    let (response, expected_constructor_argument) = test_setup(dir, &mut input).await;
+async fn test_success(dir: &'static str, input: TestInput) {
+    test_success_with(global_app_router().await, dir, input).await
+}
+
+async fn test_success_with(app_router: &AppRouter, dir: &'static str, mut input: TestInput) {
+    let (response, expected_constructor_argument) =
+        test_setup_with(app_router, dir, &mut input).await;
 
     // Assert that status code is success
     if !response.status().is_success() {
@@ -136,16 +157,40 @@ async fn test_success(dir: &'static str, mut input: TestInput) {
         "Invalid constructor args"
     );
 
+    // Compiler defaults are expected to be resolved into the actual values
+    let expected_evm_version = match input.evm_version {
+        "default" => {
+            let compiler_version =
+                Version::from_str(input.compiler_version).expect("Invalid compiler version");
+            solidity::default_evm_version(compiler_version.version())
+                .unwrap_or("default")
+                .to_string()
+        }
+        evm_version => evm_version.to_string(),
+    };
     assert_eq!(
-        verification_result.evm_version, input.evm_version,
+        verification_result.evm_version, expected_evm_version,
         "Invalid evm version"
     );
     assert_eq!(
         verification_result.compiler_version, input.compiler_version,
         "Invalid compiler version"
     );
+    // Libraries requested by bare names are returned qualified with the (only) source file
+    let expected_libraries: BTreeMap<_, _> = input
+        .contract_libraries
+        .iter()
+        .map(|(name, address)| {
+            let name = if name.contains(':') {
+                name.clone()
+            } else {
+                format!("{}:{}", verification_result.file_name, name)
+            };
+            (name, address.clone())
+        })
+        .collect();
     assert_eq!(
-        verification_result.contract_libraries, input.contract_libraries,
+        verification_result.contract_libraries, expected_libraries,
         "Invalid contract libraries"
     );
     assert_eq!(
@@ -154,9 +199,24 @@ async fn test_success(dir: &'static str, mut input: TestInput) {
         "Invalid optimization"
     );
     assert_eq!(
-        verification_result.optimization_runs, input.optimization_runs,
+        verification_result.optimization_runs,
+        Some(
+            input
+                .optimization_runs
+                .unwrap_or(solidity::DEFAULT_OPTIMIZER_RUNS)
+        ),
         "Invalid optimization runs"
     );
+    assert_eq!(
+        verification_result.is_destroyed,
+        input
+            .deployed_bytecode
+            .as_ref()
+            .expect("Set `Some` on test_setup")
+            .trim_start_matches("0x")
+            .is_empty(),
+        "Invalid is_destroyed flag"
+    );
     assert_eq!(
         verification_result.sources.len(),
         1,
@@ -226,6 +286,10 @@ async fn test_error(
 
 mod success_tests {
     use super::*;
+    use wiremock::{
+        matchers::{body_partial_json, method},
+        Mock, MockServer, ResponseTemplate,
+    };
 
     #[actix_rt::test]
     async fn verifies_the_generated_bytecode_against_bytecode_retrieved_from_the_blockchain() {
@@ -288,14 +352,64 @@ mod success_tests {
         test_success(contract_dir, test_input).await;
     }
 
+    const FACTORY_TRANSACTION_HASH: &str =
+        "0x0101010101010101010101010101010101010101010101010101010101010101";
+
+    /// Starts the node returning the `debug_traceTransaction` result of the transaction
+    /// the contract has been created in by the factory.
+    async fn mock_node(dir: &str) -> MockServer {
+        let trace: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(format!("{}/{}/trace.json", CONTRACTS_DIR, dir))
+                .expect("Error while reading trace"),
+        )
+        .expect("Invalid trace");
+        let node = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "debug_traceTransaction",
+                "params": [FACTORY_TRANSACTION_HASH, { "tracer": "callTracer" }],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": trace,
+            })))
+            .expect(1)
+            .mount(&node)
+            .await;
+        node
+    }
+
     #[actix_rt::test]
-    // verifies smart-contract created from another contract
+    // self-destructed contracts have no deployed bytecode stored in the chain
+    async fn verifies_contract_with_empty_deployed_bytecode() {
+        let contract_dir = "simple_storage";
+        let test_input = TestInput::new("SimpleStorage", "v0.4.24+commit.e67f0147")
+            .with_deployed_bytecode("0x".to_string());
+        test_success(contract_dir, test_input).await;
+    }
+
+    #[actix_rt::test]
+    // verifies smart-contract created from another contract,
+    // the creation bytecode of which is retrieved from the creation transaction traces
     async fn contract_from_factory() {
         let contract_dir = "contract_from_factory";
+        let node = mock_node(contract_dir).await;
+        let mut settings = Settings::default();
+        settings.sourcify.enabled = false;
+        settings.rpc.url = Some(node.uri().parse().unwrap());
+        let app_router = AppRouter::new(settings)
+            .await
+            .expect("couldn't initialize the app");
+
         let test_input = TestInput::new("ContractFromFactory", "v0.4.26+commit.4563c3fc")
             .with_optimization_runs(200)
+            .with_creation_transaction(
+                FACTORY_TRANSACTION_HASH,
+                "0x4e2960b9185b3b5bc7f3af59d7db35148b6a3454",
+            )
             .has_constructor_args();
-        test_success(contract_dir, test_input).await;
+        test_success_with(&app_router, contract_dir, test_input).await;
     }
 
     // #[actix_rt::test]
@@ -467,3 +581,160 @@ mod tests_from_constructor_arguments_test_exs {
         test_success(contract_dir, test_input).await;
     }
 }
+
+mod deployments_tests {
+    use super::*;
+    use smart_contract_verifier_http::DeploymentResponse;
+
+    const DEPLOYMENTS_ROUTE: &str = "/api/v1/solidity/verify/deployments/multiple-files";
+
+    fn deployment(address: &str, dir: &str) -> serde_json::Value {
+        let prefix = format!("{}/{}", CONTRACTS_DIR, dir);
+        let read = |name: &str| {
+            fs::read_to_string(format!("{}/{}", prefix, name))
+                .expect("Error while reading bytecode")
+        };
+        json!({
+            "address": address,
+            "creation_bytecode": read("creation_tx_input"),
+            "deployed_bytecode": read("deployed_bytecode"),
+        })
+    }
+
+    #[actix_rt::test]
+    async fn returns_result_of_each_deployment() {
+        let app_router = global_app_router().await;
+        let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+        let contract_path = format!("{}/simple_storage/source.sol", CONTRACTS_DIR);
+        let source = fs::read_to_string(&contract_path).expect("Error while reading source");
+        let request = json!({
+            "deployments": [
+                deployment("0x0000000000000000000000000000000000000001", "simple_storage"),
+                deployment("0x0000000000000000000000000000000000000002", "library"),
+                deployment("0x0000000000000000000000000000000000000003", "simple_storage"),
+            ],
+            "compiler_version": "v0.4.24+commit.e67f0147",
+            "sources": BTreeMap::from([(contract_path, source)]),
+            "evm_version": "default",
+        });
+        let response = TestRequest::post()
+            .uri(DEPLOYMENTS_ROUTE)
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Invalid status code (success expected): {}",
+            response.status()
+        );
+
+        let responses: Vec<DeploymentResponse> = read_body_json(response).await;
+        let statuses: Vec<_> = responses
+            .iter()
+            .map(|deployment| (deployment.address.as_str(), &deployment.response.status))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "0x0000000000000000000000000000000000000001",
+                    &VerificationStatus::Ok
+                ),
+                (
+                    "0x0000000000000000000000000000000000000002",
+                    &VerificationStatus::Failed
+                ),
+                (
+                    "0x0000000000000000000000000000000000000003",
+                    &VerificationStatus::Ok
+                ),
+            ],
+            statuses
+        );
+        let result = responses[0].response.result.as_ref().unwrap();
+        assert_eq!("SimpleStorage", result.contract_name);
+    }
+}
+
+mod archive_tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    const ARCHIVE_ROUTE: &str = "/api/v1/solidity/verify/archive";
+    const BOUNDARY: &str = "----archive-boundary";
+
+    fn multipart_body(request: &serde_json::Value, archive: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        write!(
+            body,
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"request\"\r\n\r\n{}\r\n",
+            request
+        )
+        .unwrap();
+        write!(
+            body,
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"archive\"; filename=\"project.zip\"\r\n\
+            Content-Type: application/zip\r\n\r\n"
+        )
+        .unwrap();
+        body.extend_from_slice(archive);
+        write!(body, "\r\n--{BOUNDARY}--\r\n").unwrap();
+        body
+    }
+
+    #[actix_rt::test]
+    async fn verifies_sources_from_zip() {
+        let app_router = global_app_router().await;
+        let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+        let prefix = format!("{}/simple_storage", CONTRACTS_DIR);
+        let read = |name: &str| {
+            fs::read_to_string(format!("{}/{}", prefix, name)).expect("Error while reading file")
+        };
+        let archive = {
+            let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+            zip.start_file(format!("{}/source.sol", prefix), Default::default())
+                .unwrap();
+            zip.write_all(read("source.sol").as_bytes()).unwrap();
+            zip.start_file("README.md", Default::default()).unwrap();
+            zip.finish().unwrap().into_inner()
+        };
+        let request = json!({
+            "creation_bytecode": read("creation_tx_input"),
+            "deployed_bytecode": read("deployed_bytecode"),
+            "compiler_version": "v0.4.24+commit.e67f0147",
+            "evm_version": "default",
+        });
+
+        let response = TestRequest::post()
+            .uri(ARCHIVE_ROUTE)
+            .insert_header((
+                "content-type",
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            ))
+            .set_payload(multipart_body(&request, &archive))
+            .send_request(&app)
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Invalid status code (success expected): {}",
+            response.status()
+        );
+        let verification_response: VerificationResponse = read_body_json(response).await;
+        assert_eq!(
+            verification_response.status,
+            VerificationStatus::Ok,
+            "Invalid verification status. Response: {:?}",
+            verification_response
+        );
+        assert_eq!(
+            vec![format!("{}/source.sol", prefix)],
+            verification_response
+                .result
+                .unwrap()
+                .sources
+                .into_keys()
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub struct TestInput {
     pub creation_tx_input: Option<String>,
     /// If None, the bytecode would be read from the corresponding file
     pub deployed_bytecode: Option<String>,
+    /// If Some, the creation transaction hash and the contract address are sent
+    /// instead of the creation transaction input
+    pub creation_transaction: Option<(&'static str, &'static str)>,
 }
 
 impl TestInput {
@@ -33,6 +36,7 @@ impl TestInput {
             source_code: None,
             creation_tx_input: None,
             deployed_bytecode: None,
+            creation_transaction: None,
         }
     }
 
@@ -75,4 +79,13 @@ impl TestInput {
         self.creation_tx_input = Some(creation_tx_input);
         self
     }
+
+    pub fn with_creation_transaction(
+        mut self,
+        transaction_hash: &'static str,
+        contract_address: &'static str,
+    ) -> Self {
+        self.creation_transaction = Some((transaction_hash, contract_address));
+        self
+    }
 }
@@ -8,6 +8,7 @@ use actix_web::{
 use ethers_solc::artifacts::StandardJsonCompilerInput;
 use pretty_assertions::assert_eq;
 use serde_json::json;
+use smart_contract_verifier::{solidity, Version};
 use smart_contract_verifier_http::{
     configure_router, AppRouter, DisplayBytes, Settings, VerificationResponse, VerificationStatus,
 };
@@ -122,12 +123,18 @@ async fn test_success(dir: &'static str, mut input: TestInput) {
         serde_json::from_str(&input.standard_input.expect("Set `Some` on test_setup"))
             .expect("Standard input deserialization");
 
+    // Compiler defaults are expected to be resolved into the actual values
+    let compiler_version =
+        Version::from_str(input.compiler_version).expect("Invalid compiler version");
     assert_eq!(
         verification_result.evm_version,
         standard_input
             .settings
             .evm_version
             .map(|version| version.to_string())
+            .or_else(|| {
+                solidity::default_evm_version(compiler_version.version()).map(String::from)
+            })
             .unwrap_or_else(|| "default".to_string()),
         "Invalid evm version"
     );
@@ -142,9 +149,10 @@ async fn test_success(dir: &'static str, mut input: TestInput) {
             .libraries
             .libs
             .into_iter()
-            .for_each(|(_path, libs)| {
+            .for_each(|(path, libs)| {
                 libs.into_iter().for_each(|(contract, address)| {
-                    formatted_libs.insert(contract, address);
+                    formatted_libs
+                        .insert(format!("{}:{}", path.to_string_lossy(), contract), address);
                 })
             });
         formatted_libs
@@ -154,11 +162,17 @@ async fn test_success(dir: &'static str, mut input: TestInput) {
         "Invalid contract libraries"
     );
     assert_eq!(
-        verification_result.optimization, standard_input.settings.optimizer.enabled,
+        verification_result.optimization,
+        standard_input.settings.optimizer.enabled.or(Some(false)),
         "Invalid optimization"
     );
     assert_eq!(
-        verification_result.optimization_runs, standard_input.settings.optimizer.runs,
+        verification_result.optimization_runs,
+        standard_input
+            .settings
+            .optimizer
+            .runs
+            .or(Some(solidity::DEFAULT_OPTIMIZER_RUNS)),
         "Invalid optimization runs"
     );
     assert_eq!(
@@ -207,3 +221,102 @@ mod regression_tests {
         test_success(contract_dir, test_input).await;
     }
 }
+
+mod artifacts_tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn compiler_input_keeps_output_selection() {
+        let app_router = global_app_router().await;
+        let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+        let prefix = format!("{}/issue_with_creation_code", CONTRACTS_DIR);
+        let read = |file: &str| {
+            fs::read_to_string(format!("{}/{}", prefix, file))
+                .unwrap_or_else(|err| panic!("Error while reading {}: {}", file, err))
+        };
+        let input: serde_json::Value =
+            serde_json::from_str(&read("standard_input.json")).expect("Invalid standard input");
+        let request = json!({
+            "deployed_bytecode": read("deployed_bytecode"),
+            "creation_bytecode": read("creation_tx_input"),
+            "compiler_version": "v0.5.16+commit.9c3226ce",
+            "input": input.to_string(),
+            "artifacts": ["compiler_input"],
+        });
+
+        let response = TestRequest::post()
+            .uri(ROUTE)
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Invalid status code: {}",
+            response.status()
+        );
+        let verification_response: VerificationResponse = read_body_json(response).await;
+        let compiler_input = verification_response
+            .result
+            .and_then(|result| result.artifacts)
+            .and_then(|artifacts| artifacts.compiler_input)
+            .expect("Compiler input should be returned");
+        assert_eq!(
+            input.pointer("/settings/outputSelection"),
+            compiler_input.pointer("/settings/outputSelection"),
+            "Output selection of the request should be returned"
+        );
+    }
+}
+
+mod build_info_tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn hardhat_build_info() {
+        let app_router = global_app_router().await;
+        let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+        let prefix = format!("{}/issue_5748", CONTRACTS_DIR);
+        let read = |file: &str| {
+            fs::read_to_string(format!("{}/{}", prefix, file))
+                .unwrap_or_else(|err| panic!("Error while reading {}: {}", file, err))
+        };
+        let input: serde_json::Value =
+            serde_json::from_str(&read("standard_input.json")).expect("Invalid standard input");
+        let request = json!({
+            "deployed_bytecode": read("deployed_bytecode"),
+            "creation_bytecode": read("creation_tx_input"),
+            "build_info": {
+                "_format": "hh-sol-build-info-1",
+                "solcVersion": "0.6.8",
+                "solcLongVersion": "0.6.8+commit.0bbfe453",
+                "input": input,
+                // Compilation output is ignored
+                "output": { "contracts": {}, "sources": {} },
+            },
+        });
+
+        let response = TestRequest::post()
+            .uri("/api/v1/solidity/verify/build-info")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(
+            response.status().is_success(),
+            "Invalid status code: {}",
+            response.status()
+        );
+        let verification_response: VerificationResponse = read_body_json(response).await;
+        assert_eq!(
+            verification_response.status,
+            VerificationStatus::Ok,
+            "Invalid verification status. Response: {:?}",
+            verification_response
+        );
+        assert_eq!(
+            verification_response.result.unwrap().contract_name,
+            "ExternalTestJson"
+        );
+    }
+}
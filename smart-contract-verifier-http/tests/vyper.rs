@@ -239,3 +239,50 @@ async fn vyper_verify_error() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn vyper_verify_with_source_maps() {
+    let test_case = TestCase::from_name("erc20");
+    let app_router = global_app_router().await;
+    let app = test::init_service(App::new().configure(configure_router(app_router))).await;
+
+    let request = serde_json::json!({
+        "deployed_bytecode": test_case.deployed_bytecode,
+        "creation_bytecode": test_case.creation_bytecode,
+        "compiler_version": test_case.compiler_version,
+        "sources": {
+            format!("{}.vy", test_case.contract_name): test_case.source_code
+        },
+        "artifacts": ["source_maps"],
+    });
+    let response = TestRequest::post()
+        .uri(ROUTE)
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(
+        response.status().is_success(),
+        "Invalid status code: {}",
+        response.status()
+    );
+
+    let verification_response: VerificationResponse = read_body_json(response).await;
+    assert_eq!(
+        verification_response.status,
+        VerificationStatus::Ok,
+        "Invalid verification status. Response: {:?}",
+        verification_response
+    );
+    let artifacts = verification_response
+        .result
+        .and_then(|result| result.artifacts)
+        .expect("Artifacts should be returned");
+    assert!(
+        artifacts.deployed_source_map.is_some(),
+        "Deployed source map should be returned"
+    );
+    assert_eq!(
+        artifacts.source_map, None,
+        "Vyper does not output source map of the creation bytecode"
+    );
+}
@@ -0,0 +1,182 @@
+//! Sharing of compilation outputs between verifications.
+//!
+//! Similar to the progress listener, the cache is not passed to the verification
+//! functions explicitly, but is attached to the future via [`with_cache`].
+//! All verifications run inside the same cache compile each distinct input only once
+//! (e.g., several deployments of the same contract verified as a part of the batch).
+
+use super::{compilers::Error, ExtraSettings, Version};
+use ethers_solc::{CompilerInput, CompilerOutput};
+use parking_lot::Mutex;
+use std::{collections::HashMap, future::Future, sync::Arc};
+use tokio::sync::OnceCell;
+
+/// Outputs are kept while the cache is alive, so it should be scoped
+/// to the group of verifications which are expected to share the inputs.
+#[derive(Default)]
+pub struct CompilationCache {
+    outputs: Mutex<HashMap<String, Arc<OnceCell<CompilerOutput>>>>,
+}
+
+impl CompilationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct inputs compiled inside the cache.
+    pub fn len(&self) -> usize {
+        self.outputs
+            .lock()
+            .values()
+            .filter(|output| output.initialized())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entry(&self, key: String) -> Arc<OnceCell<CompilerOutput>> {
+        self.outputs.lock().entry(key).or_default().clone()
+    }
+}
+
+tokio::task_local! {
+    static CACHE: Arc<CompilationCache>;
+}
+
+/// Runs the `future` reusing the outputs of the compilations made inside the `cache`.
+pub async fn with_cache<F: Future>(cache: Arc<CompilationCache>, future: F) -> F::Output {
+    CACHE.scope(cache, future).await
+}
+
+/// Compiles the input via `compile` if the call is not made inside [`with_cache`],
+/// or if the input has not been compiled inside the cache yet.
+/// Concurrent calls with the same input wait for the single compilation to finish.
+/// Failed compilations are not cached.
+pub(crate) async fn get_or_compile<F, Fut>(
+    compiler_version: &Version,
+    input: &CompilerInput,
+    extra_settings: &ExtraSettings,
+    compile: F,
+) -> Result<CompilerOutput, Error>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<CompilerOutput, Error>>,
+{
+    let cache = match CACHE.try_with(Arc::clone) {
+        Ok(cache) => cache,
+        Err(_) => return compile().await,
+    };
+    let key = match cache_key(compiler_version, input, extra_settings) {
+        Some(key) => key,
+        None => return compile().await,
+    };
+    let output = cache.entry(key);
+    let output = output.get_or_try_init(compile).await?;
+    Ok(output.clone())
+}
+
+/// Inputs are identified by their serialized form (which includes the extra settings).
+fn cache_key(
+    compiler_version: &Version,
+    input: &CompilerInput,
+    extra_settings: &ExtraSettings,
+) -> Option<String> {
+    let mut input = serde_json::to_value(input).ok()?;
+    extra_settings.apply_to_json(&mut input);
+    Some(format!("{}:{}", compiler_version, input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_solc::artifacts::{Source, Sources};
+    use pretty_assertions::assert_eq;
+    use std::{
+        str::FromStr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn input(source: &str) -> CompilerInput {
+        CompilerInput {
+            language: "Solidity".to_string(),
+            sources: Sources::from([(
+                "source.sol".into(),
+                Source {
+                    content: source.into(),
+                },
+            )]),
+            settings: Default::default(),
+        }
+    }
+
+    async fn compile(
+        calls: &AtomicUsize,
+        version: &Version,
+        input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+    ) -> Result<CompilerOutput, Error> {
+        get_or_compile(version, input, extra_settings, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(CompilerOutput::default())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn compiles_distinct_inputs_once() {
+        let calls = AtomicUsize::new(0);
+        let version = Version::from_str("v0.8.10+commit.fc410830").unwrap();
+        let first = input("contract A {}");
+        let second = input("contract B {}");
+        let no_cbor = ExtraSettings {
+            append_cbor: Some(false),
+        };
+
+        let cache = Arc::new(CompilationCache::new());
+        with_cache(cache.clone(), async {
+            let default = ExtraSettings::default();
+            futures::future::join_all([
+                compile(&calls, &version, &first, &default),
+                compile(&calls, &version, &first, &default),
+                compile(&calls, &version, &second, &default),
+                compile(&calls, &version, &first, &no_cbor),
+            ])
+            .await;
+            compile(&calls, &version, &second, &default).await.unwrap();
+        })
+        .await;
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        assert_eq!(3, cache.len());
+
+        // Outside of the cache scope every call compiles the input
+        compile(&calls, &version, &first, &ExtraSettings::default())
+            .await
+            .unwrap();
+        assert_eq!(4, calls.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn failed_compilations_are_not_cached() {
+        let version = Version::from_str("v0.8.10+commit.fc410830").unwrap();
+        let input = input("contract A {}");
+        let extra_settings = ExtraSettings::default();
+
+        let cache = Arc::new(CompilationCache::new());
+        with_cache(cache.clone(), async {
+            let result = get_or_compile(&version, &input, &extra_settings, || async {
+                Err(Error::Compilation(vec!["ParserError".into()]))
+            })
+            .await;
+            assert!(result.is_err());
+            let calls = AtomicUsize::new(0);
+            compile(&calls, &version, &input, &extra_settings)
+                .await
+                .unwrap();
+            assert_eq!(1, calls.load(Ordering::SeqCst));
+        })
+        .await;
+        assert_eq!(1, cache.len());
+    }
+}
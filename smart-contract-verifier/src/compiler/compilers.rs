@@ -1,9 +1,13 @@
 use super::{
+    compilation_cache,
     download_cache::DownloadCache,
     fetcher::{FetchError, Fetcher},
     version::Version,
 };
-use crate::metrics::{self, GuardedGauge};
+use crate::{
+    metrics::{self, GuardedGauge},
+    progress::{self, VerificationStage},
+};
 use ethers_solc::{artifacts::Severity, error::SolcError, CompilerInput, CompilerOutput};
 use std::{
     fmt::Debug,
@@ -28,6 +32,25 @@ pub enum Error {
     Acquire(#[from] AcquireError),
 }
 
+/// Compiler input settings which are not supported by [`CompilerInput`],
+/// and thus have to be passed to the compiler separately.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtraSettings {
+    /// `settings.metadata.appendCBOR` (solc >= 0.8.18). If `false`, the compiler
+    /// does not append CBOR encoded metadata to the bytecode.
+    /// `None` leaves the compiler default (`true`).
+    pub append_cbor: Option<bool>,
+}
+
+impl ExtraSettings {
+    /// Inserts the settings into already serialized compiler input.
+    pub fn apply_to_json(&self, input: &mut serde_json::Value) {
+        if let Some(append_cbor) = self.append_cbor {
+            input["settings"]["metadata"]["appendCBOR"] = append_cbor.into();
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait EvmCompiler {
     async fn compile(
@@ -35,6 +58,7 @@ pub trait EvmCompiler {
         path: &Path,
         ver: &Version,
         input: &CompilerInput,
+        extra_settings: &ExtraSettings,
     ) -> Result<CompilerOutput, SolcError>;
 }
 
@@ -61,12 +85,73 @@ where
             threads_semaphore,
         }
     }
-    #[instrument(name = "download_and_compile", skip(self, input), level = "debug")]
     pub async fn compile(
         &self,
         compiler_version: &Version,
         input: &CompilerInput,
     ) -> Result<CompilerOutput, Error> {
+        self.compile_with_extra_settings(compiler_version, input, &ExtraSettings::default())
+            .await
+    }
+
+    pub async fn compile_with_extra_settings(
+        &self,
+        compiler_version: &Version,
+        input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+    ) -> Result<CompilerOutput, Error> {
+        let output = self
+            .compile_output(compiler_version, input, extra_settings)
+            .await?;
+
+        // Compilations errors, warnings and info messages are returned in `CompilerOutput.error`
+        let mut errors = Vec::new();
+        for err in &output.errors {
+            if err.severity == Severity::Error {
+                errors.push(
+                    err.formatted_message
+                        .as_ref()
+                        .unwrap_or(&err.message)
+                        .clone(),
+                )
+            }
+        }
+        if !errors.is_empty() {
+            return Err(Error::Compilation(errors));
+        }
+
+        Ok(output)
+    }
+
+    /// Compiles the input without treating compilation errors as a failure,
+    /// so that all errors and warnings are returned as a part of the output.
+    ///
+    /// If called inside [`with_compilation_cache`](crate::with_compilation_cache),
+    /// the output of the same input compiled before is reused.
+    pub async fn compile_output(
+        &self,
+        compiler_version: &Version,
+        input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+    ) -> Result<CompilerOutput, Error> {
+        compilation_cache::get_or_compile(compiler_version, input, extra_settings, || {
+            self.download_and_compile(compiler_version, input, extra_settings)
+        })
+        .await
+    }
+
+    #[instrument(
+        name = "download_and_compile",
+        skip(self, input, extra_settings),
+        level = "debug"
+    )]
+    async fn download_and_compile(
+        &self,
+        compiler_version: &Version,
+        input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+    ) -> Result<CompilerOutput, Error> {
+        progress::report(VerificationStage::FetchingCompiler);
         let path_result = {
             self.cache
                 .get(self.fetcher.as_ref(), compiler_version)
@@ -88,29 +173,14 @@ where
                 let _wait_gauge_guard = metrics::COMPILATIONS_IN_QUEUE.guarded_inc();
                 self.threads_semaphore.acquire().await?
             };
+            progress::report(VerificationStage::Compiling);
             let _compile_timer_guard = metrics::COMPILE_TIME.start_timer();
             let _compile_gauge_guard = metrics::COMPILATIONS_IN_FLIGHT.guarded_inc();
             self.evm_compiler
-                .compile(&path, compiler_version, input)
+                .compile(&path, compiler_version, input, extra_settings)
                 .await?
         };
 
-        // Compilations errors, warnings and info messages are returned in `CompilerOutput.error`
-        let mut errors = Vec::new();
-        for err in &output.errors {
-            if err.severity == Severity::Error {
-                errors.push(
-                    err.formatted_message
-                        .as_ref()
-                        .unwrap_or(&err.message)
-                        .clone(),
-                )
-            }
-        }
-        if !errors.is_empty() {
-            return Err(Error::Compilation(errors));
-        }
-
         Ok(output)
     }
 
@@ -264,4 +334,31 @@ mod tests {
             _ => panic!("Invalid compilation error: {:?}", result),
         }
     }
+
+    #[tokio::test]
+    async fn compile_output_returns_compilation_errors() {
+        let source_code = r#"pragma solidity ^0.8.10; cont SimpleStorage {"#;
+
+        let compilers = global_compilers().await;
+        let input: CompilerInput = Input::with_source_code(source_code.into()).into();
+        let version = Version::from_str("v0.8.10+commit.fc410830").expect("Compiler version");
+
+        let output = compilers
+            .compile_output(&version, &input, &ExtraSettings::default())
+            .await
+            .expect("Compilation errors should be returned as a part of the output");
+        let has_parser_error = output.errors.iter().any(|err| {
+            err.severity == Severity::Error
+                && err
+                    .formatted_message
+                    .as_ref()
+                    .unwrap_or(&err.message)
+                    .contains("ParserError")
+        });
+        assert!(
+            has_parser_error,
+            "Parser error is missing: {:?}",
+            output.errors
+        );
+    }
 }
@@ -5,10 +5,12 @@ mod list_fetcher;
 mod s3_fetcher;
 mod versions_fetcher;
 
+mod compilation_cache;
 mod compilers;
 mod download_cache;
 
-pub use compilers::{Compilers, Error, EvmCompiler};
+pub use compilation_cache::{with_cache as with_compilation_cache, CompilationCache};
+pub use compilers::{Compilers, Error, EvmCompiler, ExtraSettings};
 pub use fetcher::{Fetcher, FileValidator};
 pub use list_fetcher::ListFetcher;
 pub use s3_fetcher::S3Fetcher;
@@ -1,10 +1,12 @@
+mod metrics;
 pub mod solidity;
 pub mod sourcify;
+pub mod traces;
 pub mod vyper;
 
 mod compiler;
 mod consts;
-mod metrics;
+mod progress;
 mod verifier;
 
 // TODO: to be extracted in a separate crate
@@ -18,10 +20,20 @@ pub(crate) use ethers_core::types::Bytes as DisplayBytes;
 
 pub use consts::{DEFAULT_SOLIDITY_COMPILER_LIST, DEFAULT_VYPER_COMPILER_LIST};
 
-pub use compiler::{Compilers, Fetcher, ListFetcher, S3Fetcher, Version};
+pub use compiler::{
+    with_compilation_cache, CompilationCache, Compilers, Error as CompilerError, ExtraSettings,
+    Fetcher, ListFetcher, S3Fetcher, Version,
+};
+pub use progress::{with_listener as with_progress_listener, ProgressListener, VerificationStage};
 pub use sourcify::{Error as SourcifyError, Success as SourcifySuccess};
-pub use verifier::{Error as VerificationError, Success as VerificationSuccess};
+pub use verifier::{
+    ArtifactKind, Artifacts, BytecodeInspection, ContractName, ContractVerificationError,
+    Deployment, Error as VerificationError, MatchType, MetadataSection, ParseArtifactKindError,
+    ParseContractNameError, ProxyPattern, SourceNormalization, Success as VerificationSuccess,
+    VerificationErrorKind,
+};
 
-pub use solidity::{SolcValidator, SolidityCompiler};
+pub use solidity::{PackageRegistry, SolcValidator, SolidityCompiler};
 pub use sourcify::SourcifyApiClient;
+pub use traces::TraceApiClient;
 pub use vyper::VyperCompiler;
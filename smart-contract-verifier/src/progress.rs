@@ -0,0 +1,71 @@
+//! Reporting of the verification progress to the caller.
+//!
+//! Verification functions do not accept the listener explicitly. Instead, the listener
+//! is attached to the future running the verification via [`with_listener`],
+//! so that the progress may be tracked for any of `solidity::*::verify`
+//! and `vyper::*::verify` functions.
+
+use std::{future::Future, sync::Arc};
+
+/// Stage the verification is currently at. The stages may repeat,
+/// as the sources may be compiled several times during a single verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationStage {
+    /// Compiler is being downloaded, or is waiting for the free compilation thread.
+    FetchingCompiler,
+    Compiling,
+    /// Compiled contracts are compared with the remote bytecode.
+    Comparing,
+}
+
+/// Listeners are `Send + Sync`, so that the verification may be run on any runtime thread.
+pub trait ProgressListener: Send + Sync {
+    fn on_stage(&self, stage: VerificationStage);
+}
+
+tokio::task_local! {
+    static LISTENER: Arc<dyn ProgressListener>;
+}
+
+/// Runs the `future` reporting the stages of all verifications it makes to the `listener`.
+pub async fn with_listener<F: Future>(listener: Arc<dyn ProgressListener>, future: F) -> F::Output {
+    LISTENER.scope(listener, future).await
+}
+
+/// Does nothing if the verification is not run inside [`with_listener`].
+pub(crate) fn report(stage: VerificationStage) {
+    let _ = LISTENER.try_with(|listener| listener.on_stage(stage));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Stages(Mutex<Vec<VerificationStage>>);
+
+    impl ProgressListener for Stages {
+        fn on_stage(&self, stage: VerificationStage) {
+            self.0.lock().unwrap().push(stage);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_stages_inside_scope_only() {
+        let stages = Arc::new(Stages::default());
+        report(VerificationStage::FetchingCompiler);
+        with_listener(stages.clone(), async {
+            report(VerificationStage::Compiling);
+            report(VerificationStage::Comparing);
+        })
+        .await;
+        report(VerificationStage::Compiling);
+
+        assert_eq!(
+            vec![VerificationStage::Compiling, VerificationStage::Comparing],
+            *stages.0.lock().unwrap()
+        );
+    }
+}
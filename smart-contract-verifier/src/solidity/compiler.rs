@@ -1,5 +1,5 @@
 use super::solc_cli;
-use crate::compiler::{EvmCompiler, Version};
+use crate::compiler::{EvmCompiler, ExtraSettings, Version};
 use ethers_solc::{error::SolcError, CompilerOutput, Solc};
 use std::path::Path;
 
@@ -19,11 +19,22 @@ impl EvmCompiler for SolidityCompiler {
         path: &Path,
         ver: &Version,
         input: &ethers_solc::CompilerInput,
+        extra_settings: &ExtraSettings,
     ) -> Result<CompilerOutput, SolcError> {
         if ver.version() < &semver::Version::new(0, 4, 11) {
-            solc_cli::compile_using_cli(path, input).await
-        } else {
-            Solc::from(path).async_compile(input).await
+            return solc_cli::compile_using_cli(path, input).await;
+        }
+
+        let solc = Solc::from(path);
+        match extra_settings.append_cbor {
+            None => solc.async_compile(input).await,
+            Some(_) => {
+                // `ethers_solc::CompilerInput` does not support "appendCBOR" option,
+                // so it is inserted into already serialized input
+                let mut input = serde_json::to_value(input)?;
+                extra_settings.apply_to_json(&mut input);
+                solc.async_compile(&input).await
+            }
         }
     }
 }
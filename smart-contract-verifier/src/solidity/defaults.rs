@@ -0,0 +1,59 @@
+//! Settings solc applies if they are not specified in the compiler input.
+
+/// `settings.optimizer.runs` used by solc if not specified,
+/// regardless of whether the optimizer is enabled or not.
+pub const DEFAULT_OPTIMIZER_RUNS: usize = 200;
+
+/// Default EVM versions with the first compiler versions they became the default in.
+const DEFAULT_EVM_VERSIONS: [((u64, u64, u64), &str); 9] = [
+    ((0, 4, 21), "byzantium"),
+    ((0, 5, 5), "petersburg"),
+    ((0, 5, 14), "istanbul"),
+    ((0, 8, 5), "berlin"),
+    ((0, 8, 7), "london"),
+    ((0, 8, 18), "paris"),
+    ((0, 8, 20), "shanghai"),
+    ((0, 8, 25), "cancun"),
+    ((0, 8, 30), "prague"),
+];
+
+/// Returns the EVM version targeted by the compiler if `settings.evmVersion` is not specified
+/// (https://docs.soliditylang.org/en/latest/using-the-compiler.html#target-options).
+///
+/// Returns `None` for the compilers older than 0.4.21, as they do not support choosing EVM version.
+pub fn default_evm_version(version: &semver::Version) -> Option<&'static str> {
+    // Nightly builds are considered to have the defaults of the upcoming release
+    let version = (version.major, version.minor, version.patch);
+    DEFAULT_EVM_VERSIONS
+        .iter()
+        .rev()
+        .find(|(since, _)| version >= *since)
+        .map(|(_, evm_version)| *evm_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn default_for(version: &str) -> Option<&'static str> {
+        default_evm_version(&semver::Version::parse(version).expect("Valid semver"))
+    }
+
+    #[test]
+    fn default_evm_versions() {
+        assert_eq!(None, default_for("0.4.20"));
+        assert_eq!(Some("byzantium"), default_for("0.4.21"));
+        assert_eq!(Some("petersburg"), default_for("0.5.13"));
+        assert_eq!(Some("istanbul"), default_for("0.8.4"));
+        assert_eq!(Some("london"), default_for("0.8.14"));
+        assert_eq!(Some("paris"), default_for("0.8.18"));
+        assert_eq!(Some("shanghai"), default_for("0.8.20"));
+        assert_eq!(Some("cancun"), default_for("0.8.25"));
+    }
+
+    #[test]
+    fn nightly_has_defaults_of_release() {
+        assert_eq!(Some("paris"), default_for("0.8.18-nightly.2022.11.23"));
+    }
+}
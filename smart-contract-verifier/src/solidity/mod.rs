@@ -1,4 +1,6 @@
 mod compiler;
+mod defaults;
+mod package_registry;
 mod solc_cli;
 mod validator;
 
@@ -6,4 +8,6 @@ pub mod multi_part;
 pub mod standard_json;
 
 pub use compiler::SolidityCompiler;
+pub use defaults::{default_evm_version, DEFAULT_OPTIMIZER_RUNS};
+pub use package_registry::PackageRegistry;
 pub use validator::SolcValidator;
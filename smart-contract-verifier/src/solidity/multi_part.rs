@@ -1,21 +1,34 @@
-use super::compiler::SolidityCompiler;
+use super::{compiler::SolidityCompiler, package_registry::PackageRegistry};
 use crate::{
-    compiler::{Compilers, Version},
-    verifier::{ContractVerifier, Error, Success},
+    compiler::{Compilers, ExtraSettings, Version},
+    verifier::{ArtifactKind, ContractName, ContractVerifier, Deployment, Error, Success},
 };
 use bytes::Bytes;
 use ethers_solc::{
-    artifacts::{BytecodeHash, Libraries, Settings, SettingsMetadata, Source, Sources},
+    artifacts::{
+        BytecodeHash, Libraries, OptimizerDetails, Settings, SettingsMetadata, Source, Sources,
+    },
+    remappings::Remapping,
     CompilerInput, EvmVersion,
 };
 use semver::VersionReq;
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::Arc,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VerificationRequest {
     pub deployed_bytecode: Bytes,
     pub creation_bytecode: Bytes,
     pub compiler_version: Version,
+    /// If specified, only that contract is compared with the remote bytecode
+    pub contract_name: Option<ContractName>,
+    /// If the match is partial, try to get the full one by normalizing the sources
+    pub normalize_sources: bool,
+    /// Compilation artifacts to be returned with the successful result
+    pub artifacts: BTreeSet<ArtifactKind>,
 
     pub content: MultiFileContent,
 }
@@ -24,23 +37,49 @@ pub struct VerificationRequest {
 pub struct MultiFileContent {
     pub sources: BTreeMap<PathBuf, String>,
     pub evm_version: Option<EvmVersion>,
+    /// If `None`, optimizations are considered enabled only if `optimization_runs` is specified
+    pub optimization: Option<bool>,
     pub optimization_runs: Option<usize>,
+    pub optimizer_details: Option<OptimizerDetails>,
+    pub via_ir: Option<bool>,
+    pub remappings: Vec<Remapping>,
+    /// Library names may be either bare (`Name`) or qualified
+    /// with the file the library is located at (`path:Name`)
     pub contract_libraries: Option<BTreeMap<String, String>>,
 }
 
 impl From<MultiFileContent> for CompilerInput {
     fn from(content: MultiFileContent) -> Self {
         let mut settings = Settings::default();
-        settings.optimizer.enabled = Some(content.optimization_runs.is_some());
+        settings.optimizer.enabled = Some(
+            content
+                .optimization
+                .unwrap_or(content.optimization_runs.is_some()),
+        );
         settings.optimizer.runs = content.optimization_runs;
-        if let Some(libs) = content.contract_libraries {
-            // we have to know filename for library, but we don't know,
-            // so we assume that every file MAY contains all libraries
-            let libs = content
-                .sources
-                .iter()
-                .map(|(filename, _)| (PathBuf::from(filename), libs.clone()))
-                .collect();
+        settings.optimizer.details = content.optimizer_details;
+        settings.via_ir = content.via_ir;
+        settings.remappings = content.remappings;
+        if let Some(contract_libraries) = content.contract_libraries {
+            let mut libs: BTreeMap<PathBuf, BTreeMap<String, String>> = BTreeMap::new();
+            for (name, address) in contract_libraries {
+                match name.rsplit_once(':') {
+                    Some((filename, name)) => {
+                        libs.entry(PathBuf::from(filename))
+                            .or_default()
+                            .insert(name.to_string(), address);
+                    }
+                    None => {
+                        // we have to know filename for library, but we don't know,
+                        // so we assume that every file MAY contains the library
+                        for filename in content.sources.keys() {
+                            libs.entry(filename.clone())
+                                .or_default()
+                                .insert(name.clone(), address.clone());
+                        }
+                    }
+                }
+            }
             settings.libraries = Libraries { libs };
         }
         settings.evm_version = content.evm_version;
@@ -61,32 +100,96 @@ impl From<MultiFileContent> for CompilerInput {
 pub async fn verify(
     compilers: Arc<Compilers<SolidityCompiler>>,
     request: VerificationRequest,
+    packages: Option<&PackageRegistry>,
 ) -> Result<Success, Error> {
+    let deployment = Deployment {
+        creation_tx_input: request.creation_bytecode.clone(),
+        deployed_bytecode: request.deployed_bytecode.clone(),
+    };
+    let mut results = verify_deployments(compilers, request, vec![deployment], packages).await?;
+    results.remove(0)
+}
+
+/// Verifies the sources of the `request` against each of the `deployments`
+/// (instead of the bytecodes specified in the request), compiling them once
+/// for each of the tried settings metadata options.
+///
+/// Returns an error if the sources could not be compiled. Otherwise, returns
+/// the results of the verifications in the order of `deployments`.
+pub async fn verify_deployments(
+    compilers: Arc<Compilers<SolidityCompiler>>,
+    request: VerificationRequest,
+    deployments: Vec<Deployment>,
+    packages: Option<&PackageRegistry>,
+) -> Result<Vec<Result<Success, Error>>, Error> {
     let compiler_version = request.compiler_version;
+    let mut content = request.content;
+
+    let external_sources = match packages {
+        Some(packages) => packages
+            .resolve_missing_imports(
+                &content.sources,
+                &content.remappings,
+                compiler_version.version(),
+            )
+            .await
+            .map_err(Error::Internal)?,
+        None => BTreeMap::new(),
+    };
+    let external_paths: Vec<_> = external_sources.keys().cloned().collect();
+    content.sources.extend(external_sources);
 
-    let verifier = ContractVerifier::new(
+    let verifier = ContractVerifier::for_deployments(
         compilers,
         &compiler_version,
-        request.creation_bytecode,
-        request.deployed_bytecode,
-    )?;
+        deployments,
+        request.contract_name,
+    )?
+    .with_artifacts(request.artifacts);
 
-    let mut compiler_input = CompilerInput::from(request.content);
-    for metadata in settings_metadata(&compiler_version) {
+    let mut compiler_input = CompilerInput::from(content);
+    let mut results: Vec<Option<Result<Success, Error>>> =
+        (0..verifier.deployments_len()).map(|_| None).collect();
+    for (metadata, extra_settings) in settings_metadata(&compiler_version) {
+        let unverified: Vec<_> = (0..results.len())
+            .filter(|&deployment| !matches!(results[deployment], Some(Ok(_))))
+            .collect();
+        if unverified.is_empty() {
+            break;
+        }
         compiler_input.settings.metadata = metadata;
-        let result = verifier.verify(&compiler_input).await;
+        // Uncorrectable errors (e.g., compilation ones) are the same for all deployments
+        let unverified_results = verifier
+            .verify_deployments(&compiler_input, &extra_settings, &unverified)
+            .await?;
 
-        // If no matching contracts have been found, try the next settings metadata option
-        if let Err(Error::NoMatchingContracts) = result {
-            continue;
+        for (deployment, result) in unverified.into_iter().zip(unverified_results) {
+            match result {
+                Ok(success) => {
+                    results[deployment] = Some(Ok(Success {
+                        external_sources: external_paths.clone(),
+                        ..success
+                    }));
+                }
+                // If no matching contracts have been found, try the next settings metadata option.
+                // The failure details of the first option are kept, as it corresponds
+                // to the metadata settings the compiler uses by default
+                Err(err) => {
+                    results[deployment].get_or_insert(Err(err));
+                }
+            }
         }
-
-        // Otherwise, verification either succeeded, or some uncorrectable error occurred
-        return result;
     }
 
-    // No contracts could be verified
-    Err(Error::NoMatchingContracts)
+    // Deployments no contracts could be verified for keep their first failure
+    let results = results
+        .into_iter()
+        .map(|result| result.unwrap_or(Err(Error::NoMatchingContracts(vec![]))))
+        .collect();
+    if request.normalize_sources {
+        return Ok(verifier.verify_deployments_normalized(results).await);
+    }
+    Ok(results)
 }
 
 /// Iterates through possible bytecode if required and creates
@@ -95,28 +198,46 @@ pub async fn verify(
 /// Multi-file input type does not specify it explicitly, thus, we may
 /// have to iterate through all possible options.
 ///
-/// See "settings_metadata" (https://docs.soliditylang.org/en/v0.8.15/using-the-compiler.html?highlight=compiler%20input#input-description)
-fn settings_metadata(compiler_version: &Version) -> Vec<Option<SettingsMetadata>> {
+/// Starting from solc 0.8.18 metadata may be not appended to the bytecode at all
+/// ("appendCBOR"), which is not supported by [`SettingsMetadata`], so is returned
+/// as a part of [`ExtraSettings`].
+///
+/// See "settings_metadata" (https://docs.soliditylang.org/en/v0.8.18/using-the-compiler.html#input-description)
+fn settings_metadata(compiler_version: &Version) -> Vec<(Option<SettingsMetadata>, ExtraSettings)> {
     // Options are sorted by their probability of occurring
     const BYTECODE_HASHES: [BytecodeHash; 3] =
         [BytecodeHash::Ipfs, BytecodeHash::None, BytecodeHash::Bzzr1];
 
-    if VersionReq::parse("<0.6.0")
-        .unwrap()
-        .matches(compiler_version.version())
-    {
-        [None].into()
-    } else {
-        BYTECODE_HASHES
-            .map(|hash| Some(SettingsMetadata::from(hash)))
-            .into()
+    let matches = |requirement: &str| {
+        VersionReq::parse(requirement)
+            .unwrap()
+            .matches(compiler_version.version())
+    };
+
+    if matches("<0.6.0") {
+        return [(None, ExtraSettings::default())].into();
+    }
+
+    let mut result: Vec<_> = BYTECODE_HASHES
+        .map(|hash| (Some(SettingsMetadata::from(hash)), ExtraSettings::default()))
+        .into();
+    if matches(">=0.8.18") {
+        // Bytecode hash does not affect the bytecode if metadata is not appended
+        result.push((
+            None,
+            ExtraSettings {
+                append_cbor: Some(false),
+            },
+        ));
     }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use std::str::FromStr;
 
     fn sources(sources: &[(&str, &str)]) -> BTreeMap<PathBuf, String> {
         sources
@@ -137,7 +258,11 @@ mod tests {
         let multi_part = MultiFileContent {
             sources: sources(&[("source.sol", "pragma")]),
             evm_version: Some(EvmVersion::London),
+            optimization: None,
             optimization_runs: Some(200),
+            optimizer_details: None,
+            via_ir: None,
+            remappings: vec![],
             contract_libraries: Some(BTreeMap::from([(
                 "some_library".into(),
                 "some_address".into(),
@@ -148,10 +273,119 @@ mod tests {
         let multi_part = MultiFileContent {
             sources: sources(&[("source.sol", "")]),
             evm_version: Some(EvmVersion::SpuriousDragon),
+            optimization: None,
             optimization_runs: None,
+            optimizer_details: None,
+            via_ir: None,
+            remappings: vec![],
             contract_libraries: None,
         };
         let expected = r#"{"language":"Solidity","sources":{"source.sol":{"content":""}},"settings":{"optimizer":{"enabled":false},"outputSelection":{"*":{"":["ast"],"*":["abi","evm.bytecode","evm.deployedBytecode","evm.methodIdentifiers"]}},"evmVersion":"spuriousDragon","libraries":{}}}"#;
         test_to_input(multi_part, expected);
     }
+
+    #[test]
+    fn multi_part_with_via_ir_and_optimizer_details_to_input() {
+        let multi_part = MultiFileContent {
+            sources: sources(&[("source.sol", "pragma")]),
+            evm_version: None,
+            optimization: Some(true),
+            optimization_runs: None,
+            optimizer_details: Some(OptimizerDetails {
+                yul: Some(true),
+                ..Default::default()
+            }),
+            via_ir: Some(true),
+            remappings: vec![],
+            contract_libraries: None,
+        };
+        let input = CompilerInput::from(multi_part);
+        assert_eq!(Some(true), input.settings.optimizer.enabled);
+        assert_eq!(None, input.settings.optimizer.runs);
+        assert_eq!(
+            Some(true),
+            input
+                .settings
+                .optimizer
+                .details
+                .and_then(|details| details.yul),
+            "Invalid optimizer details"
+        );
+        assert_eq!(Some(true), input.settings.via_ir);
+
+        let multi_part = MultiFileContent {
+            sources: sources(&[("source.sol", "pragma")]),
+            evm_version: None,
+            optimization: Some(false),
+            optimization_runs: Some(200),
+            optimizer_details: None,
+            via_ir: None,
+            remappings: vec![],
+            contract_libraries: None,
+        };
+        let input = CompilerInput::from(multi_part);
+        assert_eq!(
+            Some(false),
+            input.settings.optimizer.enabled,
+            "Explicit optimization flag should take precedence over runs"
+        );
+        assert_eq!(Some(200), input.settings.optimizer.runs);
+    }
+
+    #[test]
+    fn multi_part_with_remappings_and_qualified_libraries_to_input() {
+        let multi_part = MultiFileContent {
+            sources: sources(&[("src/A.sol", "pragma"), ("lib/Lib.sol", "pragma")]),
+            evm_version: None,
+            optimization: None,
+            optimization_runs: None,
+            optimizer_details: None,
+            via_ir: None,
+            remappings: vec![
+                Remapping::from_str("@openzeppelin/=lib/openzeppelin-contracts/")
+                    .expect("Valid remapping"),
+            ],
+            contract_libraries: Some(BTreeMap::from([
+                ("lib/Lib.sol:Lib".into(), "0x01".into()),
+                ("Other".into(), "0x02".into()),
+            ])),
+        };
+        let expected = r#"{"language":"Solidity","sources":{"lib/Lib.sol":{"content":"pragma"},"src/A.sol":{"content":"pragma"}},"settings":{"remappings":["@openzeppelin/=lib/openzeppelin-contracts/"],"optimizer":{"enabled":false},"outputSelection":{"*":{"":["ast"],"*":["abi","evm.bytecode","evm.deployedBytecode","evm.methodIdentifiers"]}},"libraries":{"lib/Lib.sol":{"Lib":"0x01","Other":"0x02"},"src/A.sol":{"Other":"0x02"}}}}"#;
+        test_to_input(multi_part, expected);
+    }
+
+    #[test]
+    fn settings_metadata_variants() {
+        let variants = |version: &str| {
+            settings_metadata(&Version::from_str(version).expect("Invalid compiler version"))
+        };
+
+        assert_eq!(
+            vec![(None, ExtraSettings::default())],
+            variants("v0.5.17+commit.d19bba13"),
+            "Metadata settings are not supported before 0.6.0"
+        );
+
+        let without_append_cbor = variants("v0.8.17+commit.8df45f5f");
+        assert_eq!(3, without_append_cbor.len());
+        assert!(
+            without_append_cbor
+                .iter()
+                .all(|(_, extra_settings)| extra_settings.append_cbor.is_none()),
+            "appendCBOR is not supported before 0.8.18"
+        );
+
+        let with_append_cbor = variants("v0.8.18+commit.87f61d96");
+        assert_eq!(without_append_cbor.len() + 1, with_append_cbor.len());
+        assert_eq!(
+            Some(&(
+                None,
+                ExtraSettings {
+                    append_cbor: Some(false)
+                }
+            )),
+            with_append_cbor.last(),
+            "Variant without appended metadata is missing"
+        );
+    }
 }
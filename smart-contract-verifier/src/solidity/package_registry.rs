@@ -0,0 +1,538 @@
+//! Local registry of well-known Solidity packages (e.g., OpenZeppelin or Solmate)
+//! used to satisfy imports which were not provided with the verification request.
+//!
+//! The registry is a directory with the following layout:
+//! ```text
+//! <root>/<package>/<version>/<package files>
+//! <root>/@<scope>/<package>/<version>/<package files>
+//! ```
+//! e.g., `<root>/@openzeppelin/contracts/4.8.0/token/ERC20/ERC20.sol` is used to resolve
+//! `import "@openzeppelin/contracts/token/ERC20/ERC20.sol";`.
+
+use anyhow::Context;
+use ethers_solc::remappings::Remapping;
+use semver::VersionReq;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::{Component, Path, PathBuf},
+};
+
+pub struct PackageRegistry {
+    root: PathBuf,
+    /// Available versions of each package sorted in descending order
+    packages: BTreeMap<String, Vec<semver::Version>>,
+}
+
+impl PackageRegistry {
+    /// Scans `root` directory for available packages and their versions.
+    pub fn load(root: &Path) -> Result<Self, anyhow::Error> {
+        let mut packages = BTreeMap::new();
+        for (name, path) in package_dirs(root)? {
+            let mut versions = Vec::new();
+            for entry in read_dir(&path)? {
+                let version = entry.file_name().to_string_lossy().to_string();
+                match semver::Version::parse(&version) {
+                    Ok(version) => versions.push(version),
+                    Err(err) => tracing::warn!(
+                        "skipping package {} version {}: invalid version: {}",
+                        name,
+                        version,
+                        err
+                    ),
+                }
+            }
+            versions.sort_by(|a, b| b.cmp(a));
+            if !versions.is_empty() {
+                packages.insert(name, versions);
+            }
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            packages,
+        })
+    }
+
+    pub fn packages(&self) -> &BTreeMap<String, Vec<semver::Version>> {
+        &self.packages
+    }
+
+    /// Finds imports which cannot be resolved using provided `sources` and tries
+    /// to satisfy them using the registry packages. Imports of added files are resolved
+    /// as well. For each package the latest version compatible with the compiler version
+    /// (according to the pragma of the imported file) is chosen, and the same version
+    /// is used for all files of that package.
+    ///
+    /// Returns only files which were added. Imports which could not be resolved are
+    /// skipped, so that they are reported by the compiler.
+    pub async fn resolve_missing_imports(
+        &self,
+        sources: &BTreeMap<PathBuf, String>,
+        remappings: &[Remapping],
+        compiler_version: &semver::Version,
+    ) -> Result<BTreeMap<PathBuf, String>, anyhow::Error> {
+        // ignore build and pre metadata
+        let compiler_version = semver::Version::new(
+            compiler_version.major,
+            compiler_version.minor,
+            compiler_version.patch,
+        );
+
+        let mut added = BTreeMap::new();
+        let mut chosen_versions = BTreeMap::new();
+        let mut unresolved = BTreeSet::new();
+
+        let mut queue: VecDeque<(PathBuf, String)> = sources
+            .iter()
+            .map(|(path, content)| (path.clone(), content.clone()))
+            .collect();
+        while let Some((path, content)) = queue.pop_front() {
+            for import in imports(&content) {
+                let import = resolve_import_path(&path, &import, remappings);
+                if sources.contains_key(&import)
+                    || added.contains_key(&import)
+                    || unresolved.contains(&import)
+                {
+                    continue;
+                }
+
+                match self
+                    .find_file(&import, &compiler_version, &mut chosen_versions)
+                    .await?
+                {
+                    Some(content) => {
+                        queue.push_back((import.clone(), content.clone()));
+                        added.insert(import, content);
+                    }
+                    None => {
+                        unresolved.insert(import);
+                    }
+                }
+            }
+        }
+
+        Ok(added)
+    }
+
+    async fn find_file(
+        &self,
+        import: &Path,
+        compiler_version: &semver::Version,
+        chosen_versions: &mut BTreeMap<String, semver::Version>,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let (name, file) = match self.split_package_name(import) {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+
+        if let Some(version) = chosen_versions.get(&name) {
+            return self.read_file(&name, version, &file).await;
+        }
+
+        for version in &self.packages[&name] {
+            let content = match self.read_file(&name, version, &file).await? {
+                Some(content) => content,
+                None => continue,
+            };
+            if is_compatible(&content, compiler_version) {
+                chosen_versions.insert(name, version.clone());
+                return Ok(Some(content));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Splits the import path onto the registry package name and the path inside the package.
+    fn split_package_name(&self, import: &Path) -> Option<(String, PathBuf)> {
+        let components: Vec<_> = import
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let name_length = if components.first()?.starts_with('@') {
+            2
+        } else {
+            1
+        };
+        if components.len() <= name_length {
+            return None;
+        }
+
+        let name = components[..name_length].join("/");
+        self.packages.contains_key(&name).then(|| {
+            let file = components[name_length..].iter().collect();
+            (name, file)
+        })
+    }
+
+    async fn read_file(
+        &self,
+        name: &str,
+        version: &semver::Version,
+        file: &Path,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let path = self.root.join(name).join(version.to_string()).join(file);
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context(format!("failed to read {}", path.to_string_lossy())),
+        }
+    }
+}
+
+fn read_dir(path: &Path) -> Result<Vec<std::fs::DirEntry>, anyhow::Error> {
+    let mut entries = Vec::new();
+    for entry in
+        std::fs::read_dir(path).context(format!("failed to read {}", path.to_string_lossy()))?
+    {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Returns package names with corresponding directories, taking scoped packages into account.
+fn package_dirs(root: &Path) -> Result<Vec<(String, PathBuf)>, anyhow::Error> {
+    let mut result = Vec::new();
+    for entry in read_dir(root)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('@') {
+            for package in read_dir(&entry.path())? {
+                let package_name = package.file_name().to_string_lossy().to_string();
+                result.push((format!("{}/{}", name, package_name), package.path()));
+            }
+        } else {
+            result.push((name, entry.path()));
+        }
+    }
+    Ok(result)
+}
+
+/// Resolves the import path the same way the compiler does: relative imports are
+/// resolved against the importing file directory, and all others are remapped.
+fn resolve_import_path(file: &Path, import: &str, remappings: &[Remapping]) -> PathBuf {
+    let path = if import.starts_with("./") || import.starts_with("../") {
+        file.parent().unwrap_or_else(|| Path::new("")).join(import)
+    } else {
+        let remapping = remappings
+            .iter()
+            .filter(|remapping| import.starts_with(&remapping.name))
+            .max_by_key(|remapping| remapping.name.len());
+        match remapping {
+            Some(remapping) => PathBuf::from(format!(
+                "{}{}",
+                remapping.path,
+                &import[remapping.name.len()..]
+            )),
+            None => PathBuf::from(import),
+        }
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Removes comments, so that commented out imports and pragmas are not taken into account.
+fn strip_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) => {
+                result.push(c);
+                if c == '\\' {
+                    result.extend(chars.next());
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                result.push(c);
+            }
+            (None, '/') if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        result.push(c);
+                        break;
+                    }
+                }
+            }
+            (None, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+                result.push(' ');
+            }
+            (None, c) => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns bodies of all statements starting with `keyword`, e.g., `import "A.sol"`.
+fn statements<'a>(content: &'a str, keyword: &'a str) -> impl Iterator<Item = &'a str> {
+    content
+        .match_indices(keyword)
+        .filter_map(move |(index, _)| {
+            let is_boundary =
+                |c: Option<char>| c.map_or(true, |c| !c.is_alphanumeric() && c != '_');
+            let before = content[..index].chars().last();
+            let rest = &content[index + keyword.len()..];
+            if !is_boundary(before) || !is_boundary(rest.chars().next()) {
+                return None;
+            }
+            rest.split_once(';').map(|(statement, _)| statement)
+        })
+}
+
+/// Returns paths of all files imported by the source.
+/// The path is the only string literal in any form of the import statement.
+fn imports(content: &str) -> Vec<String> {
+    let content = strip_comments(content);
+    statements(&content, "import")
+        .filter_map(|statement| {
+            let start = statement.find(|c| c == '"' || c == '\'')?;
+            let quote = statement[start..].chars().next()?;
+            let rest = &statement[start + 1..];
+            let end = rest.find(quote)?;
+            Some(rest[..end].to_string())
+        })
+        .collect()
+}
+
+/// Checks whether all `pragma solidity` requirements of the source are satisfied by the compiler.
+fn is_compatible(content: &str, compiler_version: &semver::Version) -> bool {
+    let content = strip_comments(content);
+    let mut requirements = statements(&content, "pragma")
+        .filter_map(|statement| statement.trim().strip_prefix("solidity"));
+    requirements.all(|requirement| {
+        match version_requirements(requirement) {
+            Some(alternatives) => alternatives.iter().any(|req| req.matches(compiler_version)),
+            // Do not reject files whose pragma we could not understand
+            None => true,
+        }
+    })
+}
+
+/// Converts Solidity version pragma into semver requirements. Solidity separates
+/// conjunct requirements by spaces, and bare versions mean exact match.
+fn version_requirements(pragma: &str) -> Option<Vec<VersionReq>> {
+    pragma
+        .split("||")
+        .map(|alternative| {
+            let mut comparators: Vec<String> = Vec::new();
+            let mut operator = String::new();
+            for token in alternative.split_whitespace() {
+                if token.chars().all(|c| "<>=^~".contains(c)) {
+                    operator.push_str(token);
+                    continue;
+                }
+                let token = format!("{}{}", operator, token);
+                operator.clear();
+                if token.starts_with(|c: char| c.is_ascii_digit()) {
+                    comparators.push(format!("={}", token));
+                } else {
+                    comparators.push(token);
+                }
+            }
+            VersionReq::parse(&comparators.join(", ")).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    fn write_file(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn sources(sources: &[(&str, &str)]) -> BTreeMap<PathBuf, String> {
+        sources
+            .iter()
+            .map(|(name, content)| (PathBuf::from(name), content.to_string()))
+            .collect()
+    }
+
+    fn version(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    const ERC20_V4: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import "./IERC20.sol";
+import {Context} from "../../utils/Context.sol";
+
+contract ERC20 is IERC20, Context {}
+"#;
+
+    fn registry() -> (tempfile::TempDir, PackageRegistry) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_file(
+            root,
+            "@openzeppelin/contracts/3.4.0/token/ERC20/ERC20.sol",
+            "pragma solidity >=0.6.0 <0.8.0;\ncontract ERC20 {}",
+        );
+        write_file(
+            root,
+            "@openzeppelin/contracts/4.8.0/token/ERC20/ERC20.sol",
+            ERC20_V4,
+        );
+        write_file(
+            root,
+            "@openzeppelin/contracts/4.8.0/token/ERC20/IERC20.sol",
+            "pragma solidity ^0.8.0;\ninterface IERC20 {}",
+        );
+        write_file(
+            root,
+            "@openzeppelin/contracts/4.8.0/utils/Context.sol",
+            "pragma solidity ^0.8.0;\nabstract contract Context {}",
+        );
+        write_file(
+            root,
+            "solmate/6.2.0/src/tokens/ERC721.sol",
+            "pragma solidity >=0.8.0;\nabstract contract ERC721 {}",
+        );
+        let registry = PackageRegistry::load(root).expect("Registry should be loaded");
+        (dir, registry)
+    }
+
+    #[test]
+    fn loads_packages() {
+        let (_dir, registry) = registry();
+        assert_eq!(
+            &BTreeMap::from([
+                (
+                    "@openzeppelin/contracts".to_string(),
+                    vec![version("4.8.0"), version("3.4.0")]
+                ),
+                ("solmate".to_string(), vec![version("6.2.0")]),
+            ]),
+            registry.packages()
+        );
+    }
+
+    #[tokio::test]
+    async fn resolves_missing_imports_with_dependencies() {
+        let (_dir, registry) = registry();
+        let sources = sources(&[(
+            "src/Token.sol",
+            r#"pragma solidity ^0.8.4;
+            import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+            // import "@openzeppelin/contracts/token/ERC20/ERC777.sol";
+            import {ERC721} from 'solmate/src/tokens/ERC721.sol';
+            import "./Local.sol";
+            contract Token is ERC20 {}"#,
+        )]);
+
+        let added = registry
+            .resolve_missing_imports(&sources, &[], &version("0.8.17"))
+            .await
+            .expect("Resolution failed");
+        assert_eq!(
+            vec![
+                "@openzeppelin/contracts/token/ERC20/ERC20.sol",
+                "@openzeppelin/contracts/token/ERC20/IERC20.sol",
+                "@openzeppelin/contracts/utils/Context.sol",
+                "solmate/src/tokens/ERC721.sol",
+            ],
+            added
+                .keys()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ERC20_V4,
+            added[&PathBuf::from("@openzeppelin/contracts/token/ERC20/ERC20.sol")]
+        );
+    }
+
+    #[tokio::test]
+    async fn picks_version_compatible_with_pragma() {
+        let (_dir, registry) = registry();
+        let sources = sources(&[(
+            "Token.sol",
+            "import \"@openzeppelin/contracts/token/ERC20/ERC20.sol\";",
+        )]);
+
+        let added = registry
+            .resolve_missing_imports(&sources, &[], &version("0.7.6"))
+            .await
+            .expect("Resolution failed");
+        assert_eq!(
+            Some(&"pragma solidity >=0.6.0 <0.8.0;\ncontract ERC20 {}".to_string()),
+            added.get(&PathBuf::from(
+                "@openzeppelin/contracts/token/ERC20/ERC20.sol"
+            ))
+        );
+
+        let added = registry
+            .resolve_missing_imports(&sources, &[], &version("0.5.17"))
+            .await
+            .expect("Resolution failed");
+        assert!(added.is_empty(), "No compatible version: {:?}", added);
+    }
+
+    #[tokio::test]
+    async fn skips_provided_and_remapped_imports() {
+        let (_dir, registry) = registry();
+        let sources = sources(&[
+            (
+                "Token.sol",
+                "import \"@openzeppelin/contracts/token/ERC20/ERC20.sol\";",
+            ),
+            ("lib/oz/token/ERC20/ERC20.sol", "contract ERC20 {}"),
+        ]);
+        let remappings = vec![Remapping::from_str("@openzeppelin/contracts/=lib/oz/").unwrap()];
+
+        let added = registry
+            .resolve_missing_imports(&sources, &remappings, &version("0.8.17"))
+            .await
+            .expect("Resolution failed");
+        assert!(added.is_empty(), "Nothing should be added: {:?}", added);
+    }
+
+    #[test]
+    fn converts_version_pragmas() {
+        let matches = |pragma: &str, version: &str| {
+            version_requirements(pragma)
+                .expect("Invalid pragma")
+                .iter()
+                .any(|req| req.matches(&semver::Version::parse(version).unwrap()))
+        };
+
+        assert!(matches("^0.8.0", "0.8.17"));
+        assert!(!matches("^0.8.0", "0.7.6"));
+        assert!(matches(">=0.6.0 <0.8.0", "0.7.6"));
+        assert!(!matches(">=0.6.0 <0.8.0", "0.8.0"));
+        assert!(matches(">= 0.6.0  < 0.8.0", "0.6.12"));
+        assert!(matches("0.8.17", "0.8.17"));
+        assert!(!matches("0.8.17", "0.8.18"));
+        assert!(matches("^0.4.24 || ^0.8.0", "0.8.1"));
+    }
+}
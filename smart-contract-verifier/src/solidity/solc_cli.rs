@@ -31,6 +31,7 @@ mod types {
     use ethers_solc::{
         artifacts::{Contract, Libraries},
         error::SolcError,
+        remappings::Remapping,
         CompilerInput, CompilerOutput,
     };
     use serde::{Deserialize, Serialize};
@@ -48,19 +49,39 @@ mod types {
         pub libs: BTreeMap<String, String>,
     }
 
-    fn merge_libs(libraries: Libraries) -> BTreeMap<String, String> {
-        let mut result = BTreeMap::new();
-        libraries
-            .libs
+    /// Libraries are passed to the compiler by their bare names, as the files are
+    /// compiled from a temporary directory, so that file qualified names would not match.
+    /// The linker falls back to bare names matching if no qualified one was provided.
+    ///
+    /// Returns an error if libraries with the same name in different files have
+    /// different addresses, as they could not be told apart by the linker.
+    fn merge_libs(libraries: Libraries) -> Result<BTreeMap<String, String>, SolcError> {
+        let mut result: BTreeMap<String, (String, String)> = BTreeMap::new();
+        for (path, libs) in libraries.libs {
+            let path = path.to_string_lossy().to_string();
+            for (name, address) in libs {
+                if let Some((merged_path, merged_address)) = result.get(&name) {
+                    if merged_address != &address {
+                        return Err(SolcError::Message(format!(
+                            "libraries {}:{} and {}:{} have the same name but different addresses, \
+                            which is not supported by compilers without standard json input",
+                            merged_path, name, path, name
+                        )));
+                    }
+                }
+                result.entry(name).or_insert((path.clone(), address));
+            }
+        }
+        Ok(result
             .into_iter()
-            .for_each(|(_, libs)| result.extend(libs));
-        result
+            .map(|(name, (_path, address))| (name, address))
+            .collect())
     }
 
     impl TryFrom<&CompilerInput> for InputArgs {
         type Error = SolcError;
         fn try_from(input: &CompilerInput) -> Result<Self, Self::Error> {
-            let libs = merge_libs(input.settings.libraries.clone());
+            let libs = merge_libs(input.settings.libraries.clone())?;
             Ok(InputArgs {
                 optimize: input.settings.optimizer.enabled.unwrap_or(false),
                 optimize_runs: input.settings.optimizer.runs,
@@ -135,6 +156,18 @@ mod types {
             }
         }
 
+        /// Converts remappings into command line arguments,
+        /// resolving their targets relative to the directory with input files.
+        pub fn remappings(&self, remappings: &[Remapping]) -> Vec<String> {
+            remappings
+                .iter()
+                .map(|remapping| {
+                    let path = self.files_dir.path().join(&remapping.path);
+                    format!("{}={}", remapping.name, path.to_string_lossy())
+                })
+                .collect()
+        }
+
         pub fn build(&self) -> Result<&Vec<PathBuf>, SolcError> {
             self.files_dir
                 .path()
@@ -241,10 +274,15 @@ pub async fn compile_using_cli(
     input: &CompilerInput,
 ) -> Result<CompilerOutput, SolcError> {
     let output = {
-        let input_args = types::InputArgs::try_from(input)?;
+        let input_args = match types::InputArgs::try_from(input) {
+            Ok(input_args) => input_args,
+            // Invalid libraries are reported the way the compiler reports invalid input
+            Err(err) => return Ok(compiler_output_with_error(err.to_string())),
+        };
         let input_files = types::InputFiles::try_from_compiler_input(input).await?;
         Command::new(solc)
             .args(input_args.build())
+            .args(input_files.remappings(&input.settings.remappings))
             .args(input_files.build()?)
             .stderr(Stdio::piped())
             .stdout(Stdio::piped())
@@ -258,21 +296,26 @@ pub async fn compile_using_cli(
         let output_json: types::OutputJson = serde_json::from_slice(output.stdout.as_slice())?;
         CompilerOutput::try_from(output_json)?
     } else {
-        CompilerOutput {
-            errors: vec![compiler_error(stderr)],
-            sources: BTreeMap::new(),
-            contracts: BTreeMap::new(),
-        }
+        compiler_output_with_error(stderr)
     };
     Ok(compiler_output)
 }
 
+fn compiler_output_with_error(message: String) -> CompilerOutput {
+    CompilerOutput {
+        errors: vec![compiler_error(message)],
+        sources: BTreeMap::new(),
+        contracts: BTreeMap::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::compiler::{Fetcher, ListFetcher, Version};
     use ethers_solc::{
         artifacts::{Settings, Source},
+        remappings::Remapping,
         Artifact,
     };
     use hex::ToHex;
@@ -409,6 +452,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn colliding_libraries_are_rejected() {
+        let mut input: CompilerInput = serde_json::from_str(DEFAULT_COMPILER_INPUT).unwrap();
+        let libraries = |address: &str| {
+            BTreeMap::from([
+                (
+                    "a.sol".into(),
+                    BTreeMap::from([("MyLib".into(), address.into())]),
+                ),
+                (
+                    "b.sol".into(),
+                    BTreeMap::from([(
+                        "MyLib".into(),
+                        "0x1234567890123456789012345678901234567890".into(),
+                    )]),
+                ),
+            ])
+        };
+
+        input.settings.libraries.libs = libraries("0x1234567890123456789012345678901234567890");
+        let input_args = types::InputArgs::try_from(&input)
+            .expect("libraries with the same address should be merged");
+        assert_eq!(
+            input_args.libs,
+            BTreeMap::from([(
+                "MyLib".to_string(),
+                "0x1234567890123456789012345678901234567890".to_string()
+            )])
+        );
+
+        input.settings.libraries.libs = libraries("0x0987654321098765432109876543210987654321");
+        let err = types::InputArgs::try_from(&input)
+            .expect_err("libraries with different addresses should be rejected");
+        assert!(
+            err.to_string().contains("a.sol:MyLib and b.sol:MyLib"),
+            "invalid error: {}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn correct_input_files() {
         let input: CompilerInput = serde_json::from_str(DEFAULT_COMPILER_INPUT).unwrap();
@@ -427,6 +510,24 @@ mod tests {
         assert_eq!(string_args, &expected_files);
     }
 
+    #[tokio::test]
+    async fn correct_input_remappings() {
+        let input: CompilerInput = serde_json::from_str(DEFAULT_COMPILER_INPUT).unwrap();
+        let remappings = vec![Remapping::from_str("@lib/=lib/").expect("Valid remapping")];
+
+        let input_files = types::InputFiles::try_from_compiler_input(&input)
+            .await
+            .expect("failed to convert files");
+        let expected_remapping = format!(
+            "@lib/={}",
+            input_files.files_dir.path().join("lib/").to_string_lossy()
+        );
+        assert_eq!(
+            input_files.remappings(&remappings),
+            vec![expected_remapping]
+        );
+    }
+
     #[test]
     fn correct_output() {
         let output_json: types::OutputJson = serde_json::from_str(DEFAULT_COMPILER_OUTPUT).unwrap();
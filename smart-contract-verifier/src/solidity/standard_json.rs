@@ -1,34 +1,37 @@
 use super::compiler::SolidityCompiler;
 use crate::{
-    compiler::{Compilers, Version},
-    verifier::{ContractVerifier, Error, Success},
+    compiler::{Compilers, ExtraSettings, Version},
+    verifier::{ArtifactKind, ContractName, ContractVerifier, Deployment, Error, Success},
 };
 use bytes::Bytes;
-use ethers_solc::{artifacts::output_selection::OutputSelection, CompilerInput};
-use std::sync::Arc;
+use ethers_solc::CompilerInput;
+use std::{collections::BTreeSet, sync::Arc};
 
 pub struct VerificationRequest {
     pub deployed_bytecode: Bytes,
     pub creation_bytecode: Bytes,
     pub compiler_version: Version,
+    /// If specified, only that contract is compared with the remote bytecode
+    pub contract_name: Option<ContractName>,
+    /// If the match is partial, try to get the full one by normalizing the sources
+    pub normalize_sources: bool,
+    /// Compilation artifacts to be returned with the successful result
+    pub artifacts: BTreeSet<ArtifactKind>,
 
     pub content: StandardJsonContent,
 }
 
 pub struct StandardJsonContent {
     pub input: CompilerInput,
+    /// Settings specified in the standard json input, but not supported by [`CompilerInput`]
+    pub extra_settings: ExtraSettings,
 }
 
 impl From<StandardJsonContent> for CompilerInput {
     fn from(content: StandardJsonContent) -> Self {
-        let mut input = content.input;
-
-        // always overwrite output selection as it customizes what compiler outputs and
-        // is not what is returned to the user, but only used internally by our service
-        let output_selection = OutputSelection::default_output_selection();
-        input.settings.output_selection = output_selection;
-
-        input
+        // Output selection is kept as specified by the requester, so that it is returned
+        // in the result. The verifier replaces it with its own one for the compilation
+        content.input
     }
 }
 
@@ -36,12 +39,39 @@ pub async fn verify(
     compilers: Arc<Compilers<SolidityCompiler>>,
     request: VerificationRequest,
 ) -> Result<Success, Error> {
+    let deployment = Deployment {
+        creation_tx_input: request.creation_bytecode.clone(),
+        deployed_bytecode: request.deployed_bytecode.clone(),
+    };
+    let mut results = verify_deployments(compilers, request, vec![deployment]).await?;
+    results.remove(0)
+}
+
+/// Verifies the input of the `request` against each of the `deployments`
+/// (instead of the bytecodes specified in the request), compiling it once.
+///
+/// Returns an error if the input could not be compiled. Otherwise, returns
+/// the results of the verifications in the order of `deployments`.
+pub async fn verify_deployments(
+    compilers: Arc<Compilers<SolidityCompiler>>,
+    request: VerificationRequest,
+    deployments: Vec<Deployment>,
+) -> Result<Vec<Result<Success, Error>>, Error> {
+    let extra_settings = request.content.extra_settings.clone();
     let compiler_input = CompilerInput::from(request.content);
-    let verifier = ContractVerifier::new(
+    let verifier = ContractVerifier::for_deployments(
         compilers,
         &request.compiler_version,
-        request.creation_bytecode,
-        request.deployed_bytecode,
-    )?;
-    verifier.verify(&compiler_input).await
+        deployments,
+        request.contract_name,
+    )?
+    .with_artifacts(request.artifacts);
+    let deployments: Vec<_> = (0..verifier.deployments_len()).collect();
+    let results = verifier
+        .verify_deployments(&compiler_input, &extra_settings, &deployments)
+        .await?;
+    if request.normalize_sources {
+        return Ok(verifier.verify_deployments_normalized(results).await);
+    }
+    Ok(results)
 }
@@ -0,0 +1,427 @@
+use super::{
+    api_client::{RpcResult, TraceApiClient},
+    types::{find_creation_input_in_traces, Error},
+};
+use crate::verifier::Deployment;
+use anyhow::anyhow;
+use bytes::Bytes;
+use ethers_core::types::{Address, BlockNumber, H256, U64};
+use std::sync::Arc;
+
+pub struct CreationInputRequest {
+    /// Transaction inside which the contract has been created
+    pub transaction_hash: H256,
+    /// Address of the created contract
+    pub address: Address,
+}
+
+/// Retrieves the init code (with encoded constructor arguments) the contract has been
+/// created with. Is intended for contracts created via CREATE/CREATE2 from other contracts,
+/// which have no creation transaction input of their own.
+///
+/// Geth-style `debug_traceTransaction` is tried first, and parity-style `trace_transaction`
+/// is used if the former fails (e.g., is not supported by the node or misses `callTracer`).
+pub async fn creation_input(
+    trace_client: Arc<TraceApiClient>,
+    request: CreationInputRequest,
+) -> Result<Bytes, Error> {
+    let debug_error = match trace_client
+        .debug_trace_transaction(&request.transaction_hash)
+        .await
+    {
+        Ok(RpcResult::Ok(call_frame)) => {
+            return call_frame
+                .find_creation_input(&request.address)
+                .map(|input| input.0.clone())
+                .ok_or(Error::CreationNotFound(request.address))
+        }
+        Ok(RpcResult::MethodNotFound) => None,
+        Err(err) => Some(anyhow!(
+            "error while making debug_traceTransaction request: {}",
+            err
+        )),
+    };
+
+    let traces = match trace_client
+        .trace_transaction(&request.transaction_hash)
+        .await
+    {
+        Ok(RpcResult::Ok(traces)) => traces,
+        Ok(RpcResult::MethodNotFound) => {
+            return Err(debug_error.map_or(Error::TracingNotSupported, Error::Internal))
+        }
+        Err(err) => {
+            let err = anyhow!("error while making trace_transaction request: {}", err);
+            return Err(Error::Internal(match debug_error {
+                Some(debug_error) => anyhow!("{}; {}", debug_error, err),
+                None => err,
+            }));
+        }
+    };
+    find_creation_input_in_traces(&traces, &request.address)
+        .map(|input| input.0.clone())
+        .ok_or(Error::CreationNotFound(request.address))
+}
+
+pub struct DeploymentRequest {
+    /// Address the contract is currently deployed at
+    pub address: Address,
+}
+
+/// Retrieves the bytecodes of the contract deployed at the address, so that
+/// the contract may be verified by the address only (e.g., via Etherscan-compatible API).
+///
+/// The block the contract has been created in is found via binary search over the code
+/// at the address, which requires the node to keep the historical state (i.e., to be an archive one).
+/// The creation is then searched for in the traces of that block, so that contracts
+/// created from other contracts are supported as well.
+///
+/// The search assumes the code is not removed once deployed. Contracts destroyed and re-created
+/// at the same address (via CREATE2) may be found at any of their creations, so the code
+/// created in the found block is checked to be the latest one.
+pub async fn deployment(
+    trace_client: Arc<TraceApiClient>,
+    request: DeploymentRequest,
+) -> Result<Deployment, Error> {
+    let address = request.address;
+    let deployed_bytecode = required(
+        "eth_getCode",
+        trace_client.code(&address, BlockNumber::Latest).await,
+    )?;
+    if deployed_bytecode.0.is_empty() {
+        return Err(Error::ContractNotFound(address));
+    }
+    let latest_block = required("eth_blockNumber", trace_client.block_number().await)?;
+
+    // The code is not empty at the latest block, so the search always succeeds
+    let (mut low, mut high) = (U64::zero(), latest_block);
+    let mut created_bytecode = None;
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let code = required(
+            "eth_getCode",
+            trace_client
+                .code(&address, BlockNumber::Number(middle))
+                .await,
+        )?;
+        if code.0.is_empty() {
+            low = middle + 1;
+        } else {
+            high = middle;
+            created_bytecode = Some(code);
+        }
+    }
+    // If the upper bound has not been moved, the contract was created in `latest_block`
+    let created_bytecode = match created_bytecode {
+        Some(code) => code,
+        None => required(
+            "eth_getCode",
+            trace_client.code(&address, BlockNumber::Number(low)).await,
+        )?,
+    };
+    if created_bytecode != deployed_bytecode {
+        return Err(Error::CodeChanged(address));
+    }
+
+    let creation_tx_input = block_creation_input(&trace_client, &address, low).await?;
+    Ok(Deployment {
+        creation_tx_input,
+        deployed_bytecode: deployed_bytecode.0,
+    })
+}
+
+/// Same as [`creation_input`], but searches through all transactions of the `block`.
+async fn block_creation_input(
+    trace_client: &TraceApiClient,
+    address: &Address,
+    block: U64,
+) -> Result<Bytes, Error> {
+    let debug_error = match trace_client.debug_trace_block(block).await {
+        Ok(RpcResult::Ok(transactions)) => {
+            return transactions
+                .iter()
+                .find_map(|transaction| transaction.result.find_creation_input(address))
+                .map(|input| input.0.clone())
+                .ok_or(Error::CreationNotFound(*address))
+        }
+        Ok(RpcResult::MethodNotFound) => None,
+        Err(err) => Some(anyhow!(
+            "error while making debug_traceBlockByNumber request: {}",
+            err
+        )),
+    };
+
+    let traces = match trace_client.trace_block(block).await {
+        Ok(RpcResult::Ok(traces)) => traces,
+        Ok(RpcResult::MethodNotFound) => {
+            return Err(debug_error.map_or(Error::TracingNotSupported, Error::Internal))
+        }
+        Err(err) => {
+            let err = anyhow!("error while making trace_block request: {}", err);
+            return Err(Error::Internal(match debug_error {
+                Some(debug_error) => anyhow!("{}; {}", debug_error, err),
+                None => err,
+            }));
+        }
+    };
+    find_creation_input_in_traces(&traces, address)
+        .map(|input| input.0.clone())
+        .ok_or(Error::CreationNotFound(*address))
+}
+
+/// Unwraps the result of the method every node is expected to support.
+fn required<T>(method: &str, result: Result<RpcResult<T>, anyhow::Error>) -> Result<T, Error> {
+    match result {
+        Ok(RpcResult::Ok(value)) => Ok(value),
+        Ok(RpcResult::MethodNotFound) => Err(Error::Internal(anyhow!(
+            "node does not support `{}`",
+            method
+        ))),
+        Err(err) => Err(Error::Internal(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use std::{num::NonZeroU32, str::FromStr};
+    use wiremock::{
+        matchers::{body_partial_json, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    const TRANSACTION_HASH: &str =
+        "0x0000000000000000000000000000000000000000000000000000000000000002";
+    const CREATED: &str = "0x3333333333333333333333333333333333333333";
+
+    fn trace_client(server: &MockServer, request_attempts: u32) -> Arc<TraceApiClient> {
+        let client = TraceApiClient::new(
+            server.uri().parse().unwrap(),
+            10,
+            NonZeroU32::new(request_attempts).unwrap(),
+        )
+        .expect("client should be built");
+        Arc::new(client)
+    }
+
+    fn creation_input_request() -> CreationInputRequest {
+        CreationInputRequest {
+            transaction_hash: H256::from_str(TRANSACTION_HASH).unwrap(),
+            address: Address::from_str(CREATED).unwrap(),
+        }
+    }
+
+    async fn mock_rpc(server: &MockServer, rpc_method: &str, response: Value) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    fn rpc_result(result: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+    }
+
+    fn rpc_error(code: i64) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": code, "message": "error" } })
+    }
+
+    fn call_trace() -> Value {
+        rpc_result(json!({
+            "type": "CALL",
+            "from": "0x1111111111111111111111111111111111111111",
+            "to": "0x2222222222222222222222222222222222222222",
+            "input": "0x12345678",
+            "calls": [{
+                "type": "CREATE",
+                "from": "0x2222222222222222222222222222222222222222",
+                "to": CREATED,
+                "input": "0x6080aaaa"
+            }]
+        }))
+    }
+
+    fn parity_traces() -> Value {
+        rpc_result(json!([{
+            "action": {
+                "from": "0x2222222222222222222222222222222222222222",
+                "gas": "0x1000",
+                "init": "0x6080bbbb",
+                "value": "0x0"
+            },
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "blockNumber": 1,
+            "result": { "address": CREATED, "code": "0x6080", "gasUsed": "0x100" },
+            "subtraces": 0,
+            "traceAddress": [],
+            "transactionHash": TRANSACTION_HASH,
+            "transactionPosition": 0,
+            "type": "create"
+        }]))
+    }
+
+    #[tokio::test]
+    async fn creation_input_is_found_in_call_trace() {
+        let server = MockServer::start().await;
+        mock_rpc(&server, "debug_traceTransaction", call_trace()).await;
+
+        let input = creation_input(trace_client(&server, 1), creation_input_request())
+            .await
+            .expect("creation input should be found");
+        assert_eq!(Bytes::from_static(&[0x60, 0x80, 0xaa, 0xaa]), input);
+    }
+
+    #[tokio::test]
+    async fn parity_traces_are_used_if_debug_tracing_fails() {
+        for debug_error in [-32601, -32000] {
+            let server = MockServer::start().await;
+            mock_rpc(&server, "debug_traceTransaction", rpc_error(debug_error)).await;
+            mock_rpc(&server, "trace_transaction", parity_traces()).await;
+
+            let input = creation_input(trace_client(&server, 1), creation_input_request())
+                .await
+                .expect("creation input should be found");
+            assert_eq!(
+                Bytes::from_static(&[0x60, 0x80, 0xbb, 0xbb]),
+                input,
+                "Invalid creation input when debug tracing fails with {}",
+                debug_error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn tracing_errors_are_reported() {
+        let server = MockServer::start().await;
+        mock_rpc(&server, "debug_traceTransaction", rpc_error(-32601)).await;
+        mock_rpc(&server, "trace_transaction", rpc_error(-32601)).await;
+        let result = creation_input(trace_client(&server, 1), creation_input_request()).await;
+        assert!(
+            matches!(result, Err(Error::TracingNotSupported)),
+            "Expected tracing to be unsupported, got {:?}",
+            result
+        );
+
+        let server = MockServer::start().await;
+        mock_rpc(&server, "debug_traceTransaction", rpc_error(-32000)).await;
+        mock_rpc(&server, "trace_transaction", rpc_error(-32601)).await;
+        let result = creation_input(trace_client(&server, 1), creation_input_request()).await;
+        assert!(
+            matches!(result, Err(Error::Internal(_))),
+            "Expected debug tracing error, got {:?}",
+            result
+        );
+    }
+
+    async fn mock_code(server: &MockServer, block: &str, code: &str) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                json!({ "method": "eth_getCode", "params": [CREATED, block] }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(rpc_result(json!(code))))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn deployment_is_found_in_creation_block() {
+        let server = MockServer::start().await;
+        mock_code(&server, "latest", "0x6080").await;
+        for (block, code) in [
+            ("0x0", "0x"),
+            ("0x1", "0x"),
+            ("0x2", "0x6080"),
+            ("0x3", "0x6080"),
+        ] {
+            mock_code(&server, block, code).await;
+        }
+        mock_rpc(&server, "eth_blockNumber", rpc_result(json!("0x4"))).await;
+        let block_traces = rpc_result(json!([
+            { "result": { "type": "CALL", "to": CREATED, "input": "0x" } },
+            { "result": call_trace()["result"] },
+        ]));
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "debug_traceBlockByNumber",
+                "params": ["0x2", { "tracer": "callTracer" }],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(block_traces))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = DeploymentRequest {
+            address: Address::from_str(CREATED).unwrap(),
+        };
+        let deployment = deployment(trace_client(&server, 1), request)
+            .await
+            .expect("deployment should be found");
+        assert_eq!(
+            Deployment {
+                creation_tx_input: Bytes::from_static(&[0x60, 0x80, 0xaa, 0xaa]),
+                deployed_bytecode: Bytes::from_static(&[0x60, 0x80]),
+            },
+            deployment
+        );
+    }
+
+    #[tokio::test]
+    async fn deployment_with_changed_code_is_rejected() {
+        let server = MockServer::start().await;
+        mock_code(&server, "latest", "0x6081").await;
+        for (block, code) in [
+            ("0x0", "0x"),
+            ("0x1", "0x"),
+            ("0x2", "0x6080"),
+            ("0x3", "0x6080"),
+        ] {
+            mock_code(&server, block, code).await;
+        }
+        mock_rpc(&server, "eth_blockNumber", rpc_result(json!("0x4"))).await;
+
+        let request = DeploymentRequest {
+            address: Address::from_str(CREATED).unwrap(),
+        };
+        let result = deployment(trace_client(&server, 1), request).await;
+        assert!(
+            matches!(result, Err(Error::CodeChanged(_))),
+            "Expected code to be changed, got {:?}",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn deployment_of_missing_contract_is_not_found() {
+        let server = MockServer::start().await;
+        mock_code(&server, "latest", "0x").await;
+
+        let request = DeploymentRequest {
+            address: Address::from_str(CREATED).unwrap(),
+        };
+        let result = deployment(trace_client(&server, 1), request).await;
+        assert!(
+            matches!(result, Err(Error::ContractNotFound(_))),
+            "Expected contract to be not found, got {:?}",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn requests_are_made_given_number_of_attempts() {
+        let server = MockServer::start().await;
+        // Both debug and parity tracing requests are made twice
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        creation_input(trace_client(&server, 2), creation_input_request())
+            .await
+            .expect_err("request should fail");
+    }
+}
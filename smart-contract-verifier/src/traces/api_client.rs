@@ -0,0 +1,149 @@
+use super::types::{CallFrame, TransactionCallFrame};
+use crate::DisplayBytes;
+use ethers_core::types::{Address, BlockNumber, Trace, H256, U64};
+use reqwest::Url;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use std::{num::NonZeroU32, time::Duration};
+
+/// Error code returned by JSON-RPC nodes when requested method does not exist
+/// (https://www.jsonrpc.org/specification#error_object).
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+/// Result of a single JSON-RPC method call.
+pub(super) enum RpcResult<T> {
+    Ok(T),
+    MethodNotFound,
+}
+
+pub struct TraceApiClient {
+    rpc_url: Url,
+    reqwest_client: ClientWithMiddleware,
+}
+
+impl TraceApiClient {
+    pub fn new(
+        rpc_url: Url,
+        request_timeout: u64,
+        request_attempts: NonZeroU32,
+    ) -> Result<Self, reqwest::Error> {
+        // The first attempt is not a retry
+        let retry_policy =
+            ExponentialBackoff::builder().build_with_max_retries(request_attempts.get() - 1);
+        let reqwest_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(request_timeout))
+            .build()?;
+        let reqwest_client = ClientBuilder::new(reqwest_client)
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+
+        Ok(Self {
+            rpc_url,
+            reqwest_client,
+        })
+    }
+
+    /// Calls geth-style `debug_traceTransaction` with built-in `callTracer`.
+    pub(super) async fn debug_trace_transaction(
+        &self,
+        transaction_hash: &H256,
+    ) -> Result<RpcResult<CallFrame>, anyhow::Error> {
+        self.request(
+            "debug_traceTransaction",
+            json!([transaction_hash, { "tracer": "callTracer" }]),
+        )
+        .await
+    }
+
+    /// Calls parity-style (OpenEthereum, Nethermind, Erigon) `trace_transaction`.
+    pub(super) async fn trace_transaction(
+        &self,
+        transaction_hash: &H256,
+    ) -> Result<RpcResult<Vec<Trace>>, anyhow::Error> {
+        self.request("trace_transaction", json!([transaction_hash]))
+            .await
+    }
+
+    /// Calls geth-style `debug_traceBlockByNumber` with built-in `callTracer`.
+    pub(super) async fn debug_trace_block(
+        &self,
+        block: U64,
+    ) -> Result<RpcResult<Vec<TransactionCallFrame>>, anyhow::Error> {
+        self.request(
+            "debug_traceBlockByNumber",
+            json!([block, { "tracer": "callTracer" }]),
+        )
+        .await
+    }
+
+    /// Calls parity-style (OpenEthereum, Nethermind, Erigon) `trace_block`.
+    pub(super) async fn trace_block(
+        &self,
+        block: U64,
+    ) -> Result<RpcResult<Vec<Trace>>, anyhow::Error> {
+        self.request("trace_block", json!([block])).await
+    }
+
+    pub(super) async fn block_number(&self) -> Result<RpcResult<U64>, anyhow::Error> {
+        self.request("eth_blockNumber", json!([])).await
+    }
+
+    /// Returns the code deployed at `address` as of the end of the `block`.
+    pub(super) async fn code(
+        &self,
+        address: &Address,
+        block: BlockNumber,
+    ) -> Result<RpcResult<DisplayBytes>, anyhow::Error> {
+        self.request("eth_getCode", json!([address, block])).await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<RpcResult<T>, anyhow::Error> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: JsonRpcResponse<T> = self
+            .reqwest_client
+            .post(self.rpc_url.as_str())
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await
+            .map_err(anyhow::Error::msg)?;
+
+        match (response.result, response.error) {
+            (_, Some(error)) if error.code == METHOD_NOT_FOUND_CODE => {
+                Ok(RpcResult::MethodNotFound)
+            }
+            (_, Some(error)) => Err(anyhow::anyhow!(
+                "{} returned an error: {} (code {})",
+                method,
+                error.message,
+                error.code
+            )),
+            (Some(result), None) => Ok(RpcResult::Ok(result)),
+            (None, None) => Err(anyhow::anyhow!("{} returned an empty result", method)),
+        }
+    }
+}
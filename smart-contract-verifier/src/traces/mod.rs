@@ -0,0 +1,7 @@
+mod api_client;
+mod types;
+
+pub mod api;
+
+pub use api_client::TraceApiClient;
+pub use types::{CallFrame, Error};
@@ -0,0 +1,223 @@
+use crate::DisplayBytes;
+use ethers_core::types::{Action, Address, Res, Trace};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0:#}")]
+    Internal(anyhow::Error),
+    #[error("node does not support neither debug (geth-style) nor parity-style tracing")]
+    TracingNotSupported,
+    #[error("creation of contract {0:?} was not found in the traces")]
+    CreationNotFound(Address),
+    #[error("no contract is deployed at {0:?}")]
+    ContractNotFound(Address),
+    #[error("contract {0:?} has been re-created with a different code")]
+    CodeChanged(Address),
+}
+
+/// Single frame returned by geth `callTracer`
+/// (https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers#call-tracer).
+///
+/// Only fields required to find contract creations are parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub input: DisplayBytes,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    fn is_create(&self) -> bool {
+        matches!(self.typ.as_str(), "CREATE" | "CREATE2")
+    }
+
+    /// Searches for the frame which successfully created the contract at `address`
+    /// and returns its input (init code with encoded constructor arguments).
+    pub fn find_creation_input(&self, address: &Address) -> Option<&DisplayBytes> {
+        if self.error.is_some() {
+            // Everything created inside reverted frame has been reverted as well
+            return None;
+        }
+        if self.is_create() && self.to.as_ref() == Some(address) {
+            return Some(&self.input);
+        }
+        self.calls
+            .iter()
+            .find_map(|call| call.find_creation_input(address))
+    }
+}
+
+/// Single item of `debug_traceBlockByNumber` result.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransactionCallFrame {
+    pub result: CallFrame,
+}
+
+/// Searches through the parity-style `trace_transaction` results for the trace
+/// which successfully created the contract at `address` and returns its init code.
+pub fn find_creation_input_in_traces<'a>(
+    traces: &'a [Trace],
+    address: &Address,
+) -> Option<&'a DisplayBytes> {
+    // Subtraces of a failed trace are reverted too, even if they do not contain an error themselves
+    let failed_trace_addresses: Vec<&Vec<usize>> = traces
+        .iter()
+        .filter(|trace| trace.error.is_some())
+        .map(|trace| &trace.trace_address)
+        .collect();
+    let is_reverted = |trace: &Trace| {
+        failed_trace_addresses
+            .iter()
+            .any(|failed| trace.trace_address.starts_with(failed))
+    };
+
+    traces
+        .iter()
+        .filter(|trace| !is_reverted(trace))
+        .find_map(|trace| match (&trace.action, &trace.result) {
+            (Action::Create(create), Some(Res::Create(result))) if &result.address == address => {
+                Some(&create.init)
+            }
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn address(address: &str) -> Address {
+        Address::from_str(address).expect("Invalid address")
+    }
+
+    fn bytes(bytes: &str) -> DisplayBytes {
+        DisplayBytes::from_str(bytes).expect("Invalid bytes")
+    }
+
+    const CALL_TRACE: &str = r#"{
+        "type": "CALL",
+        "from": "0x1111111111111111111111111111111111111111",
+        "to": "0x2222222222222222222222222222222222222222",
+        "input": "0x12345678",
+        "calls": [
+            {
+                "type": "CREATE",
+                "from": "0x2222222222222222222222222222222222222222",
+                "to": "0x3333333333333333333333333333333333333333",
+                "input": "0x6080aaaa",
+                "error": "execution reverted"
+            },
+            {
+                "type": "CALL",
+                "from": "0x2222222222222222222222222222222222222222",
+                "to": "0x4444444444444444444444444444444444444444",
+                "input": "0x",
+                "calls": [
+                    {
+                        "type": "CREATE2",
+                        "from": "0x4444444444444444444444444444444444444444",
+                        "to": "0x5555555555555555555555555555555555555555",
+                        "input": "0x6080bbbb"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn finds_nested_creation_in_call_trace() {
+        let frame: CallFrame = serde_json::from_str(CALL_TRACE).expect("Valid call trace");
+
+        assert_eq!(
+            Some(&bytes("0x6080bbbb")),
+            frame.find_creation_input(&address("0x5555555555555555555555555555555555555555")),
+            "Invalid creation input"
+        );
+    }
+
+    #[test]
+    fn skips_reverted_and_missing_creations_in_call_trace() {
+        let frame: CallFrame = serde_json::from_str(CALL_TRACE).expect("Valid call trace");
+
+        assert_eq!(
+            None,
+            frame.find_creation_input(&address("0x3333333333333333333333333333333333333333")),
+            "Reverted creation should be skipped"
+        );
+        assert_eq!(
+            None,
+            frame.find_creation_input(&address("0x4444444444444444444444444444444444444444")),
+            "Not a creation frame"
+        );
+    }
+
+    #[test]
+    fn finds_creation_in_parity_traces() {
+        let traces = r#"[
+            {
+                "action": {
+                    "callType": "call",
+                    "from": "0x1111111111111111111111111111111111111111",
+                    "gas": "0x1000",
+                    "input": "0x12345678",
+                    "to": "0x2222222222222222222222222222222222222222",
+                    "value": "0x0"
+                },
+                "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "blockNumber": 1,
+                "result": { "gasUsed": "0x100", "output": "0x" },
+                "subtraces": 1,
+                "traceAddress": [],
+                "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                "transactionPosition": 0,
+                "type": "call"
+            },
+            {
+                "action": {
+                    "from": "0x2222222222222222222222222222222222222222",
+                    "gas": "0x1000",
+                    "init": "0x6080cccc",
+                    "value": "0x0"
+                },
+                "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "blockNumber": 1,
+                "result": {
+                    "address": "0x6666666666666666666666666666666666666666",
+                    "code": "0x6080",
+                    "gasUsed": "0x100"
+                },
+                "subtraces": 0,
+                "traceAddress": [0],
+                "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                "transactionPosition": 0,
+                "type": "create"
+            }
+        ]"#;
+        let traces: Vec<Trace> = serde_json::from_str(traces).expect("Valid parity traces");
+
+        assert_eq!(
+            Some(&bytes("0x6080cccc")),
+            find_creation_input_in_traces(
+                &traces,
+                &address("0x6666666666666666666666666666666666666666")
+            ),
+            "Invalid creation input"
+        );
+        assert_eq!(
+            None,
+            find_creation_input_in_traces(
+                &traces,
+                &address("0x2222222222222222222222222222222222222222")
+            ),
+            "Call trace should not be treated as a creation"
+        );
+    }
+}
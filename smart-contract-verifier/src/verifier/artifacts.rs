@@ -0,0 +1,268 @@
+use crate::compiler::ExtraSettings;
+use ethers_solc::{
+    artifacts::{Contract, Error as CompilerError, Severity},
+    CompilerInput,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown artifact: {0}")]
+pub struct ParseArtifactKindError(String);
+
+/// Compilation artifacts which may be returned together with the verification result,
+/// so that the caller does not have to recompile the contract to obtain them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ArtifactKind {
+    /// Standard JSON input the contract has been compiled with. Output selection
+    /// is returned as specified by the requester, rather than the one the verifier uses.
+    CompilerInput,
+    /// Warnings emitted by the compiler.
+    Warnings,
+    /// Source maps of the creation and runtime bytecode.
+    SourceMaps,
+    MethodIdentifiers,
+    /// Solidity only.
+    StorageLayout,
+    Userdoc,
+    Devdoc,
+    /// Metadata JSON the metadata hash is calculated of (Solidity only).
+    Metadata,
+}
+
+impl ArtifactKind {
+    pub const ALL: [Self; 8] = [
+        Self::CompilerInput,
+        Self::Warnings,
+        Self::SourceMaps,
+        Self::MethodIdentifiers,
+        Self::StorageLayout,
+        Self::Userdoc,
+        Self::Devdoc,
+        Self::Metadata,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CompilerInput => "compiler_input",
+            Self::Warnings => "warnings",
+            Self::SourceMaps => "source_maps",
+            Self::MethodIdentifiers => "method_identifiers",
+            Self::StorageLayout => "storage_layout",
+            Self::Userdoc => "userdoc",
+            Self::Devdoc => "devdoc",
+            Self::Metadata => "metadata",
+        }
+    }
+
+    /// Compiler outputs to be selected for every contract to obtain the artifact.
+    /// Outputs of the diagnostic artifacts are selected via the diagnostic mode
+    /// of [`super::output_selection::required_output_selection`] instead.
+    pub fn outputs(&self) -> &'static [&'static str] {
+        match self {
+            Self::CompilerInput | Self::Warnings | Self::SourceMaps | Self::Metadata => &[],
+            Self::MethodIdentifiers => &["evm.methodIdentifiers"],
+            Self::StorageLayout => &["storageLayout"],
+            Self::Userdoc => &["userdoc"],
+            Self::Devdoc => &["devdoc"],
+        }
+    }
+
+    /// Metadata and source maps are selected only if the artifacts requiring them are requested.
+    pub fn is_diagnostic(&self) -> bool {
+        matches!(self, Self::SourceMaps | Self::Metadata)
+    }
+
+    /// Vyper compiler does not produce storage layout and metadata outputs.
+    pub fn is_solidity_only(&self) -> bool {
+        matches!(self, Self::StorageLayout | Self::Metadata)
+    }
+}
+
+impl FromStr for ArtifactKind {
+    type Err = ParseArtifactKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| ParseArtifactKindError(s.to_string()))
+    }
+}
+
+impl Display for ArtifactKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Artifacts of the verified contract. Only requested artifacts are set.
+///
+/// Structured artifacts are returned in the format of the compiler standard JSON output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Artifacts {
+    pub compiler_input: Option<Value>,
+    pub warnings: Option<Vec<String>>,
+    pub source_map: Option<String>,
+    pub deployed_source_map: Option<String>,
+    pub method_identifiers: Option<Value>,
+    pub storage_layout: Option<Value>,
+    pub userdoc: Option<Value>,
+    pub devdoc: Option<Value>,
+    pub metadata: Option<Value>,
+}
+
+impl Artifacts {
+    pub(crate) fn new(
+        kinds: &BTreeSet<ArtifactKind>,
+        compiler_input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+        compiler_errors: &[CompilerError],
+        contract: &Contract,
+    ) -> Self {
+        let mut artifacts = Self::default();
+        if kinds.is_empty() {
+            return artifacts;
+        }
+
+        // Contract outputs are accessed via their standard JSON representation,
+        // so that artifacts keep the format the compiler returns them in
+        let contract = serde_json::to_value(contract).unwrap_or_default();
+        let output = |pointer: &str| {
+            contract
+                .pointer(pointer)
+                .filter(|value| !value.is_null())
+                .cloned()
+        };
+        for kind in kinds {
+            match kind {
+                ArtifactKind::CompilerInput => {
+                    artifacts.compiler_input =
+                        serde_json::to_value(compiler_input).ok().map(|mut input| {
+                            extra_settings.apply_to_json(&mut input);
+                            input
+                        })
+                }
+                ArtifactKind::Warnings => {
+                    artifacts.warnings = Some(
+                        compiler_errors
+                            .iter()
+                            .filter(|err| err.severity == Severity::Warning)
+                            .map(|err| {
+                                err.formatted_message
+                                    .as_ref()
+                                    .unwrap_or(&err.message)
+                                    .clone()
+                            })
+                            .collect(),
+                    )
+                }
+                ArtifactKind::SourceMaps => {
+                    let source_map = |pointer| output(pointer).and_then(as_string);
+                    artifacts.source_map = source_map("/evm/bytecode/sourceMap");
+                    artifacts.deployed_source_map = source_map("/evm/deployedBytecode/sourceMap");
+                }
+                ArtifactKind::MethodIdentifiers => {
+                    artifacts.method_identifiers = output("/evm/methodIdentifiers")
+                }
+                ArtifactKind::StorageLayout => artifacts.storage_layout = output("/storageLayout"),
+                ArtifactKind::Userdoc => artifacts.userdoc = output("/userdoc"),
+                ArtifactKind::Devdoc => artifacts.devdoc = output("/devdoc"),
+                // Metadata is returned by the compiler as a JSON encoded string
+                ArtifactKind::Metadata => {
+                    artifacts.metadata = output("/metadata").map(|metadata| match metadata {
+                        Value::String(raw) => {
+                            serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+                        }
+                        metadata => metadata,
+                    })
+                }
+            }
+        }
+        artifacts
+    }
+}
+
+fn as_string(value: Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_solc::artifacts::Settings;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parse_artifact_kinds() {
+        for kind in ArtifactKind::ALL {
+            assert_eq!(Ok(kind), ArtifactKind::from_str(kind.as_str()));
+        }
+        assert!(ArtifactKind::from_str("ast").is_err());
+    }
+
+    #[test]
+    fn extract_requested_artifacts() {
+        let metadata = json!({
+            "compiler": { "version": "0.8.7+commit.e28d00a7" },
+            "language": "Solidity",
+            "output": { "abi": [], "devdoc": {}, "userdoc": {} },
+            "settings": { "optimizer": { "enabled": false, "runs": 200 } },
+            "sources": {},
+            "version": 1
+        });
+        let contract: Contract = serde_json::from_value(json!({
+            "abi": [],
+            "evm": {
+                "bytecode": { "object": "6080", "sourceMap": "0:1:0:-:0" },
+                "deployedBytecode": { "object": "6080", "sourceMap": "1:1:0:-:0" },
+                "methodIdentifiers": { "get()": "6d4ce63c" }
+            },
+            "metadata": metadata.to_string()
+        }))
+        .expect("Valid contract");
+        let compiler_input = CompilerInput {
+            language: "Solidity".into(),
+            sources: BTreeMap::new(),
+            settings: Settings::default(),
+        };
+        let kinds = BTreeSet::from([
+            ArtifactKind::CompilerInput,
+            ArtifactKind::SourceMaps,
+            ArtifactKind::MethodIdentifiers,
+            ArtifactKind::Metadata,
+        ]);
+        let extra_settings = ExtraSettings {
+            append_cbor: Some(false),
+        };
+
+        let artifacts = Artifacts::new(&kinds, &compiler_input, &extra_settings, &[], &contract);
+        assert_eq!(Some("0:1:0:-:0".into()), artifacts.source_map);
+        assert_eq!(Some("1:1:0:-:0".into()), artifacts.deployed_source_map);
+        assert_eq!(
+            Some(json!({ "get()": "6d4ce63c" })),
+            artifacts.method_identifiers
+        );
+        assert_eq!(Some(metadata), artifacts.metadata);
+        assert_eq!(
+            Some(&json!(false)),
+            artifacts
+                .compiler_input
+                .as_ref()
+                .and_then(|input| input.pointer("/settings/metadata/appendCBOR")),
+            "Extra settings should be a part of the compiler input"
+        );
+        assert_eq!(None, artifacts.warnings, "Warnings were not requested");
+        assert_eq!(None, artifacts.storage_layout);
+    }
+}
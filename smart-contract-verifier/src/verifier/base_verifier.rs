@@ -6,7 +6,10 @@ use super::{
 use crate::{mismatch::Mismatch, DisplayBytes};
 use bytes::Bytes;
 use ethabi::{Constructor, Token};
-use ethers_solc::{artifacts::Contract, Artifact, CompilerOutput};
+use ethers_solc::{
+    artifacts::{BytecodeObject, Contract},
+    Artifact, CompilerOutput,
+};
 
 /// Verifier used for contract verification.
 ///
@@ -25,29 +28,59 @@ pub struct VerificationSuccess {
     pub contract_name: String,
     pub abi: ethabi::Contract,
     pub constructor_args: Option<DisplayBytes>,
+    pub match_type: MatchType,
+    /// Compiler output of the verified contract
+    pub contract: Contract,
+}
+
+/// Describes how precisely the locally compiled bytecode corresponds to the remote one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchType {
+    /// Both executable code and metadata hashes are equal.
+    Full,
+    /// Executable code is equal, but metadata hashes differ
+    /// (e.g., the sources differ in comments or whitespaces).
+    Partial,
 }
 
 impl Verifier {
+    /// Initializes a new verifier with the data obtained from the chain.
+    ///
+    /// Empty `deployed_bytecode` results in creation-only mode, where only
+    /// creation transaction input is compared (see [`Bytecode::new_creation_only`]).
     pub fn new(
         creation_tx_input: Bytes,
         deployed_bytecode: Bytes,
     ) -> Result<Self, BytecodeInitError> {
-        let bytecode = Bytecode::new(creation_tx_input, deployed_bytecode)?;
+        let bytecode = if deployed_bytecode.is_empty() {
+            Bytecode::new_creation_only(creation_tx_input)?
+        } else {
+            Bytecode::new(creation_tx_input, deployed_bytecode)?
+        };
         Ok(Self {
             remote_bytecode: bytecode,
         })
     }
 
+    /// Returns `true` if the contract has no runtime code in the chain,
+    /// so that only its creation transaction input is verified.
+    pub fn is_creation_only(&self) -> bool {
+        self.remote_bytecode.is_creation_only()
+    }
+
     /// Verifies input data provided on initialization by comparing it
     /// with compiler output received when compiling source data locally.
     ///
     /// Iterates through all contracts received from local compilation and
     /// returns [`VerificationSuccess`] with file path and contract name
     /// of succeeded contract, if any. Otherwise, returns [`None`].
+    ///
+    /// Outputs are taken by reference, so that the same compilation
+    /// may be compared with several remote bytecodes.
     pub fn verify(
         &self,
-        output: CompilerOutput,
-        output_modified: CompilerOutput,
+        output: &CompilerOutput,
+        output_modified: &CompilerOutput,
     ) -> Result<VerificationSuccess, Vec<VerificationError>> {
         let not_found_in_modified_compiler_output_error =
             |file_path: String, contract_name: Option<String>| match contract_name {
@@ -67,12 +100,12 @@ impl Verifier {
             };
 
         let mut errors = Vec::new();
-        for (path, contracts) in output.contracts {
+        for (path, contracts) in &output.contracts {
             let contracts_modified = {
-                if let Some(contracts_modified) = output_modified.contracts.get(&path) {
+                if let Some(contracts_modified) = output_modified.contracts.get(path) {
                     contracts_modified
                 } else {
-                    let error = not_found_in_modified_compiler_output_error(path, None);
+                    let error = not_found_in_modified_compiler_output_error(path.clone(), None);
 
                     tracing::error!("{}", error);
                     errors.push(error);
@@ -83,11 +116,13 @@ impl Verifier {
 
             for (name, contract) in contracts {
                 let contract_modified = {
-                    if let Some(contract) = contracts_modified.get(&name) {
+                    if let Some(contract) = contracts_modified.get(name) {
                         contract
                     } else {
-                        let error =
-                            not_found_in_modified_compiler_output_error(path.clone(), Some(name));
+                        let error = not_found_in_modified_compiler_output_error(
+                            path.clone(),
+                            Some(name.clone()),
+                        );
 
                         tracing::error!("{}", error);
                         errors.push(error);
@@ -96,17 +131,20 @@ impl Verifier {
                     }
                 };
 
-                match self.compare(&contract, contract_modified) {
-                    Ok((abi, constructor_args)) => {
+                match self.compare(contract, contract_modified) {
+                    Ok((abi, constructor_args, match_type)) => {
                         return Ok(VerificationSuccess {
-                            file_path: path,
-                            contract_name: name,
+                            file_path: path.clone(),
+                            contract_name: name.clone(),
                             abi,
                             constructor_args: constructor_args.map(DisplayBytes::from),
+                            match_type,
+                            contract: contract.clone(),
                         })
                     }
                     Err(err) => {
-                        let error = VerificationError::with_contract(path.clone(), name, err);
+                        let error =
+                            VerificationError::with_contract(path.clone(), name.clone(), err);
 
                         tracing::error!("{}", error);
                         errors.push(error)
@@ -123,7 +161,7 @@ impl Verifier {
         &self,
         contract: &Contract,
         contract_modified: &Contract,
-    ) -> Result<(ethabi::Contract, Option<Bytes>), VerificationErrorKind> {
+    ) -> Result<(ethabi::Contract, Option<Bytes>, MatchType), VerificationErrorKind> {
         let abi = contract
             .get_abi()
             .ok_or_else(|| VerificationErrorKind::InternalError("missing abi".into()))?;
@@ -134,7 +172,9 @@ impl Verifier {
             }
             // Corresponding bytecode was not linked properly
             BytecodeInitError::InvalidCreationTxInput(_)
-            | BytecodeInitError::InvalidDeployedBytecode(_) => VerificationErrorKind::LibraryMissed,
+            | BytecodeInitError::InvalidDeployedBytecode(_) => {
+                VerificationErrorKind::LibraryMissed(Self::library_placeholders(contract))
+            }
         })?;
         // If libraries were linked for main contract, they must be linked for modified contract as well
         let bytecode_modified = Bytecode::try_from(contract_modified).map_err(|err| {
@@ -143,7 +183,7 @@ impl Verifier {
 
         let local_bytecode = LocalBytecode::new(bytecode, bytecode_modified)?;
 
-        Self::compare_creation_tx_inputs(&self.remote_bytecode, &local_bytecode)?;
+        let match_type = Self::compare_creation_tx_inputs(&self.remote_bytecode, &local_bytecode)?;
 
         let constructor_args = Self::extract_constructor_args(
             self.remote_bytecode.creation_tx_input(),
@@ -151,13 +191,42 @@ impl Verifier {
             abi.constructor(),
         )?;
 
-        Ok((abi.into_owned(), constructor_args))
+        Ok((abi.into_owned(), constructor_args, match_type))
+    }
+
+    /// Returns distinct placeholders of the libraries the contract bytecode has not been linked with.
+    fn library_placeholders(contract: &Contract) -> Vec<String> {
+        // Placeholders are 40 characters long and start with "__"
+        // (https://docs.soliditylang.org/en/latest/using-the-compiler.html#library-linking)
+        const PLACEHOLDER_LENGTH: usize = 40;
+
+        let unlinked = [
+            contract.get_bytecode_object(),
+            contract.get_deployed_bytecode_object(),
+        ];
+        let mut placeholders = Vec::new();
+        for object in unlinked.iter().flatten() {
+            if let BytecodeObject::Unlinked(object) = &**object {
+                let mut rest = object.as_str();
+                while let Some(start) = rest.find("__") {
+                    let placeholder = match rest.get(start..start + PLACEHOLDER_LENGTH) {
+                        Some(placeholder) => placeholder,
+                        None => break,
+                    };
+                    if !placeholders.iter().any(|known| known == placeholder) {
+                        placeholders.push(placeholder.to_string());
+                    }
+                    rest = &rest[start + PLACEHOLDER_LENGTH..];
+                }
+            }
+        }
+        placeholders
     }
 
     fn compare_creation_tx_inputs(
         remote_bytecode: &Bytecode,
         local_bytecode: &LocalBytecode,
-    ) -> Result<(), VerificationErrorKind> {
+    ) -> Result<MatchType, VerificationErrorKind> {
         let remote_creation_tx_input = remote_bytecode.creation_tx_input();
         let local_creation_tx_input = local_bytecode.creation_tx_input();
 
@@ -178,14 +247,15 @@ impl Verifier {
             remote_creation_tx_input,
             local_creation_tx_input,
             local_bytecode.creation_tx_input_parts(),
-        )?;
-
-        Ok(())
+        )
     }
 
     /// Performs an actual comparison of locally compiled bytecode
     /// with remote bytecode provided for verification.
     ///
+    /// Metadata hashes are not required to be equal,
+    /// but their difference results in [`MatchType::Partial`].
+    ///
     /// # Panics
     ///
     /// The function will panic if `remote_raw.len()` is less than `local_raw.len()`.
@@ -193,7 +263,7 @@ impl Verifier {
         remote_raw: &Bytes,
         local_raw: &Bytes,
         local_parts: &Vec<BytecodePart>,
-    ) -> Result<(), VerificationErrorKind> {
+    ) -> Result<MatchType, VerificationErrorKind> {
         // A caller should ensure that this precondition holds.
         // Currently only `compare_creation_tx_inputs` calls current function,
         // and it guarantees that `remote_creation_tx_input.len() < local_creation_tx_input.len()`
@@ -204,6 +274,7 @@ impl Verifier {
         );
 
         let mut i = 0usize; // keep track of current processing position of `remote_raw`
+        let mut match_type = MatchType::Full;
 
         for part in local_parts {
             match part {
@@ -219,9 +290,9 @@ impl Verifier {
                     }
                 }
                 BytecodePart::Metadata {
+                    metadata_raw,
                     metadata,
                     metadata_length_raw,
-                    ..
                 } => {
                     let (remote_metadata, remote_metadata_length) =
                         MetadataHash::from_cbor(&remote_raw[i..])
@@ -247,13 +318,17 @@ impl Verifier {
                             Mismatch::new(expected_solc, remote_solc),
                         ));
                     }
+
+                    if metadata_raw != &remote_raw[i..i + metadata_raw.len()] {
+                        match_type = MatchType::Partial;
+                    }
                 }
             }
 
             i += part.size();
         }
 
-        Ok(())
+        Ok(match_type)
     }
 
     /// Extracts constructor arguments from the creation transaction input specified on
@@ -373,12 +448,29 @@ mod verifier_initialization_tests {
     }
 
     #[test]
-    fn initialization_with_empty_deployed_bytecode_should_fail() {
-        let verifier = new_verifier(DEFAULT_CREATION_TX_INPUT, "");
+    fn initialization_with_empty_deployed_bytecode_is_creation_only() {
+        let verifier = new_verifier(DEFAULT_CREATION_TX_INPUT, "")
+            .expect("Verifier initialization without deployed bytecode failed");
+        assert!(
+            verifier.is_creation_only(),
+            "Verifier should be in creation-only mode"
+        );
+
+        let verifier = new_verifier(DEFAULT_CREATION_TX_INPUT, DEFAULT_DEPLOYED_BYTECODE)
+            .expect("Verifier initialization failed");
+        assert!(
+            !verifier.is_creation_only(),
+            "Verifier should not be in creation-only mode"
+        );
+    }
+
+    #[test]
+    fn initialization_with_empty_bytecodes_should_fail() {
+        let verifier = new_verifier("", "");
         assert!(verifier.is_err(), "Verifier initialization should fail");
         assert_eq!(
             verifier.unwrap_err(),
-            BytecodeInitError::EmptyDeployedBytecode
+            BytecodeInitError::EmptyCreationTxInput
         )
     }
 }
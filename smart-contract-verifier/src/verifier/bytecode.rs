@@ -1,9 +1,12 @@
+mod eof;
+
 use super::{
     errors::{BytecodeInitError, VerificationErrorKind},
     metadata::MetadataHash,
 };
 use crate::mismatch::Mismatch;
 use bytes::{Buf, Bytes};
+use eof::EofContainer;
 use ethers_solc::{artifacts::Contract, Artifact};
 
 /// Combine creation_tx_input and deployed_bytecode.
@@ -11,7 +14,8 @@ use ethers_solc::{artifacts::Contract, Artifact};
 pub struct Bytecode {
     /// Raw bytecode bytes used in contract creation transaction
     creation_tx_input: Bytes,
-    /// Raw deployed bytecode bytes
+    /// Raw deployed bytecode bytes. Is empty only for bytecodes
+    /// initialized via [`Bytecode::new_creation_only`].
     deployed_bytecode: Bytes,
 }
 
@@ -34,9 +38,28 @@ impl Bytecode {
         })
     }
 
+    /// Initializes bytecode of the contract which has no runtime code stored in the chain
+    /// (e.g., it has been self-destructed, or its constructor returned empty code).
+    ///
+    /// Only creation transaction input may be verified for such contracts.
+    pub fn new_creation_only(creation_tx_input: Bytes) -> Result<Self, BytecodeInitError> {
+        if creation_tx_input.is_empty() {
+            return Err(BytecodeInitError::EmptyCreationTxInput);
+        }
+
+        Ok(Self {
+            creation_tx_input,
+            deployed_bytecode: Bytes::new(),
+        })
+    }
+
     pub fn creation_tx_input(&self) -> &Bytes {
         &self.creation_tx_input
     }
+
+    pub fn is_creation_only(&self) -> bool {
+        self.deployed_bytecode.is_empty()
+    }
 }
 
 impl TryFrom<&Contract> for Bytecode {
@@ -147,6 +170,87 @@ impl LocalBytecode {
             )));
         }
 
+        if EofContainer::is_eof(raw) {
+            Self::split_eof(raw, raw_modified)
+        } else {
+            Self::split_legacy(raw, raw_modified)
+        }
+    }
+
+    /// Splits EOF container section by section.
+    ///
+    /// Metadata hashes are searched only inside data sections (including the ones of
+    /// nested containers). All other sections are returned as [`BytecodePart::Main`] parts,
+    /// and must not differ from the corresponding sections of modified bytecode.
+    fn split_eof(
+        raw: &Bytes,
+        raw_modified: &Bytes,
+    ) -> Result<Vec<BytecodePart>, VerificationErrorKind> {
+        let parse = |raw: &Bytes| {
+            EofContainer::parse(raw).map_err(|err| {
+                VerificationErrorKind::InternalError(format!(
+                    "failed to parse EOF container: {}",
+                    err
+                ))
+            })
+        };
+        let container = parse(raw)?;
+        let container_modified = parse(raw_modified)?;
+
+        let main_sections = std::iter::once((&container.header, &container_modified.header))
+            .chain(std::iter::once((
+                &container.types,
+                &container_modified.types,
+            )))
+            .chain(
+                container
+                    .code_sections
+                    .iter()
+                    .zip(container_modified.code_sections.iter()),
+            );
+
+        let mut result = Vec::new();
+        for (section, section_modified) in main_sections {
+            if section != section_modified {
+                return Err(VerificationErrorKind::InternalError(
+                    "EOF container differs from modified one outside of data sections".into(),
+                ));
+            }
+            if !section.is_empty() {
+                result.push(BytecodePart::Main {
+                    raw: section.clone(),
+                });
+            }
+        }
+
+        for (subcontainer, subcontainer_modified) in container
+            .container_sections
+            .iter()
+            .zip(container_modified.container_sections.iter())
+        {
+            result.extend(Self::split_eof(subcontainer, subcontainer_modified)?);
+        }
+
+        result.extend(Self::split_legacy(
+            &container.data,
+            &container_modified.data,
+        )?);
+
+        // Everything after the container is not a part of EOF, so is treated as a legacy bytecode
+        let container_size = container.size();
+        result.extend(Self::split_legacy(
+            &raw.slice(container_size..),
+            &raw_modified.slice(container_size..),
+        )?);
+
+        Ok(result)
+    }
+
+    /// Splits legacy (non-EOF) bytecode, where metadata hashes may be placed anywhere.
+    fn split_legacy(
+        raw: &Bytes,
+        raw_modified: &Bytes,
+    ) -> Result<Vec<BytecodePart>, VerificationErrorKind> {
         let parts_total_size = |parts: &Vec<BytecodePart>| -> usize {
             parts.iter().fold(0, |size, el| size + el.size())
         };
@@ -461,6 +565,73 @@ mod local_bytecode_initialization_tests {
         );
     }
 
+    #[test]
+    fn eof_with_metadata_in_data_sections() {
+        let runtime = eof::tests::container(DEPLOYED_BYTECODE_MAIN_PART_1, &[], METADATA_PART_1);
+        let runtime_modified =
+            eof::tests::container(DEPLOYED_BYTECODE_MAIN_PART_1, &[], METADATA_PART1_MODIFIED);
+        let initcode = eof::tests::container("5f5fee00", &[runtime.clone()], "");
+        let initcode_modified = eof::tests::container("5f5fee00", &[runtime_modified], "");
+
+        let bytecode = new_bytecode(&initcode, &runtime).expect("Bytecode initialization failed");
+        let bytecode_modified = new_bytecode(&initcode_modified, &runtime)
+            .expect("Modified bytecode initialization failed");
+
+        let local_bytecode = LocalBytecode::new(bytecode.clone(), bytecode_modified);
+
+        let local_bytecode = local_bytecode.expect("Initialization of local bytecode failed");
+        let initcode_container = EofContainer::parse(bytecode.creation_tx_input())
+            .expect("Initcode is a valid container");
+        let runtime_container = EofContainer::parse(&initcode_container.container_sections[0])
+            .expect("Runtime code is a valid container");
+        assert_eq!(
+            vec![
+                BytecodePart::Main {
+                    raw: initcode_container.header
+                },
+                BytecodePart::Main {
+                    raw: initcode_container.types
+                },
+                main_bytecode_part("5f5fee00"),
+                BytecodePart::Main {
+                    raw: runtime_container.header
+                },
+                BytecodePart::Main {
+                    raw: runtime_container.types
+                },
+                main_bytecode_part(DEPLOYED_BYTECODE_MAIN_PART_1),
+                metadata_bytecode_part(METADATA_PART_1),
+            ],
+            local_bytecode.creation_tx_input_parts,
+            "Invalid bytecode parts"
+        );
+    }
+
+    #[test]
+    fn eof_with_difference_outside_of_data_sections_should_fail() {
+        let initcode = eof::tests::container("5f5ff3", &[], METADATA_PART_1);
+        let initcode_modified = eof::tests::container("5f5ff4", &[], METADATA_PART1_MODIFIED);
+
+        let bytecode = new_bytecode(&initcode, DEFAULT_DEPLOYED_BYTECODE)
+            .expect("Bytecode initialization failed");
+        let bytecode_modified =
+            new_bytecode(&initcode_modified, DEFAULT_DEPLOYED_BYTECODE_MODIFIED)
+                .expect("Modified bytecode initialization failed");
+
+        let local_bytecode = LocalBytecode::new(bytecode, bytecode_modified);
+
+        match local_bytecode.expect_err("Local bytecode initialization should fail") {
+            VerificationErrorKind::InternalError(error) => {
+                assert!(
+                    error.contains("outside of data sections"),
+                    "Invalid error message: {}",
+                    error
+                )
+            }
+            err => panic!("Invalid error: {}", err),
+        }
+    }
+
     #[test]
     fn with_different_lengths_should_fail() {
         let creation_tx_input = format!("{}{}", CREATION_TX_INPUT_MAIN_PART_1, METADATA_PART_1);
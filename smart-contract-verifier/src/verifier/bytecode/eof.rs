@@ -0,0 +1,278 @@
+//! Parser for EVM Object Format containers
+//! (https://eips.ethereum.org/EIPS/eip-3540, https://eips.ethereum.org/EIPS/eip-7692).
+//!
+//! Only the container layout is parsed. Code validation
+//! is the responsibility of the compiler and the chain.
+
+use bytes::{Buf, Bytes};
+use thiserror::Error;
+
+/// Every EOF container starts with `0xEF00` magic.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+const VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum EofParseError {
+    #[error("container does not start with EOF magic")]
+    InvalidMagic,
+    #[error("unsupported EOF version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("unexpected section kind: expected {expected:#04x}, found {found:#04x}")]
+    UnexpectedSectionKind { expected: u8, found: u8 },
+    #[error("container does not have any code section")]
+    NoCodeSections,
+    #[error("container is shorter than declared in its header")]
+    UnexpectedEnd,
+}
+
+/// EOF container split into its sections.
+///
+/// Sections are stored in the order they are placed in the container,
+/// so that concatenation of all of them results in the original container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofContainer {
+    /// Magic, version and section headers up to and including the terminator
+    pub header: Bytes,
+    pub types: Bytes,
+    pub code_sections: Vec<Bytes>,
+    /// Nested containers (e.g., runtime code of the contract inside its initcode)
+    pub container_sections: Vec<Bytes>,
+    /// Data section. May be shorter than declared in the header
+    /// for containers which data is appended on deployment.
+    pub data: Bytes,
+}
+
+impl EofContainer {
+    pub fn is_eof(raw: &[u8]) -> bool {
+        raw.starts_with(&MAGIC)
+    }
+
+    /// Parses the container placed at the beginning of `raw`.
+    ///
+    /// Bytes that follow the container (e.g., constructor arguments
+    /// appended to the initcode) are not included into the result.
+    pub fn parse(raw: &Bytes) -> Result<Self, EofParseError> {
+        let mut header = raw.clone();
+
+        if !Self::is_eof(&header) {
+            return Err(EofParseError::InvalidMagic);
+        }
+        header.advance(MAGIC.len());
+
+        let version = read_u8(&mut header)?;
+        if version != VERSION {
+            return Err(EofParseError::UnsupportedVersion(version));
+        }
+
+        expect_kind(&mut header, KIND_TYPES)?;
+        let types_size = read_u16(&mut header)? as usize;
+
+        expect_kind(&mut header, KIND_CODE)?;
+        let code_sizes = read_sizes(&mut header, read_u16)?;
+        if code_sizes.is_empty() {
+            return Err(EofParseError::NoCodeSections);
+        }
+
+        let container_sizes = match peek_u8(&header)? {
+            KIND_CONTAINER => {
+                header.advance(1);
+                read_sizes(&mut header, read_u32)?
+            }
+            _ => vec![],
+        };
+
+        expect_kind(&mut header, KIND_DATA)?;
+        let data_size = read_u16(&mut header)? as usize;
+        expect_kind(&mut header, TERMINATOR)?;
+
+        let header_size = raw.len() - header.len();
+        let mut body = header;
+        let header = raw.slice(..header_size);
+
+        let types = split_to(&mut body, types_size)?;
+        let code_sections = code_sizes
+            .into_iter()
+            .map(|size| split_to(&mut body, size))
+            .collect::<Result<_, _>>()?;
+        let container_sections = container_sizes
+            .into_iter()
+            .map(|size| split_to(&mut body, size))
+            .collect::<Result<_, _>>()?;
+        let data = body.split_to(data_size.min(body.len()));
+
+        Ok(Self {
+            header,
+            types,
+            code_sections,
+            container_sections,
+            data,
+        })
+    }
+
+    /// Number of bytes the container occupies.
+    pub fn size(&self) -> usize {
+        self.header.len()
+            + self.types.len()
+            + self.code_sections.iter().map(Bytes::len).sum::<usize>()
+            + self
+                .container_sections
+                .iter()
+                .map(Bytes::len)
+                .sum::<usize>()
+            + self.data.len()
+    }
+}
+
+fn read_u8(buf: &mut Bytes) -> Result<u8, EofParseError> {
+    if buf.remaining() < 1 {
+        return Err(EofParseError::UnexpectedEnd);
+    }
+    Ok(buf.get_u8())
+}
+
+fn peek_u8(buf: &Bytes) -> Result<u8, EofParseError> {
+    buf.first().copied().ok_or(EofParseError::UnexpectedEnd)
+}
+
+fn read_u16(buf: &mut Bytes) -> Result<u32, EofParseError> {
+    if buf.remaining() < 2 {
+        return Err(EofParseError::UnexpectedEnd);
+    }
+    Ok(buf.get_u16() as u32)
+}
+
+fn read_u32(buf: &mut Bytes) -> Result<u32, EofParseError> {
+    if buf.remaining() < 4 {
+        return Err(EofParseError::UnexpectedEnd);
+    }
+    Ok(buf.get_u32())
+}
+
+/// Reads the number of sections followed by the size of each of them.
+fn read_sizes(
+    buf: &mut Bytes,
+    read_size: fn(&mut Bytes) -> Result<u32, EofParseError>,
+) -> Result<Vec<usize>, EofParseError> {
+    let number = read_u16(buf)?;
+    (0..number)
+        .map(|_| read_size(buf).map(|size| size as usize))
+        .collect()
+}
+
+fn expect_kind(buf: &mut Bytes, expected: u8) -> Result<(), EofParseError> {
+    let found = read_u8(buf)?;
+    if found != expected {
+        return Err(EofParseError::UnexpectedSectionKind { expected, found });
+    }
+    Ok(())
+}
+
+fn split_to(buf: &mut Bytes, size: usize) -> Result<Bytes, EofParseError> {
+    if buf.len() < size {
+        return Err(EofParseError::UnexpectedEnd);
+    }
+    Ok(buf.split_to(size))
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::DisplayBytes;
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    fn bytes(hex: &str) -> Bytes {
+        DisplayBytes::from_str(hex).expect("Invalid hex").0
+    }
+
+    /// Builds hex encoded container with a single code section
+    /// and specified nested containers and data.
+    pub fn container(code: &str, containers: &[String], data: &str) -> String {
+        let containers_header = if containers.is_empty() {
+            "".to_string()
+        } else {
+            let sizes: String = containers
+                .iter()
+                .map(|container| format!("{:08x}", container.len() / 2))
+                .collect();
+            format!("03{:04x}{}", containers.len(), sizes)
+        };
+        format!(
+            "ef0001010004020001{:04x}{}ff{:04x}00{}{}{}{}",
+            code.len() / 2,
+            containers_header,
+            data.len() / 2,
+            "00800000",
+            code,
+            containers.concat(),
+            data
+        )
+    }
+
+    #[test]
+    fn parses_container_without_subcontainers() {
+        let raw = bytes(&format!("{}cafe", container("5f5ff3", &[], "aabbcc")));
+
+        let container = EofContainer::parse(&raw).expect("Valid container");
+        assert_eq!(bytes("ef00010100040200010003ff000300"), container.header);
+        assert_eq!(bytes("00800000"), container.types);
+        assert_eq!(vec![bytes("5f5ff3")], container.code_sections);
+        assert!(container.container_sections.is_empty());
+        assert_eq!(bytes("aabbcc"), container.data);
+        assert_eq!(
+            raw.len() - 2,
+            container.size(),
+            "Trailing bytes are not a part of the container"
+        );
+    }
+
+    #[test]
+    fn parses_container_with_subcontainer() {
+        let runtime = container("00", &[], "");
+        let raw = bytes(&container("5f5fee00", &[runtime.clone()], "aa"));
+
+        let container = EofContainer::parse(&raw).expect("Valid container");
+        assert_eq!(vec![bytes(&runtime)], container.container_sections);
+        assert_eq!(bytes("aa"), container.data);
+        assert_eq!(raw.len(), container.size());
+    }
+
+    #[test]
+    fn parses_container_with_truncated_data() {
+        let raw = container("00", &[], "aabbcc");
+        let raw = bytes(&raw[..raw.len() - 2]);
+
+        let container = EofContainer::parse(&raw).expect("Valid container");
+        assert_eq!(bytes("aabb"), container.data);
+    }
+
+    #[test]
+    fn parse_invalid_containers_should_fail() {
+        assert_eq!(
+            Err(EofParseError::InvalidMagic),
+            EofContainer::parse(&bytes("6080604052"))
+        );
+        assert_eq!(
+            Err(EofParseError::UnsupportedVersion(2)),
+            EofContainer::parse(&bytes("ef0002010004"))
+        );
+        assert_eq!(
+            Err(EofParseError::UnexpectedSectionKind {
+                expected: KIND_CODE,
+                found: KIND_DATA
+            }),
+            EofContainer::parse(&bytes("ef0001010004ff0000"))
+        );
+        let raw = container("5f5ff3", &[], "");
+        assert_eq!(
+            Err(EofParseError::UnexpectedEnd),
+            EofContainer::parse(&bytes(&raw[..raw.len() - 2]))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use ethers_solc::{artifacts::output_selection::OutputSelection, CompilerOutput};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("contract name is empty")]
+pub struct ParseContractNameError;
+
+/// Name of the contract the remote bytecode is expected to correspond to.
+/// Either a bare contract name (`Name`), or the one qualified
+/// with the file the contract is located at (`path:Name`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractName {
+    pub file_path: Option<String>,
+    pub name: String,
+}
+
+impl FromStr for ContractName {
+    type Err = ParseContractNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (file_path, name) = match s.rsplit_once(':') {
+            Some((file_path, name)) => (Some(file_path.to_string()), name),
+            None => (None, s),
+        };
+        if name.is_empty() {
+            return Err(ParseContractNameError);
+        }
+        Ok(Self {
+            file_path,
+            name: name.to_string(),
+        })
+    }
+}
+
+impl Display for ContractName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.file_path {
+            Some(file_path) => write!(f, "{}:{}", file_path, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl ContractName {
+    /// Contracts compiled via the legacy command line interface
+    /// (see `solidity::solc_cli`) have no file paths, so are matched by names only.
+    pub fn matches(&self, file_path: &str, name: &str) -> bool {
+        let file_path_matches = match &self.file_path {
+            Some(expected) => file_path.is_empty() || expected == file_path,
+            None => true,
+        };
+        file_path_matches && self.name == name
+    }
+
+    /// Restricts output selection, so that the outputs required
+    /// for every contract are requested for the named contract only.
+    pub fn narrow_output_selection(&self, output_selection: &mut OutputSelection) {
+        let contract_outputs = output_selection
+            .0
+            .values()
+            .find_map(|file_selection| file_selection.get("*"))
+            .cloned()
+            .unwrap_or_default();
+        let file_path = self.file_path.clone().unwrap_or_else(|| "*".to_string());
+        *output_selection = OutputSelection(BTreeMap::from([(
+            file_path,
+            BTreeMap::from([(self.name.clone(), contract_outputs)]),
+        )]));
+    }
+
+    /// Removes all contracts but the named one from the compiler output.
+    pub fn retain_matching(&self, output: &mut CompilerOutput) {
+        for (file_path, contracts) in output.contracts.iter_mut() {
+            contracts.retain(|name, _| self.matches(file_path, name));
+        }
+        output
+            .contracts
+            .retain(|_, contracts| !contracts.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::output_selection::required_output_selection;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_contract_name() {
+        assert_eq!(
+            Ok(ContractName {
+                file_path: None,
+                name: "Token".into()
+            }),
+            ContractName::from_str("Token")
+        );
+        assert_eq!(
+            Ok(ContractName {
+                file_path: Some("contracts/Token.sol".into()),
+                name: "Token".into()
+            }),
+            ContractName::from_str("contracts/Token.sol:Token")
+        );
+        assert_eq!(
+            Err(ParseContractNameError),
+            ContractName::from_str("contracts/Token.sol:")
+        );
+    }
+
+    #[test]
+    fn narrows_output_selection() {
+        let contract_name = ContractName::from_str("contracts/Token.sol:Token").unwrap();
+        let mut output_selection = required_output_selection(false, "Solidity");
+        contract_name.narrow_output_selection(&mut output_selection);
+
+        let output_selection = serde_json::to_value(&output_selection).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "contracts/Token.sol": {
+                    "Token": ["abi", "evm.bytecode.object", "evm.deployedBytecode.object"]
+                }
+            }),
+            output_selection
+        );
+    }
+
+    #[test]
+    fn retains_only_matching_contracts() {
+        let mut output: CompilerOutput = serde_json::from_value(serde_json::json!({
+            "contracts": {
+                "A.sol": { "Token": {}, "Other": {} },
+                "B.sol": { "Token": {} },
+            }
+        }))
+        .unwrap();
+
+        ContractName::from_str("B.sol:Token")
+            .unwrap()
+            .retain_matching(&mut output);
+        assert_eq!(
+            vec![("B.sol".to_string(), "Token".to_string())],
+            output
+                .contracts
+                .iter()
+                .flat_map(|(file, contracts)| contracts
+                    .keys()
+                    .map(move |name| (file.clone(), name.clone())))
+                .collect::<Vec<_>>()
+        );
+    }
+}
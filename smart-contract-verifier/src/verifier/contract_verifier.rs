@@ -1,12 +1,24 @@
-use super::{base_verifier::Verifier, errors::BytecodeInitError};
+use super::{
+    artifacts::{ArtifactKind, Artifacts},
+    base_verifier::{MatchType, Verifier},
+    contract_name::ContractName,
+    errors::{BytecodeInitError, VerificationError, VerificationErrorKind},
+    output_selection::{required_output_selection, select_artifacts},
+    source_normalization::SourceNormalization,
+};
 use crate::{
-    compiler::{self, Compilers, EvmCompiler, Version},
-    DisplayBytes,
+    compiler::{
+        self, with_compilation_cache, CompilationCache, Compilers, EvmCompiler, ExtraSettings,
+        Version,
+    },
+    progress::{self, VerificationStage},
+    solidity, DisplayBytes,
 };
 use anyhow::anyhow;
 use bytes::Bytes;
-use ethers_solc::CompilerInput;
-use std::{ops::Add, path::PathBuf, sync::Arc};
+use ethers_core::utils::keccak256;
+use ethers_solc::{artifacts::output_selection::OutputSelection, CompilerInput};
+use std::{collections::BTreeSet, ops::Add, path::PathBuf, sync::Arc};
 use thiserror::Error;
 use tracing::instrument;
 
@@ -20,8 +32,27 @@ pub enum Error {
     Compilation(Vec<String>),
     #[error("{0}")]
     Internal(anyhow::Error),
+    /// Contains the reasons each of compiled contracts has failed to be verified
     #[error("No contract could be verified with provided data")]
-    NoMatchingContracts,
+    NoMatchingContracts(Vec<VerificationError>),
+    #[error("Contract {0} was not found in the compilation output")]
+    ContractNotFound(ContractName),
+    #[error(
+        "Contract {contract_name} does not match the deployed bytecode: {}",
+        display_errors(.errors)
+    )]
+    ContractMismatch {
+        contract_name: ContractName,
+        errors: Vec<VerificationError>,
+    },
+}
+
+fn display_errors(errors: &[VerificationError]) -> String {
+    errors
+        .iter()
+        .map(VerificationError::message)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl From<BytecodeInitError> for Error {
@@ -44,17 +75,76 @@ impl From<compiler::Error> for Error {
 #[derive(Clone, Debug)]
 pub struct Success {
     pub compiler_input: CompilerInput,
+    /// Settings used for compilation in addition to the ones specified in `compiler_input`.
+    pub extra_settings: ExtraSettings,
     pub compiler_version: Version,
     pub file_path: String,
     pub contract_name: String,
     pub abi: ethabi::Contract,
     pub constructor_args: Option<DisplayBytes>,
+    /// `true` if the contract has no deployed bytecode in the chain (e.g., self-destructed),
+    /// and only its creation transaction input has been verified.
+    pub is_destroyed: bool,
+    /// Source files which were not provided by the requester,
+    /// but have been added from the local package registry.
+    pub external_sources: Vec<PathBuf>,
+    pub match_type: MatchType,
+    /// Normalization applied to the sources provided by the requester
+    /// in order to get a full match (see [`ContractVerifier::verify_normalized`]).
+    pub source_normalization: Option<SourceNormalization>,
+    /// Compilation artifacts requested via [`ContractVerifier::with_artifacts`]
+    pub artifacts: Artifacts,
+}
+
+/// Settings the compiler has actually used, with the defaults applied by solc resolved.
+/// Vyper settings are returned as specified in the input.
+impl Success {
+    fn is_solidity(&self) -> bool {
+        self.compiler_input.language == "Solidity"
+    }
+
+    /// Returns `None` if the compiler does not support choosing EVM version (solc < 0.4.21).
+    pub fn evm_version(&self) -> Option<String> {
+        match &self.compiler_input.settings.evm_version {
+            Some(evm_version) => Some(evm_version.to_string()),
+            None if self.is_solidity() => {
+                solidity::default_evm_version(self.compiler_version.version()).map(String::from)
+            }
+            None => None,
+        }
+    }
+
+    pub fn optimization(&self) -> Option<bool> {
+        let enabled = self.compiler_input.settings.optimizer.enabled;
+        match enabled {
+            None if self.is_solidity() => Some(false),
+            enabled => enabled,
+        }
+    }
+
+    pub fn optimization_runs(&self) -> Option<usize> {
+        let runs = self.compiler_input.settings.optimizer.runs;
+        match runs {
+            None if self.is_solidity() => Some(solidity::DEFAULT_OPTIMIZER_RUNS),
+            runs => runs,
+        }
+    }
+}
+
+/// Remote bytecodes of a single deployment of the contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deployment {
+    pub creation_tx_input: Bytes,
+    /// Empty if the contract has no runtime code in the chain (see [`Success::is_destroyed`])
+    pub deployed_bytecode: Bytes,
 }
 
 pub struct ContractVerifier<'a, T> {
     compilers: Arc<Compilers<T>>,
     compiler_version: &'a Version,
-    verifier: Verifier,
+    verifiers: Vec<Verifier>,
+    contract_name: Option<ContractName>,
+    artifacts: BTreeSet<ArtifactKind>,
 }
 
 impl<'a, T: EvmCompiler> ContractVerifier<'a, T> {
@@ -63,23 +153,121 @@ impl<'a, T: EvmCompiler> ContractVerifier<'a, T> {
         compiler_version: &'a Version,
         creation_tx_input: Bytes,
         deployed_bytecode: Bytes,
+        contract_name: Option<ContractName>,
     ) -> Result<Self, Error> {
-        let verifier = Verifier::new(creation_tx_input, deployed_bytecode)?;
+        let deployment = Deployment {
+            creation_tx_input,
+            deployed_bytecode,
+        };
+        Self::for_deployments(compilers, compiler_version, vec![deployment], contract_name)
+    }
+
+    /// Initializes the verifier comparing a single compilation with each of the `deployments`
+    /// (see [`ContractVerifier::verify_deployments`]).
+    pub fn for_deployments(
+        compilers: Arc<Compilers<T>>,
+        compiler_version: &'a Version,
+        deployments: Vec<Deployment>,
+        contract_name: Option<ContractName>,
+    ) -> Result<Self, Error> {
+        if deployments.is_empty() {
+            return Err(Error::Initialization(anyhow!("no deployments to verify")));
+        }
+        // Errors are prefixed with the deployment index, unless there is only one of them
+        let single = deployments.len() == 1;
+        let verifiers = deployments
+            .into_iter()
+            .enumerate()
+            .map(|(index, deployment)| {
+                Verifier::new(deployment.creation_tx_input, deployment.deployed_bytecode).map_err(
+                    |err| {
+                        if single {
+                            Error::from(err)
+                        } else {
+                            Error::Initialization(anyhow!("deployment {}: {}", index, err))
+                        }
+                    },
+                )
+            })
+            .collect::<Result<_, _>>()?;
         Ok(Self {
             compilers,
             compiler_version,
-            verifier,
+            verifiers,
+            contract_name,
+            artifacts: BTreeSet::new(),
         })
     }
 
-    #[instrument(skip(self, compiler_input), level = "debug")]
-    pub async fn verify(&self, compiler_input: &CompilerInput) -> Result<Success, Error> {
-        let compiler_output = self
+    /// Sets the artifacts to be returned with the verification result.
+    /// The outputs they require are selected by the verifier itself.
+    pub fn with_artifacts(mut self, artifacts: BTreeSet<ArtifactKind>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Outputs the input in `language` is compiled with: the ones required for the comparison
+    /// and for the requested artifacts, selected for the named contract only if it is specified.
+    fn output_selection(&self, language: &str) -> OutputSelection {
+        let diagnostic = self.artifacts.iter().any(ArtifactKind::is_diagnostic);
+        let mut output_selection = required_output_selection(diagnostic, language);
+        select_artifacts(&mut output_selection, &self.artifacts);
+        if let Some(contract_name) = &self.contract_name {
+            contract_name.narrow_output_selection(&mut output_selection);
+        }
+        output_selection
+    }
+
+    /// Number of deployments the verifier has been initialized with.
+    pub fn deployments_len(&self) -> usize {
+        self.verifiers.len()
+    }
+
+    /// Verifies the first deployment the verifier has been initialized with.
+    pub async fn verify(
+        &self,
+        compiler_input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+    ) -> Result<Success, Error> {
+        let mut results = self
+            .verify_deployments(compiler_input, extra_settings, &[0])
+            .await?;
+        results.remove(0)
+    }
+
+    /// Compiles the input once and compares the output with each of the `deployments`
+    /// (indexes in the list the verifier has been initialized with).
+    ///
+    /// Returns an error if the input could not be compiled. Otherwise, returns
+    /// the results of the comparisons in the order of `deployments`.
+    ///
+    /// Output selection of the `compiler_input` is replaced with the one the verifier
+    /// requires for compilation, while the successful results contain the input as is.
+    ///
+    /// # Panics
+    ///
+    /// The function will panic if some index is out of the deployments bounds.
+    #[instrument(skip(self, compiler_input, extra_settings), level = "debug")]
+    pub async fn verify_deployments(
+        &self,
+        compiler_input: &CompilerInput,
+        extra_settings: &ExtraSettings,
+        deployments: &[usize],
+    ) -> Result<Vec<Result<Success, Error>>, Error> {
+        let mut compiled_input = compiler_input.clone();
+        compiled_input.settings.output_selection = self.output_selection(&compiler_input.language);
+        let mut compiler_output = self
             .compilers
-            .compile(self.compiler_version, compiler_input)
+            .compile_with_extra_settings(self.compiler_version, &compiled_input, extra_settings)
             .await?;
+        if let Some(contract_name) = &self.contract_name {
+            contract_name.retain_matching(&mut compiler_output);
+            if compiler_output.contracts.is_empty() {
+                return Err(Error::ContractNotFound(contract_name.clone()));
+            }
+        }
         let compiler_output_modified = {
-            let mut compiler_input = compiler_input.clone();
+            let mut compiler_input = compiled_input.clone();
             let entry = compiler_input
                 .settings
                 .libraries
@@ -97,25 +285,276 @@ impl<'a, T: EvmCompiler> ContractVerifier<'a, T> {
                 "0xcafecafecafecafecafecafecafecafecafecafe".into(),
             );
             self.compilers
-                .compile(self.compiler_version, &compiler_input)
+                .compile_with_extra_settings(self.compiler_version, &compiler_input, extra_settings)
                 .await?
         };
 
-        let verification_success = self
-            .verifier
-            .verify(compiler_output, compiler_output_modified)
-            .map_err(|_err| Error::NoMatchingContracts)?;
-
-        // We accept compiler input and compiler version by reference, so that we
-        // avoid their cloning if verification fails.
-        // In case of success, they will be cloned exactly once.
-        Ok(Success {
-            compiler_input: compiler_input.clone(),
-            compiler_version: self.compiler_version.clone(),
-            file_path: verification_success.file_path,
-            contract_name: verification_success.contract_name,
-            abi: verification_success.abi,
-            constructor_args: verification_success.constructor_args,
+        progress::report(VerificationStage::Comparing);
+        // Compiler messages are not required for the comparison itself
+        let compiler_errors = std::mem::take(&mut compiler_output.errors);
+        let results = deployments
+            .iter()
+            .map(|&deployment| {
+                let verifier = &self.verifiers[deployment];
+                let verification_success = verifier
+                    .verify(&compiler_output, &compiler_output_modified)
+                    .map_err(|errors| {
+                        let errors = resolve_library_placeholders(errors, compiler_input);
+                        match &self.contract_name {
+                            // The compiler output contains the named contract only,
+                            // so all errors correspond to it
+                            Some(contract_name) => Error::ContractMismatch {
+                                contract_name: contract_name.clone(),
+                                errors,
+                            },
+                            None => Error::NoMatchingContracts(errors),
+                        }
+                    })?;
+
+                // We accept compiler input, extra settings and compiler version by reference,
+                // so that we avoid their cloning if verification fails.
+                // In case of success, they will be cloned exactly once per deployment.
+                // The input is returned with the output selection specified by the caller
+                let artifacts = Artifacts::new(
+                    &self.artifacts,
+                    compiler_input,
+                    extra_settings,
+                    &compiler_errors,
+                    &verification_success.contract,
+                );
+                Ok(Success {
+                    compiler_input: compiler_input.clone(),
+                    extra_settings: extra_settings.clone(),
+                    compiler_version: self.compiler_version.clone(),
+                    file_path: verification_success.file_path,
+                    contract_name: verification_success.contract_name,
+                    abi: verification_success.abi,
+                    constructor_args: verification_success.constructor_args,
+                    is_destroyed: verifier.is_creation_only(),
+                    external_sources: vec![],
+                    match_type: verification_success.match_type,
+                    source_normalization: None,
+                    artifacts,
+                })
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Tries to turn a partial match into the full one by recompiling
+    /// the sources normalized in each of [`SourceNormalization::ALL`] ways.
+    ///
+    /// External sources of the `success` are not normalized, as they have not been
+    /// provided by the requester. Returns the original `success` if none of
+    /// the normalizations results in a full match.
+    pub async fn verify_normalized(&self, success: Success) -> Success {
+        self.verify_deployment_normalized(0, success).await
+    }
+
+    /// Same as [`ContractVerifier::verify_normalized`], but for the `success`
+    /// of the given deployment (see [`ContractVerifier::verify_deployments`]).
+    pub async fn verify_deployment_normalized(
+        &self,
+        deployment: usize,
+        success: Success,
+    ) -> Success {
+        if success.match_type == MatchType::Full {
+            return success;
+        }
+
+        for normalization in SourceNormalization::ALL {
+            let compiler_input = match normalization
+                .apply_to_input(&success.compiler_input, &success.external_sources)
+            {
+                Some(compiler_input) => compiler_input,
+                None => continue,
+            };
+            let result = self
+                .verify_deployments(&compiler_input, &success.extra_settings, &[deployment])
+                .await
+                .and_then(|mut results| results.remove(0));
+            match result {
+                Ok(normalized_success) if normalized_success.match_type == MatchType::Full => {
+                    return Success {
+                        external_sources: success.external_sources,
+                        source_normalization: Some(normalization),
+                        ..normalized_success
+                    };
+                }
+                Ok(_) => {}
+                Err(err) => tracing::debug!(
+                    %normalization,
+                    "normalized sources verification failed: {}",
+                    err
+                ),
+            }
+        }
+
+        success
+    }
+
+    /// Applies [`ContractVerifier::verify_deployment_normalized`] to each successful result,
+    /// where `results` are positioned as the deployments the verifier has been initialized with.
+    /// Sources normalized the same way are compiled once for all the deployments.
+    pub async fn verify_deployments_normalized(
+        &self,
+        results: Vec<Result<Success, Error>>,
+    ) -> Vec<Result<Success, Error>> {
+        let normalize = async {
+            let mut normalized = Vec::with_capacity(results.len());
+            for (deployment, result) in results.into_iter().enumerate() {
+                let result = match result {
+                    Ok(success) => Ok(self.verify_deployment_normalized(deployment, success).await),
+                    err => err,
+                };
+                normalized.push(result);
+            }
+            normalized
+        };
+        // A single deployment keeps using the cache of the caller, if any
+        if self.verifiers.len() == 1 {
+            return normalize.await;
+        }
+        with_compilation_cache(Arc::new(CompilationCache::new()), normalize).await
+    }
+}
+
+/// Replaces placeholders of missed libraries with `path:Name` of the libraries declared
+/// in the sources, so that the requester knows which of them should be linked.
+fn resolve_library_placeholders(
+    errors: Vec<VerificationError>,
+    compiler_input: &CompilerInput,
+) -> Vec<VerificationError> {
+    let libraries = declared_libraries(compiler_input);
+    let resolve = |placeholder: String| {
+        libraries
+            .iter()
+            .find(|library| {
+                placeholder == hash_placeholder(library)
+                    || placeholder == fully_qualified_placeholder(library)
+            })
+            .cloned()
+            .unwrap_or(placeholder)
+    };
+
+    errors
+        .into_iter()
+        .map(|error| match error.kind {
+            VerificationErrorKind::LibraryMissed(placeholders) => VerificationError {
+                kind: VerificationErrorKind::LibraryMissed(
+                    placeholders.into_iter().map(resolve).collect(),
+                ),
+                ..error
+            },
+            _ => error,
         })
+        .collect()
+}
+
+/// Returns `path:Name` of every library declared in the sources.
+fn declared_libraries(compiler_input: &CompilerInput) -> Vec<String> {
+    let mut libraries = Vec::new();
+    for (path, source) in &compiler_input.sources {
+        let words: Vec<_> = source.content.split_whitespace().collect();
+        for declaration in words.windows(2).filter(|words| words[0] == "library") {
+            let name: String = declaration[1]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+                .collect();
+            if !name.is_empty() {
+                libraries.push(format!("{}:{}", path.to_string_lossy(), name));
+            }
+        }
+    }
+    libraries
+}
+
+/// Placeholder used by solc >= 0.5.0
+fn hash_placeholder(library: &str) -> String {
+    let hash = hex::encode(keccak256(library.as_bytes()));
+    format!("__${}$__", &hash[..34])
+}
+
+/// Placeholder used by solc < 0.5.0
+fn fully_qualified_placeholder(library: &str) -> String {
+    let library: String = library.chars().take(36).collect();
+    format!("__{:_<38}", library)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mismatch::Mismatch;
+    use ethers_solc::artifacts::{Settings, Source};
+    use pretty_assertions::assert_eq;
+    use std::{collections::BTreeMap, str::FromStr};
+
+    #[test]
+    fn resolves_library_placeholders() {
+        let compiler_input = CompilerInput {
+            language: "Solidity".into(),
+            sources: BTreeMap::from([(
+                "contracts/Lib.sol".into(),
+                Source {
+                    content: "library Lib{ function f() public {} }\nlibrary Other {}".into(),
+                },
+            )]),
+            settings: Settings::default(),
+        };
+        let errors = vec![VerificationError::with_contract(
+            "contracts/Main.sol".into(),
+            "Main".into(),
+            VerificationErrorKind::LibraryMissed(vec![
+                hash_placeholder("contracts/Lib.sol:Lib"),
+                fully_qualified_placeholder("contracts/Lib.sol:Other"),
+                "__$0000000000000000000000000000000000$__".into(),
+            ]),
+        )];
+
+        let errors = resolve_library_placeholders(errors, &compiler_input);
+        assert_eq!(
+            VerificationErrorKind::LibraryMissed(vec![
+                "contracts/Lib.sol:Lib".into(),
+                "contracts/Lib.sol:Other".into(),
+                "__$0000000000000000000000000000000000$__".into(),
+            ]),
+            errors[0].kind
+        );
+    }
+
+    #[test]
+    fn library_placeholders() {
+        assert_eq!(40, hash_placeholder("contracts/Lib.sol:Lib").len());
+        assert_eq!(
+            "__contracts/Lib.sol:Lib_________________",
+            fully_qualified_placeholder("contracts/Lib.sol:Lib")
+        );
+    }
+
+    #[test]
+    fn contract_mismatch_omits_raw_bytecodes() {
+        let error = Error::ContractMismatch {
+            contract_name: ContractName::from_str("contracts/Main.sol:Main").unwrap(),
+            errors: vec![VerificationError::with_contract(
+                "contracts/Main.sol".into(),
+                "Main".into(),
+                VerificationErrorKind::BytecodeMismatch {
+                    part: Mismatch::new(
+                        DisplayBytes::from([0x60, 0x80]),
+                        DisplayBytes::from([0x60, 0x40]),
+                    ),
+                    raw: Mismatch::new(
+                        DisplayBytes::from([0x60, 0x80, 0xaa, 0xbb]),
+                        DisplayBytes::from([0x60, 0x40, 0xaa, 0xbb]),
+                    ),
+                },
+            )],
+        };
+        assert_eq!(
+            "Contract contracts/Main.sol:Main does not match the deployed bytecode: \
+            contracts/Main.sol:Main - bytecode does not match compilation output: \
+            Expected 0x6080, found 0x6040",
+            error.to_string()
+        );
     }
 }
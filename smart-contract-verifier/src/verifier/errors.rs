@@ -21,8 +21,10 @@ pub enum BytecodeInitError {
 pub enum VerificationErrorKind {
     #[error("internal error: {0}")]
     InternalError(String),
-    #[error("library missed")]
-    LibraryMissed,
+    /// Libraries are identified by `path:Name` if known,
+    /// otherwise by their placeholders in the unlinked bytecode.
+    #[error("library missed: {}", .0.join(", "))]
+    LibraryMissed(Vec<String>),
     #[error("contract is abstract")]
     AbstractContract,
     #[error("bytecode length is less than expected: {part}; bytecodes: {raw}")]
@@ -35,7 +37,7 @@ pub enum VerificationErrorKind {
         part: Mismatch<DisplayBytes>,
         raw: Mismatch<DisplayBytes>,
     },
-    #[error("cannot parse metadata")]
+    #[error("cannot parse metadata: {0}")]
     MetadataParse(String),
     #[error("compiler versions included into metadata hash does not match: {0:?}")]
     CompilerVersionMismatch(Mismatch<Option<String>>),
@@ -43,6 +45,38 @@ pub enum VerificationErrorKind {
     InvalidConstructorArguments(DisplayBytes),
 }
 
+impl VerificationErrorKind {
+    /// Machine-readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            VerificationErrorKind::InternalError(_) => "internal_error",
+            VerificationErrorKind::LibraryMissed(_) => "library_missed",
+            VerificationErrorKind::AbstractContract => "abstract_contract",
+            VerificationErrorKind::BytecodeLengthMismatch { .. } => "bytecode_length_mismatch",
+            VerificationErrorKind::BytecodeMismatch { .. } => "bytecode_mismatch",
+            VerificationErrorKind::MetadataParse(_) => "metadata_parse",
+            VerificationErrorKind::CompilerVersionMismatch(_) => "compiler_version_mismatch",
+            VerificationErrorKind::InvalidConstructorArguments(_) => {
+                "invalid_constructor_arguments"
+            }
+        }
+    }
+
+    /// Human-readable description of the error. Unlike `Display` implementation,
+    /// omits the full bytecodes compared, which may be arbitrarily large.
+    pub fn message(&self) -> String {
+        match self {
+            VerificationErrorKind::BytecodeLengthMismatch { part, .. } => {
+                format!("bytecode length is less than expected: {}", part)
+            }
+            VerificationErrorKind::BytecodeMismatch { part, .. } => {
+                format!("bytecode does not match compilation output: {}", part)
+            }
+            kind => kind.to_string(),
+        }
+    }
+}
+
 /// Error obtained as a result of a single contract verification.
 /// Is used to return more details about verification process to the caller.
 ///
@@ -88,4 +122,49 @@ impl VerificationError {
             kind,
         }
     }
+
+    /// Same as `Display` implementation, but describes the error kind
+    /// via [`VerificationErrorKind::message`].
+    pub fn message(&self) -> String {
+        match &self.contract_name {
+            None => format!("{} - {}", self.file_path, self.kind.message()),
+            Some(name) => format!("{}:{} - {}", self.file_path, name, self.kind.message()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn message_omits_raw_bytecodes() {
+        let kind = VerificationErrorKind::BytecodeMismatch {
+            part: Mismatch::new(
+                DisplayBytes::from([0x60, 0x80]),
+                DisplayBytes::from([0x60, 0x40]),
+            ),
+            raw: Mismatch::new(
+                DisplayBytes::from([0x60, 0x80, 0xaa, 0xbb]),
+                DisplayBytes::from([0x60, 0x40, 0xaa, 0xbb]),
+            ),
+        };
+        assert_eq!(
+            "bytecode does not match compilation output: Expected 0x6080, found 0x6040",
+            kind.message()
+        );
+
+        let kind = VerificationErrorKind::BytecodeLengthMismatch {
+            part: Mismatch::new(4, 2),
+            raw: Mismatch::new(
+                DisplayBytes::from([0x60, 0x80, 0xaa, 0xbb]),
+                DisplayBytes::from([0x60, 0x80]),
+            ),
+        };
+        assert_eq!(
+            "bytecode length is less than expected: Expected 4, found 2",
+            kind.message()
+        );
+    }
 }
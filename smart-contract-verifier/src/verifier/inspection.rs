@@ -0,0 +1,302 @@
+//! Structure of the bytecode which may be obtained without any compilation.
+
+use super::metadata::MetadataHash;
+use bytes::Bytes;
+
+/// CBOR encoded metadata hash appended to the bytecode by the compiler
+/// (https://docs.soliditylang.org/en/v0.8.14/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataSection {
+    /// Offset of the CBOR encoded metadata in the bytecode
+    pub offset: usize,
+    /// Length of the CBOR encoded metadata, not including two bytes of the length itself
+    pub length: usize,
+    pub compiler_version: Option<String>,
+    /// CIDv0 of the metadata file uploaded to IPFS
+    pub ipfs: Option<String>,
+    pub bzzr0: Option<Bytes>,
+    pub bzzr1: Option<Bytes>,
+}
+
+/// Known proxy patterns the bytecode may implement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProxyPattern {
+    /// EIP-1167 minimal proxy delegating all calls to the hardcoded implementation
+    MinimalProxy { implementation: Bytes },
+    /// Uses EIP-1967 implementation slot
+    Eip1967,
+    /// Uses EIP-1967 beacon slot
+    Eip1967Beacon,
+    /// Uses EIP-1822 (UUPS) proxiable slot
+    Eip1822,
+    /// Uses `org.zeppelinos.proxy.implementation` slot of legacy OpenZeppelin proxies
+    ZeppelinOs,
+}
+
+const MINIMAL_PROXY_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = [
+    0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3,
+];
+
+const PUSH32: u8 = 0x7f;
+const PROXY_SLOTS: [(&str, ProxyPattern); 4] = [
+    (
+        "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+        ProxyPattern::Eip1967,
+    ),
+    (
+        "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50",
+        ProxyPattern::Eip1967Beacon,
+    ),
+    (
+        "c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7",
+        ProxyPattern::Eip1822,
+    ),
+    (
+        "7050c9e0f4ca769c69bd3a8ef740bc37934f8e2c036e5a723fd8ee048ed3f8c3",
+        ProxyPattern::ZeppelinOs,
+    ),
+];
+
+/// Structure of the creation transaction input or deployed bytecode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytecodeInspection {
+    pub metadata_sections: Vec<MetadataSection>,
+    /// Compiler version specified in the last metadata section containing it
+    pub compiler_version: Option<String>,
+    pub proxy_patterns: Vec<ProxyPattern>,
+    /// Bytes following the last metadata section of the creation transaction input,
+    /// which are likely to be ABI encoded constructor arguments
+    pub constructor_args_candidate: Option<Bytes>,
+}
+
+impl BytecodeInspection {
+    pub fn creation_tx_input(raw: &Bytes) -> Self {
+        let mut inspection = Self::new(raw);
+        inspection.constructor_args_candidate = inspection
+            .metadata_sections
+            .last()
+            .map(|section| section.offset + section.length + 2)
+            .filter(|&end| end < raw.len())
+            .map(|end| raw.slice(end..));
+        inspection
+    }
+
+    pub fn deployed_bytecode(raw: &Bytes) -> Self {
+        Self::new(raw)
+    }
+
+    fn new(raw: &Bytes) -> Self {
+        let metadata_sections = metadata_sections(raw);
+        let compiler_version = metadata_sections
+            .iter()
+            .rev()
+            .find_map(|section| section.compiler_version.clone());
+        Self {
+            metadata_sections,
+            compiler_version,
+            proxy_patterns: proxy_patterns(raw),
+            constructor_args_candidate: None,
+        }
+    }
+}
+
+/// Looks for CBOR maps followed by their two bytes length,
+/// which contain at least one of the keys the compiler appends.
+fn metadata_sections(raw: &[u8]) -> Vec<MetadataSection> {
+    // CBOR map header with 1 to 5 elements
+    let is_map_header = |byte: u8| (0xa1..=0xa5).contains(&byte);
+
+    let mut sections = Vec::new();
+    let mut i = 0;
+    while i < raw.len() {
+        if is_map_header(raw[i]) {
+            if let Some(section) = metadata_section(raw, i) {
+                i += section.length + 2;
+                sections.push(section);
+                continue;
+            }
+        }
+        i += 1;
+    }
+    sections
+}
+
+fn metadata_section(raw: &[u8], offset: usize) -> Option<MetadataSection> {
+    let (metadata, length) = MetadataHash::from_cbor(&raw[offset..]).ok()?;
+    let length_raw = raw.get(offset + length..offset + length + 2)?;
+    if u16::from_be_bytes([length_raw[0], length_raw[1]]) as usize != length {
+        return None;
+    }
+    if metadata == MetadataHash::default() {
+        return None;
+    }
+
+    let compiler_version = metadata.solc.map(|solc| match solc.as_ref() {
+        // released versions are encoded as three bytes: major, minor and patch
+        [major, minor, patch] => format!("{}.{}.{}", major, minor, patch),
+        _ => String::from_utf8_lossy(&solc).to_string(),
+    });
+    Some(MetadataSection {
+        offset,
+        length,
+        compiler_version,
+        ipfs: metadata.ipfs.map(|multihash| base58(&multihash)),
+        bzzr0: metadata.bzzr0,
+        bzzr1: metadata.bzzr1,
+    })
+}
+
+fn proxy_patterns(raw: &[u8]) -> Vec<ProxyPattern> {
+    let mut patterns = Vec::new();
+
+    let minimal_proxy_length = MINIMAL_PROXY_PREFIX.len() + 20 + MINIMAL_PROXY_SUFFIX.len();
+    if let Some(proxy) = raw.windows(minimal_proxy_length).find(|window| {
+        window.starts_with(&MINIMAL_PROXY_PREFIX) && window.ends_with(&MINIMAL_PROXY_SUFFIX)
+    }) {
+        let implementation = &proxy[MINIMAL_PROXY_PREFIX.len()..MINIMAL_PROXY_PREFIX.len() + 20];
+        patterns.push(ProxyPattern::MinimalProxy {
+            implementation: Bytes::copy_from_slice(implementation),
+        });
+    }
+
+    for (slot, pattern) in PROXY_SLOTS {
+        let push_slot = [
+            vec![PUSH32],
+            hex::decode(slot).expect("slots are valid hex strings"),
+        ]
+        .concat();
+        if raw
+            .windows(push_slot.len())
+            .any(|window| window == push_slot)
+        {
+            patterns.push(pattern);
+        }
+    }
+
+    patterns
+}
+
+/// Base58 encoding with the bitcoin alphabet used by IPFS.
+fn base58(raw: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    // base58 digits in little-endian order
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in raw {
+        let mut carry = byte as usize;
+        for digit in digits.iter_mut() {
+            carry += (*digit as usize) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let leading_zeros = raw.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat('1')
+        .take(leading_zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| ALPHABET[digit as usize] as char),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DisplayBytes;
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    // { "ipfs": b"1220BCC988B1311237F2C00CCD0BFBD8B01D24DC18F720603B0DE93FE6327DF53625", "solc": b'00080e' }
+    const METADATA: &str = "a2646970667358221220bcc988b1311237f2c00ccd0bfbd8b01d24dc18f720603b0de93fe6327df5362564736f6c634300080e0033";
+
+    fn bytes(hex: &str) -> Bytes {
+        DisplayBytes::from_str(hex).expect("Invalid hex").0
+    }
+
+    #[test]
+    fn inspect_deployed_bytecode() {
+        let raw = bytes(&format!("6080604052600080fd{}", METADATA));
+
+        let inspection = BytecodeInspection::deployed_bytecode(&raw);
+        assert_eq!(
+            vec![MetadataSection {
+                offset: 9,
+                length: 51,
+                compiler_version: Some("0.8.14".into()),
+                ipfs: Some("Qmb3bbRhqQGFeTxDDMmDPcr2sZrKJTB9L3qKGhkcWaBxZi".into()),
+                bzzr0: None,
+                bzzr1: None,
+            }],
+            inspection.metadata_sections
+        );
+        assert_eq!(Some("0.8.14".into()), inspection.compiler_version);
+        assert!(inspection.proxy_patterns.is_empty());
+        assert_eq!(None, inspection.constructor_args_candidate);
+    }
+
+    #[test]
+    fn inspect_creation_tx_input() {
+        let runtime = format!("6080604052600080fd{}", METADATA);
+        let constructor_args = "000000000000000000000000000000000000000000000000000000000000002a";
+        let raw = bytes(&format!("60806040{}{}", runtime, constructor_args));
+
+        let inspection = BytecodeInspection::creation_tx_input(&raw);
+        assert_eq!(1, inspection.metadata_sections.len());
+        assert_eq!(
+            Some(bytes(constructor_args)),
+            inspection.constructor_args_candidate
+        );
+
+        let inspection =
+            BytecodeInspection::creation_tx_input(&bytes(&format!("60806040{}", runtime)));
+        assert_eq!(
+            None, inspection.constructor_args_candidate,
+            "Creation input without constructor arguments"
+        );
+    }
+
+    #[test]
+    fn inspect_bytecode_without_metadata() {
+        let inspection = BytecodeInspection::deployed_bytecode(&bytes("6080604052a1600080fd"));
+        assert!(inspection.metadata_sections.is_empty());
+        assert_eq!(None, inspection.compiler_version);
+    }
+
+    #[test]
+    fn detect_proxy_patterns() {
+        let implementation = "bebebebebebebebebebebebebebebebebebebebe";
+        let minimal_proxy = bytes(&format!(
+            "363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3",
+            implementation
+        ));
+        assert_eq!(
+            vec![ProxyPattern::MinimalProxy {
+                implementation: bytes(implementation)
+            }],
+            BytecodeInspection::deployed_bytecode(&minimal_proxy).proxy_patterns
+        );
+
+        let eip1967_proxy =
+            bytes("60806040527f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc54");
+        assert_eq!(
+            vec![ProxyPattern::Eip1967],
+            BytecodeInspection::deployed_bytecode(&eip1967_proxy).proxy_patterns
+        );
+    }
+
+    #[test]
+    fn base58_encoding() {
+        assert_eq!("", base58(&[]));
+        assert_eq!("11", base58(&[0, 0]));
+        assert_eq!("5Q", base58(&[0xff]));
+    }
+}
@@ -4,10 +4,14 @@ use thiserror::Error;
 /// Parsed metadata hash
 /// (https://docs.soliditylang.org/en/v0.8.14/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode).
 ///
-/// Currently we are interested only in `solc` value.
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Only `solc` value is used for verification.
+/// Source hashes are decoded to be reported on bytecode inspection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MetadataHash {
     pub solc: Option<bytes::Bytes>,
+    pub ipfs: Option<bytes::Bytes>,
+    pub bzzr0: Option<bytes::Bytes>,
+    pub bzzr1: Option<bytes::Bytes>,
 }
 
 impl MetadataHash {
@@ -42,6 +46,7 @@ impl<'b> Decode<'b, DecodeContext> for MetadataHash {
         let number_of_elements = d.map()?.unwrap_or(u64::MAX);
 
         let mut solc = None;
+        let (mut ipfs, mut bzzr0, mut bzzr1) = (None, None, None);
         for _ in 0..number_of_elements {
             // try to parse the key
             match d.str() {
@@ -64,6 +69,18 @@ impl<'b> Decode<'b, DecodeContext> for MetadataHash {
                         }
                     }
                 }
+                Ok(s) if ["ipfs", "bzzr0", "bzzr1"].contains(&s) => {
+                    let hash = match s {
+                        "ipfs" => &mut ipfs,
+                        "bzzr0" => &mut bzzr0,
+                        _ => &mut bzzr1,
+                    };
+                    match d.datatype()? {
+                        Type::Bytes => *hash = Some(bytes::Bytes::copy_from_slice(d.bytes()?)),
+                        // hashes are not used for verification, so unexpected values are ignored
+                        _ => d.skip()?,
+                    }
+                }
                 Ok(_) => {
                     // if key is not "solc" str we may skip the corresponding value
                     d.skip()?;
@@ -78,11 +95,16 @@ impl<'b> Decode<'b, DecodeContext> for MetadataHash {
         ctx.used_size = d.position();
 
         let solc = solc.map(bytes::Bytes::copy_from_slice);
-        Ok(MetadataHash { solc })
+        Ok(MetadataHash {
+            solc,
+            ipfs,
+            bzzr0,
+            bzzr1,
+        })
     }
 
     fn nil() -> Option<Self> {
-        Some(Self { solc: None })
+        Some(Self::default())
     }
 }
 
@@ -117,7 +139,16 @@ mod metadata_hash_deserialization_tests {
         let hex =
             "a165627a7a72305820d4fba422541feba2d648f6657d9354ec14ea9f5919b520abe0feb60981d7b17c";
         let encoded = DisplayBytes::from_str(hex).unwrap().0;
-        let expected = MetadataHash { solc: None };
+        let expected = MetadataHash {
+            bzzr0: Some(
+                DisplayBytes::from_str(
+                    "d4fba422541feba2d648f6657d9354ec14ea9f5919b520abe0feb60981d7b17c",
+                )
+                .unwrap()
+                .0,
+            ),
+            ..Default::default()
+        };
         let expected_size = encoded.len();
 
         // when
@@ -137,6 +168,14 @@ mod metadata_hash_deserialization_tests {
         let encoded = DisplayBytes::from_str(hex).unwrap().0;
         let expected = MetadataHash {
             solc: Some("\u{0}\u{8}\u{e}".as_bytes().into()),
+            ipfs: Some(
+                DisplayBytes::from_str(
+                    "1220bcc988b1311237f2c00ccd0bfbd8b01d24dc18f720603b0de93fe6327df53625",
+                )
+                .unwrap()
+                .0,
+            ),
+            ..Default::default()
         };
         let expected_size = encoded.len();
 
@@ -157,6 +196,14 @@ mod metadata_hash_deserialization_tests {
         let encoded = DisplayBytes::from_str(hex).unwrap().0;
         let expected = MetadataHash {
             solc: Some("0.8.15-ci.2022.5.23+commit.21591531".as_bytes().into()),
+            ipfs: Some(
+                DisplayBytes::from_str(
+                    "1220ba5af27fe13bc83e671bd6981216d35df49ab3ac923741b8948b277f93fbf732",
+                )
+                .unwrap()
+                .0,
+            ),
+            ..Default::default()
         };
         let expected_size = encoded.len();
 
@@ -181,6 +228,14 @@ mod metadata_hash_deserialization_tests {
         let encoded = DisplayBytes::from_str(&hex).unwrap().0;
         let expected = MetadataHash {
             solc: Some("\u{0}\u{8}\u{e}".as_bytes().into()),
+            ipfs: Some(
+                DisplayBytes::from_str(
+                    "1220bcc988b1311237f2c00ccd0bfbd8b01d24dc18f720603b0de93fe6327df53625",
+                )
+                .unwrap()
+                .0,
+            ),
+            ..Default::default()
         };
         let expected_size = DisplayBytes::from_str(first).unwrap().0.len();
 
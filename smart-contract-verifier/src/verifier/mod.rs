@@ -4,6 +4,17 @@ mod bytecode;
 mod errors;
 mod metadata;
 
+mod artifacts;
+mod contract_name;
 mod contract_verifier;
+mod inspection;
+mod output_selection;
+mod source_normalization;
 
-pub use contract_verifier::{ContractVerifier, Error, Success};
+pub use artifacts::{ArtifactKind, Artifacts, ParseArtifactKindError};
+pub use base_verifier::MatchType;
+pub use contract_name::{ContractName, ParseContractNameError};
+pub use contract_verifier::{ContractVerifier, Deployment, Error, Success};
+pub use errors::{VerificationError as ContractVerificationError, VerificationErrorKind};
+pub use inspection::{BytecodeInspection, MetadataSection, ProxyPattern};
+pub use source_normalization::SourceNormalization;
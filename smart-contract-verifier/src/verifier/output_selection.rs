@@ -0,0 +1,144 @@
+use super::artifacts::ArtifactKind;
+use ethers_solc::artifacts::output_selection::OutputSelection;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Outputs required to compare the bytecode and to decode constructor arguments.
+const REQUIRED_OUTPUTS: [&str; 3] = ["abi", "evm.bytecode.object", "evm.deployedBytecode.object"];
+
+/// Outputs which are not used for the comparison, but describe how the contract
+/// has been compiled (metadata) and which code the bytecode corresponds to (source maps).
+const DIAGNOSTIC_OUTPUTS: [&str; 3] = [
+    "metadata",
+    "evm.bytecode.sourceMap",
+    "evm.deployedBytecode.sourceMap",
+];
+
+/// Vyper rejects the selection of metadata and of the creation bytecode source map
+/// as invalid, so only the source map of the deployed bytecode is selected for it.
+const VYPER_DIAGNOSTIC_OUTPUTS: [&str; 1] = ["evm.deployedBytecode.sourceMap"];
+
+/// Specifies which outputs the compiler should produce for every contract.
+///
+/// Only the outputs required for verification are selected,
+/// so that the compiler does not spend time on ASTs, method identifiers, etc.
+/// Metadata and source maps are added in the `diagnostic` mode only
+/// (see [`ArtifactKind::is_diagnostic`]), depending on the `language` of the compiler input.
+/// The outputs of the rest of the requested artifacts are added via [`select_artifacts`].
+pub fn required_output_selection(diagnostic: bool, language: &str) -> OutputSelection {
+    let mut outputs = REQUIRED_OUTPUTS.map(String::from).to_vec();
+    if diagnostic {
+        let diagnostic_outputs: &[&str] = match language {
+            "Vyper" => &VYPER_DIAGNOSTIC_OUTPUTS,
+            _ => &DIAGNOSTIC_OUTPUTS,
+        };
+        outputs.extend(diagnostic_outputs.iter().map(|output| output.to_string()));
+    }
+
+    OutputSelection(BTreeMap::from([(
+        "*".to_string(),
+        BTreeMap::from([("*".to_string(), outputs)]),
+    )]))
+}
+
+/// Adds the outputs required to obtain the `artifacts` to every selected contract.
+pub fn select_artifacts(
+    output_selection: &mut OutputSelection,
+    artifacts: &BTreeSet<ArtifactKind>,
+) {
+    let artifact_outputs = artifacts.iter().flat_map(|artifact| artifact.outputs());
+    for contract_outputs in output_selection
+        .0
+        .values_mut()
+        .flat_map(|file_selection| file_selection.values_mut())
+    {
+        for output in artifact_outputs.clone() {
+            if !contract_outputs.iter().any(|selected| selected == output) {
+                contract_outputs.push(output.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn minimal_output_selection() {
+        let output_selection = serde_json::to_value(required_output_selection(false, "Solidity"))
+            .expect("Serializable output selection");
+        assert_eq!(
+            serde_json::json!({
+                "*": {
+                    "*": ["abi", "evm.bytecode.object", "evm.deployedBytecode.object"]
+                }
+            }),
+            output_selection
+        );
+    }
+
+    #[test]
+    fn diagnostic_output_selection() {
+        let output_selection = serde_json::to_value(required_output_selection(true, "Solidity"))
+            .expect("Serializable output selection");
+        assert_eq!(
+            serde_json::json!({
+                "*": {
+                    "*": [
+                        "abi",
+                        "evm.bytecode.object",
+                        "evm.deployedBytecode.object",
+                        "metadata",
+                        "evm.bytecode.sourceMap",
+                        "evm.deployedBytecode.sourceMap"
+                    ]
+                }
+            }),
+            output_selection
+        );
+    }
+
+    #[test]
+    fn vyper_diagnostic_output_selection() {
+        let output_selection = serde_json::to_value(required_output_selection(true, "Vyper"))
+            .expect("Serializable output selection");
+        assert_eq!(
+            serde_json::json!({
+                "*": {
+                    "*": [
+                        "abi",
+                        "evm.bytecode.object",
+                        "evm.deployedBytecode.object",
+                        "evm.deployedBytecode.sourceMap"
+                    ]
+                }
+            }),
+            output_selection
+        );
+    }
+
+    #[test]
+    fn artifacts_output_selection() {
+        let mut output_selection = required_output_selection(false, "Solidity");
+        select_artifacts(
+            &mut output_selection,
+            &BTreeSet::from([ArtifactKind::Warnings, ArtifactKind::MethodIdentifiers]),
+        );
+        let output_selection =
+            serde_json::to_value(output_selection).expect("Serializable output selection");
+        assert_eq!(
+            serde_json::json!({
+                "*": {
+                    "*": [
+                        "abi",
+                        "evm.bytecode.object",
+                        "evm.deployedBytecode.object",
+                        "evm.methodIdentifiers"
+                    ]
+                }
+            }),
+            output_selection
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use ethers_solc::{artifacts::Source, CompilerInput};
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// Transformations of the source code which do not affect the executable code,
+/// but change the metadata hash appended to the bytecode.
+///
+/// Sources copied through web forms often have their line endings
+/// or trailing newlines changed, which results in a partial match only.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceNormalization {
+    /// Replaces CRLF line endings with LF.
+    Lf,
+    /// Replaces LF line endings with CRLF.
+    Crlf,
+    /// Removes whitespaces at the end of every line.
+    TrimTrailingWhitespace,
+    /// Appends a newline to the files not ending with one.
+    AddTrailingNewline,
+    /// Removes a single newline at the end of the files.
+    RemoveTrailingNewline,
+}
+
+impl SourceNormalization {
+    /// Normalizations are sorted by their probability of resulting in a full match
+    pub const ALL: [Self; 5] = [
+        Self::Lf,
+        Self::Crlf,
+        Self::AddTrailingNewline,
+        Self::RemoveTrailingNewline,
+        Self::TrimTrailingWhitespace,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "lf",
+            Self::Crlf => "crlf",
+            Self::TrimTrailingWhitespace => "trim_trailing_whitespace",
+            Self::AddTrailingNewline => "add_trailing_newline",
+            Self::RemoveTrailingNewline => "remove_trailing_newline",
+        }
+    }
+
+    pub fn apply(&self, source: &str) -> String {
+        match self {
+            Self::Lf => source.replace("\r\n", "\n"),
+            Self::Crlf => source.replace("\r\n", "\n").replace('\n', "\r\n"),
+            Self::TrimTrailingWhitespace => source
+                .split_inclusive('\n')
+                .map(|line| {
+                    let content = line.trim_end_matches(['\r', '\n']);
+                    let line_ending = &line[content.len()..];
+                    format!("{}{}", content.trim_end(), line_ending)
+                })
+                .collect(),
+            Self::AddTrailingNewline if source.ends_with('\n') => source.to_string(),
+            Self::AddTrailingNewline => {
+                let line_ending = if source.contains("\r\n") {
+                    "\r\n"
+                } else {
+                    "\n"
+                };
+                format!("{}{}", source, line_ending)
+            }
+            Self::RemoveTrailingNewline => source
+                .strip_suffix("\r\n")
+                .or_else(|| source.strip_suffix('\n'))
+                .unwrap_or(source)
+                .to_string(),
+        }
+    }
+
+    /// Normalizes all sources of the input except for the `skipped` ones.
+    /// Returns `None` if none of the sources has been changed.
+    pub fn apply_to_input(
+        &self,
+        input: &CompilerInput,
+        skipped: &[PathBuf],
+    ) -> Option<CompilerInput> {
+        let mut normalized = input.clone();
+        let mut changed = false;
+        for (path, source) in normalized.sources.iter_mut() {
+            if skipped.contains(path) {
+                continue;
+            }
+            let content = self.apply(&source.content);
+            if content != source.content {
+                *source = Source { content };
+                changed = true;
+            }
+        }
+        changed.then_some(normalized)
+    }
+}
+
+impl Display for SourceNormalization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_solc::artifacts::Settings;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn apply_normalizations() {
+        let source = "contract A {  \r\n}\r\n";
+        assert_eq!("contract A {  \n}\n", SourceNormalization::Lf.apply(source));
+        assert_eq!(
+            "contract A {\r\n}\r\n",
+            SourceNormalization::TrimTrailingWhitespace.apply(source)
+        );
+        assert_eq!(
+            "contract A {  \r\n}",
+            SourceNormalization::RemoveTrailingNewline.apply(source)
+        );
+        assert_eq!(
+            source,
+            SourceNormalization::AddTrailingNewline.apply(source),
+            "Newline should not be added if already present"
+        );
+
+        let source = "contract A {\n}";
+        assert_eq!("contract A {\r\n}", SourceNormalization::Crlf.apply(source));
+        assert_eq!(
+            "contract A {\n}\n",
+            SourceNormalization::AddTrailingNewline.apply(source)
+        );
+    }
+
+    #[test]
+    fn apply_to_input() {
+        let input = CompilerInput {
+            language: "Solidity".into(),
+            sources: BTreeMap::from([
+                (
+                    "A.sol".into(),
+                    Source {
+                        content: "contract A {}\r\n".into(),
+                    },
+                ),
+                (
+                    "lib/B.sol".into(),
+                    Source {
+                        content: "contract B {}\r\n".into(),
+                    },
+                ),
+            ]),
+            settings: Settings::default(),
+        };
+
+        let normalized = SourceNormalization::Lf
+            .apply_to_input(&input, &["lib/B.sol".into()])
+            .expect("Sources should be changed");
+        assert_eq!(
+            "contract A {}\n",
+            normalized.sources[&PathBuf::from("A.sol")].content
+        );
+        assert_eq!(
+            "contract B {}\r\n",
+            normalized.sources[&PathBuf::from("lib/B.sol")].content,
+            "Skipped sources should not be changed"
+        );
+
+        assert!(
+            SourceNormalization::AddTrailingNewline
+                .apply_to_input(&input, &[])
+                .is_none(),
+            "Unchanged sources should not result in a new input"
+        );
+    }
+}
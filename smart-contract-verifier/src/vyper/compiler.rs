@@ -1,4 +1,4 @@
-use crate::compiler::{EvmCompiler, Version};
+use crate::compiler::{EvmCompiler, ExtraSettings, Version};
 use ethers_solc::{error::SolcError, CompilerInput, CompilerOutput, Solc};
 use std::path::Path;
 
@@ -18,6 +18,7 @@ impl EvmCompiler for VyperCompiler {
         path: &Path,
         _ver: &Version,
         input: &CompilerInput,
+        _extra_settings: &ExtraSettings,
     ) -> Result<CompilerOutput, SolcError> {
         let vyper_output: types::VyperCompilerOutput = Solc::from(path).compile_as(input)?;
         Ok(CompilerOutput::from(vyper_output))
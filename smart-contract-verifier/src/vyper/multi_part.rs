@@ -1,19 +1,31 @@
 use super::compiler::VyperCompiler;
 use crate::{
-    compiler::{Compilers, Version},
-    verifier::{ContractVerifier, Error, Success},
+    compiler::{Compilers, ExtraSettings, Version},
+    verifier::{ArtifactKind, ContractName, ContractVerifier, Deployment, Error, Success},
 };
 use bytes::Bytes;
 use ethers_solc::{
     artifacts::{Settings, Source, Sources},
     CompilerInput, EvmVersion,
 };
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::Arc,
+};
 
 pub struct VerificationRequest {
     pub deployed_bytecode: Bytes,
     pub creation_bytecode: Bytes,
     pub compiler_version: Version,
+    /// If specified, only that contract is compared with the remote bytecode.
+    /// Vyper contracts are named after their files (without extension)
+    pub contract_name: Option<ContractName>,
+    /// If the match is partial, try to get the full one by normalizing the sources
+    pub normalize_sources: bool,
+    /// Compilation artifacts to be returned with the successful result.
+    /// Solidity only artifacts are not returned
+    pub artifacts: BTreeSet<ArtifactKind>,
 
     pub content: MultiFileContent,
 }
@@ -52,13 +64,41 @@ pub async fn verify(
     compilers: Arc<Compilers<VyperCompiler>>,
     request: VerificationRequest,
 ) -> Result<Success, Error> {
+    let deployment = Deployment {
+        creation_tx_input: request.creation_bytecode.clone(),
+        deployed_bytecode: request.deployed_bytecode.clone(),
+    };
+    let mut results = verify_deployments(compilers, request, vec![deployment]).await?;
+    results.remove(0)
+}
+
+/// Verifies the sources of the `request` against each of the `deployments`
+/// (instead of the bytecodes specified in the request), compiling them once.
+///
+/// Returns an error if the sources could not be compiled. Otherwise, returns
+/// the results of the verifications in the order of `deployments`.
+pub async fn verify_deployments(
+    compilers: Arc<Compilers<VyperCompiler>>,
+    request: VerificationRequest,
+    deployments: Vec<Deployment>,
+) -> Result<Vec<Result<Success, Error>>, Error> {
+    let mut artifacts = request.artifacts;
+    artifacts.retain(|artifact| !artifact.is_solidity_only());
     let compiler_input = CompilerInput::from(request.content);
-    let verifier = ContractVerifier::new(
+    let verifier = ContractVerifier::for_deployments(
         compilers,
         &request.compiler_version,
-        request.creation_bytecode,
-        request.deployed_bytecode,
-    )?;
+        deployments,
+        request.contract_name,
+    )?
+    .with_artifacts(artifacts);
 
-    verifier.verify(&compiler_input).await
+    let deployments: Vec<_> = (0..verifier.deployments_len()).collect();
+    let results = verifier
+        .verify_deployments(&compiler_input, &ExtraSettings::default(), &deployments)
+        .await?;
+    if request.normalize_sources {
+        return Ok(verifier.verify_deployments_normalized(results).await);
+    }
+    Ok(results)
 }